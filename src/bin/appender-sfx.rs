@@ -0,0 +1,36 @@
+//! 自解压程序存根
+//!
+//! 由 `Appender sfx` 复制并附加资源后运行：释放资源到指定目录(第一个参数)或默认目录，
+//! 再执行自解压配置中的命令。
+
+use appender::sfx::{extract_sfx, run_post_extract};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let destination = std::env::args_os().nth(1).map(PathBuf::from);
+    let result = std::env::current_exe()
+        .map_err(Into::into)
+        .and_then(|executable| extract_sfx(&executable, destination.as_deref(), &mut |_| {}));
+    let (directory, manifest) = match result {
+        Ok(extracted) => extracted,
+        Err(e) => {
+            eprintln!("Extraction failed: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    println!(
+        "Extracted {} file(s) to \"{}\"",
+        manifest.resources.len(),
+        directory.display()
+    );
+
+    if let Some(command) = &manifest.run {
+        println!("Running: {}", command);
+        if let Err(e) = run_post_extract(command, &directory) {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    }
+    ExitCode::SUCCESS
+}
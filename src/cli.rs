@@ -6,6 +6,9 @@ use std::path::PathBuf;
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+    /// Suppress progress bars and status messages
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
 }
 
 #[derive(Subcommand, Debug)]
@@ -15,46 +18,225 @@ pub enum Commands {
         /// Target file path
         #[arg(value_parser = validate_path)]
         target_file: PathBuf,
-        /// Resource ID to filter (optional)
+        /// Resource ID to filter (optional, `outer/inner` for nested resources)
         #[arg(short, long)]
         id: Option<String>,
+        /// Only list resources whose ID matches a glob (`*`, `**`, `?`, `[a-z]`)
+        #[arg(short = 'm', long = "match", value_name = "GLOB", conflicts_with_all = ["id", "regex"])]
+        glob: Option<String>,
+        /// Only list resources whose ID matches a regular expression
+        #[arg(long, value_name = "REGEX", conflicts_with = "id")]
+        regex: Option<String>,
+        /// Also list resources nested inside resources
+        #[arg(short, long)]
+        recursive: bool,
+        /// Operate on the sidecar file (`<target>.overlay`) bound to the target instead of the target itself
+        #[arg(long)]
+        sidecar: bool,
     },
     /// Add resources
     Add {
         /// Target file path
         #[arg(value_parser = validate_path)]
         target_file: PathBuf,
-        /// Resource file path
+        /// Resource file path (`-` to read from stdin)
+        #[arg(value_parser = validate_input_path)]
+        resources: PathBuf,
+        /// Resource ID
+        id: String,
+        /// New file path (optional)
+        new_file_path: Option<PathBuf>,
+        /// Stored file name (defaults to the resource file name, or the ID when reading stdin)
+        #[arg(short, long)]
+        name: Option<String>,
+        /// Compression level (0-9)
+        #[arg(short, long, default_value = "1")]
+        compression: u32,
+        /// Compression threads (0 = all cores); more than one writes concatenated gzip members
+        #[arg(long, default_value = "1")]
+        threads: usize,
+        /// Custom tag in `key=value` form (repeatable)
+        #[arg(short, long = "tag", value_parser = parse_tag)]
+        tags: Vec<(String, String)>,
+        /// Store the payload as content-defined chunks shared with other chunked resources
+        #[arg(long)]
+        chunked: bool,
+        /// Maximum stored bytes per file (e.g. `3G`); the rest goes to volume files next to the target
+        #[arg(long, value_parser = parse_size, conflicts_with = "chunked")]
+        split_size: Option<u64>,
+        /// Operate on the sidecar file (`<target>.overlay`) bound to the target instead of the target itself
+        #[arg(long, conflicts_with = "new_file_path")]
+        sidecar: bool,
+        /// Validate and print the planned changes without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Replace the data of an existing resource with a new version
+    Update {
+        /// Target file path
+        #[arg(value_parser = validate_path)]
+        target_file: PathBuf,
+        /// New version of the resource file
         #[arg(value_parser = validate_path)]
         resources: PathBuf,
         /// Resource ID
         id: String,
         /// New file path (optional)
         new_file_path: Option<PathBuf>,
+        /// Stored file name (defaults to the resource file name)
+        #[arg(short, long)]
+        name: Option<String>,
         /// Compression level (0-9)
         #[arg(short, long, default_value = "1")]
         compression: u32,
+        /// Custom tag in `key=value` form (repeatable)
+        #[arg(short, long = "tag", value_parser = parse_tag)]
+        tags: Vec<(String, String)>,
+        /// Store the new version as a binary diff against the current one
+        #[arg(long)]
+        delta: bool,
+        /// Operate on the sidecar file (`<target>.overlay`) bound to the target instead of the target itself
+        #[arg(long, conflicts_with = "new_file_path")]
+        sidecar: bool,
+        /// Validate and print the planned changes without writing anything
+        #[arg(long)]
+        dry_run: bool,
     },
     /// Export resources
     Export {
         /// Target file path
         #[arg(value_parser = validate_path)]
         target_file: PathBuf,
-        /// Resource ID
-        id: String,
-        /// Output path
-        output_path: PathBuf,
+        /// Resource ID (`outer/inner` for nested resources; omit with `--match` or `--regex`)
+        id: Option<String>,
+        /// Output path (`-` to write to stdout; a directory with `--match` or `--regex`)
+        output_path: Option<PathBuf>,
+        /// Export every resource whose ID matches a glob (`*`, `**`, `?`, `[a-z]`)
+        #[arg(
+            short = 'm',
+            long = "match",
+            value_name = "GLOB",
+            conflicts_with = "regex"
+        )]
+        glob: Option<String>,
+        /// Export every resource whose ID matches a regular expression
+        #[arg(long, value_name = "REGEX")]
+        regex: Option<String>,
+        /// Operate on the sidecar file (`<target>.overlay`) bound to the target instead of the target itself
+        #[arg(long)]
+        sidecar: bool,
+        /// Use the stored file name as-is when exporting into a directory, even if it contains `..`,
+        /// an absolute path, a reserved device name or control characters
+        #[arg(long)]
+        allow_unsafe_names: bool,
     },
     /// Remove a resource by ID
     Remove {
         /// Target file path
         #[arg(value_parser = validate_path)]
         target_file: PathBuf,
-        /// Resource ID
-        id: String,
+        /// Resource ID (omit with `--match` or `--regex`)
+        id: Option<String>,
         /// New file path (optional)
         new_file_path: Option<PathBuf>,
+        /// Remove every resource whose ID matches a glob (`*`, `**`, `?`, `[a-z]`)
+        #[arg(
+            short = 'm',
+            long = "match",
+            value_name = "GLOB",
+            conflicts_with = "regex"
+        )]
+        glob: Option<String>,
+        /// Remove every resource whose ID matches a regular expression
+        #[arg(long, value_name = "REGEX")]
+        regex: Option<String>,
+        /// Operate on the sidecar file (`<target>.overlay`) bound to the target instead of the target itself
+        #[arg(long, conflicts_with = "new_file_path")]
+        sidecar: bool,
+        /// Validate and print the planned changes without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Show detailed resource layout (offsets, lengths, compression ratio)
+    Info {
+        /// Target file path
+        #[arg(value_parser = validate_path)]
+        target_file: PathBuf,
+        /// Resource ID to filter (optional)
+        id: Option<String>,
+        /// Operate on the sidecar file (`<target>.overlay`) bound to the target instead of the target itself
+        #[arg(long)]
+        sidecar: bool,
     },
+    /// Verify resource integrity without writing any data
+    Verify {
+        /// Target file path
+        #[arg(value_parser = validate_path)]
+        target_file: PathBuf,
+        /// Resource ID (optional, `outer/inner` for nested resources)
+        id: Option<String>,
+        /// Operate on the sidecar file (`<target>.overlay`) bound to the target instead of the target itself
+        #[arg(long)]
+        sidecar: bool,
+    },
+    /// Serve resources over a read-only HTTP interface
+    Serve {
+        /// Target file path
+        #[arg(value_parser = validate_path)]
+        target_file: PathBuf,
+        /// Address to listen on
+        #[arg(short, long, default_value = "127.0.0.1:8080")]
+        bind: String,
+        /// Operate on the sidecar file (`<target>.overlay`) bound to the target instead of the target itself
+        #[arg(long)]
+        sidecar: bool,
+    },
+    /// Build a self-extracting executable from the bundled stub and resource files
+    Sfx {
+        /// Output executable path
+        output_path: PathBuf,
+        /// Resource files to bundle (stored under their file names)
+        #[arg(required = true, value_parser = validate_path)]
+        resources: Vec<PathBuf>,
+        /// Stub executable (defaults to the `appender-sfx` stub next to this program)
+        #[arg(long, value_parser = validate_path)]
+        stub: Option<PathBuf>,
+        /// Default extraction directory, relative to the working directory at launch
+        #[arg(short, long, default_value = ".")]
+        destination: String,
+        /// Command to run in the extraction directory after extracting
+        #[arg(long)]
+        run: Option<String>,
+        /// Compression level (0-9)
+        #[arg(short, long, default_value = "1")]
+        compression: u32,
+    },
+    /// Strip all resources and write out the original host file
+    Strip {
+        /// Target file path
+        #[arg(value_parser = validate_path)]
+        target_file: PathBuf,
+        /// New file path (optional)
+        new_file_path: Option<PathBuf>,
+    },
+}
+
+/// 标准输入/输出路径标识
+pub const STDIO_PATH: &str = "-";
+
+/// 验证输入路径是否存在(允许 `-` 表示标准输入)
+///
+/// # 参数
+/// - `s`: 路径字符串
+///
+/// # 返回值
+/// - `Ok(PathBuf)`: 路径存在或为 `-`
+/// - `Err(err)`: 路径不存在
+fn validate_input_path(s: &str) -> Result<PathBuf, String> {
+    if s == STDIO_PATH {
+        return Ok(PathBuf::from(s));
+    }
+    validate_path(s)
 }
 
 /// 验证路径是否存在
@@ -74,3 +256,49 @@ fn validate_path(s: &str) -> Result<PathBuf, String> {
     }
     Ok(path)
 }
+
+/// 解析 `key=value` 形式的标签
+///
+/// # 参数
+/// - `s`: 标签字符串
+///
+/// # 返回值
+/// - `Ok((String, String))`: 标签键值
+/// - `Err(err)`: 格式错误
+fn parse_tag(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => Err("Tags must be in `key=value` form with a non-empty key".to_string()),
+    }
+}
+
+/// 解析字节大小(支持 `K`、`M`、`G` 后缀，以 1024 为进制)
+///
+/// # 参数
+/// - `s`: 大小字符串
+///
+/// # 返回值
+/// - `Ok(u64)`: 字节数
+/// - `Err(err)`: 格式错误或为 0
+fn parse_size(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let (number, unit) = match s.char_indices().last() {
+        Some((index, c)) if c.is_ascii_alphabetic() => (&s[..index], c.to_ascii_uppercase()),
+        _ => (s, 'B'),
+    };
+    let shift = match unit {
+        'B' => 0,
+        'K' => 10,
+        'M' => 20,
+        'G' => 30,
+        _ => return Err("Size suffix must be one of K, M or G".to_string()),
+    };
+    match number.trim().parse::<u64>() {
+        Ok(size) if size > 0 => size
+            .checked_mul(1 << shift)
+            .ok_or_else(|| "Size is too large".to_string()),
+        _ => Err("Size must be a positive number of bytes".to_string()),
+    }
+}
@@ -0,0 +1,827 @@
+use crate::core::{
+    all_records, copy_range, create_temp_file, is_same_file, open_source, payload_range,
+    plan_removals, record_len, resolve_path, resource_end, stream_head, volume_files, write_record,
+    AddOptions, AppendTarget, Backend, CompressMode, Payload, Range, Removal, ResourceHead,
+    Storage, BUFFER_SIZE, MAX_HEADER_SIZE,
+};
+use crate::error::{Error, Result};
+use crate::progress::{ProgressCallback, ProgressTracker, Stage};
+use crate::source::FileSource;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Cursor, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// 资源容器（事务式修改）
+///
+/// 打开时扫描一次目标文件，随后增加、删除、更新与重命名资源只修改内存中的记录，
+/// 提交时一次写入全部修改：只增加资源时追加到文件末尾，否则重写一次目标文件。
+/// 提交失败时目标文件保持不变。
+///
+/// 也可以通过 [`Container::from_backend`] 在任意读写后端(如内存缓冲区)上修改资源，
+/// 此时重写在内存中完成，且不支持分卷资源。
+///
+/// ```no_run
+/// use appender::container::Container;
+/// use appender::core::AddOptions;
+///
+/// let mut container = Container::open("D:/Program.exe")?;
+/// container
+///     .add("D:/lang/en.json", "lang/en", &AddOptions::default())?
+///     .remove("lang/old")?
+///     .rename("Archive", "Archive-v1")?;
+/// container.commit(None, &mut |_| {})?;
+/// # Ok::<(), appender::error::Error>(())
+/// ```
+pub struct Container<'a> {
+    /// 目标数据
+    target: Target<'a>,
+    /// 目标文件中的全部顶层记录(资源起始位置, 资源配置)，包含内部记录
+    records: Vec<(usize, ResourceHead)>,
+    /// 目标文件中的资源
+    resources: Vec<Resource>,
+    /// 待追加的资源(按追加顺序)
+    pending: Vec<Pending>,
+}
+
+/// 容器的目标数据
+enum Target<'a> {
+    /// 目标文件路径
+    File(PathBuf),
+    /// 读写后端
+    Backend(&'a mut dyn Backend),
+}
+
+/// 目标文件中的资源
+struct Resource {
+    /// 资源起始位置
+    start: usize,
+    /// 资源配置
+    config: ResourceHead,
+    /// 提交后的资源ID(删除时为 None)
+    id: Option<String>,
+}
+
+/// 待追加的资源
+struct Pending {
+    /// 资源ID
+    id: String,
+    /// 数据来源
+    source: Source,
+    /// 增加资源选项
+    options: AddOptions,
+}
+
+/// 待追加资源的数据来源
+enum Source {
+    /// 资源文件(提交时打开，相对路径相对于目标文件所在目录)
+    File(PathBuf),
+    /// 数据流
+    Reader(Box<dyn Read>),
+}
+
+/// 按资源ID找到的资源
+enum Found {
+    /// 目标文件中的资源(序号)
+    Resource(usize),
+    /// 待追加的资源(序号)
+    Pending(usize),
+}
+
+impl<'a> Container<'a> {
+    /// 打开目标文件（扫描一次全部资源）
+    ///
+    /// # 参数
+    /// - `path`: 目标文件路径
+    ///
+    /// # 返回值
+    /// - Ok(Container): 资源容器
+    /// - Err(err)
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let records = all_records(&mut FileSource::open(&path)?)?;
+        Ok(Self::with_records(Target::File(path), records))
+    }
+
+    /// 在读写后端上打开容器（扫描一次全部资源）
+    ///
+    /// 资源文件的相对路径相对于当前工作目录。
+    ///
+    /// # 参数
+    /// - `backend`: 读写后端(如 `File` 或 `Cursor<Vec<u8>>`)
+    ///
+    /// # 返回值
+    /// - Ok(Container): 资源容器
+    /// - Err(err)
+    pub fn from_backend(backend: &'a mut dyn Backend) -> Result<Self> {
+        let records = all_records(&mut FileSource::from_reader(&mut *backend)?)?;
+        Ok(Self::with_records(Target::Backend(backend), records))
+    }
+
+    /// 由扫描得到的记录创建容器
+    fn with_records(target: Target<'a>, records: Vec<(usize, ResourceHead)>) -> Self {
+        let resources = records
+            .iter()
+            .filter(|(_, config)| !config.is_hidden())
+            .map(|(start, config)| Resource {
+                start: *start,
+                config: config.clone(),
+                id: Some(config.id().trim().to_string()),
+            })
+            .collect();
+        Container {
+            target,
+            records,
+            resources,
+            pending: Vec::new(),
+        }
+    }
+
+    /// 获取提交后的资源ID列表(目标文件中的资源在前，待追加的资源在后)
+    pub fn ids(&self) -> Vec<&str> {
+        self.resources
+            .iter()
+            .filter_map(|resource| resource.id.as_deref())
+            .chain(self.pending.iter().map(|pending| pending.id.as_str()))
+            .collect()
+    }
+
+    /// 是否有未提交的修改
+    pub fn is_modified(&self) -> bool {
+        !self.pending.is_empty()
+            || self
+                .resources
+                .iter()
+                .any(|resource| resource.id.as_deref() != Some(resource.config.id().trim()))
+    }
+
+    /// 增加资源
+    ///
+    /// # 参数
+    /// - `source_file_path`: 资源文件路径(提交时读取)
+    /// - `id`: 资源ID（不可重复）
+    /// - `options`: 增加资源选项(`output_path` 不生效，输出路径在提交时指定)
+    ///
+    /// # 返回值
+    /// - Ok(&mut Container)
+    /// - Err(err): 资源ID无效或已存在
+    pub fn add(
+        &mut self,
+        source_file_path: impl Into<PathBuf>,
+        id: &str,
+        options: &AddOptions,
+    ) -> Result<&mut Self> {
+        self.push(id, Source::File(source_file_path.into()), options)
+    }
+
+    /// 从数据流增加资源（提交时读取，文件名默认取资源ID）
+    ///
+    /// # 参数
+    /// - `reader`: 资源数据流
+    /// - `id`: 资源ID（不可重复）
+    /// - `options`: 增加资源选项(`output_path` 不生效，输出路径在提交时指定)
+    ///
+    /// # 返回值
+    /// - Ok(&mut Container)
+    /// - Err(err): 资源ID无效或已存在
+    pub fn add_reader(
+        &mut self,
+        reader: impl Read + 'static,
+        id: &str,
+        options: &AddOptions,
+    ) -> Result<&mut Self> {
+        self.push(id, Source::Reader(Box::new(reader)), options)
+    }
+
+    /// 删除资源
+    ///
+    /// # 参数
+    /// - `id`: 资源ID
+    ///
+    /// # 返回值
+    /// - Ok(&mut Container)
+    /// - Err(err): 资源不存在
+    pub fn remove(&mut self, id: &str) -> Result<&mut Self> {
+        match self.find(id)? {
+            Found::Resource(index) => self.resources[index].id = None,
+            Found::Pending(index) => {
+                self.pending.remove(index);
+            }
+        }
+        Ok(self)
+    }
+
+    /// 更新资源（删除旧版本，新版本追加到文件末尾）
+    ///
+    /// # 参数
+    /// - `source_file_path`: 新版本资源文件路径(提交时读取)
+    /// - `id`: 资源ID
+    /// - `options`: 增加资源选项(`output_path` 不生效，输出路径在提交时指定)
+    ///
+    /// # 返回值
+    /// - Ok(&mut Container)
+    /// - Err(err): 资源不存在
+    pub fn update(
+        &mut self,
+        source_file_path: impl Into<PathBuf>,
+        id: &str,
+        options: &AddOptions,
+    ) -> Result<&mut Self> {
+        let source = Source::File(source_file_path.into());
+        match self.find(id)? {
+            Found::Resource(index) => {
+                let id = self.resources[index].id.take().unwrap_or_default();
+                self.pending.push(Pending {
+                    id,
+                    source,
+                    options: options.clone(),
+                });
+            }
+            Found::Pending(index) => {
+                let pending = &mut self.pending[index];
+                pending.source = source;
+                pending.options = options.clone();
+            }
+        }
+        Ok(self)
+    }
+
+    /// 重命名资源
+    ///
+    /// 分卷资源与旧版资源(1.0.0)的ID同时记录在分卷文件或固定布局中，不支持重命名。
+    ///
+    /// # 参数
+    /// - `id`: 资源ID
+    /// - `new_id`: 新资源ID（不可重复）
+    ///
+    /// # 返回值
+    /// - Ok(&mut Container)
+    /// - Err(err): 资源不存在、新资源ID无效或已存在、资源不支持重命名
+    pub fn rename(&mut self, id: &str, new_id: &str) -> Result<&mut Self> {
+        let found = self.find(id)?;
+        let new_id = new_id.trim();
+        if new_id == id.trim() {
+            return Ok(self);
+        }
+        check_id(new_id)?;
+        if self.find(new_id).is_ok() {
+            return Err(Error::DuplicateId(new_id.to_string()));
+        }
+        match found {
+            Found::Resource(index) => {
+                // 差异资源的旧版本需同时重命名
+                let config = &self.resources[index].config;
+                let history = self
+                    .records
+                    .iter()
+                    .map(|(_, c)| c)
+                    .filter(|c| c.superseded() && c.id() == config.id());
+                if std::iter::once(config)
+                    .chain(history)
+                    .any(|c| c.storage() == Storage::Split || c.trailer_len() == 0)
+                {
+                    return Err(Error::Unsupported(format!(
+                        "Resource {} is a split or legacy resource and cannot be renamed",
+                        id.trim()
+                    )));
+                }
+                self.resources[index].id = Some(new_id.to_string());
+            }
+            Found::Pending(index) => self.pending[index].id = new_id.to_string(),
+        }
+        Ok(self)
+    }
+
+    /// 提交全部修改
+    ///
+    /// 只增加资源时追加到末尾；有删除、更新或重命名时重写一次目标数据
+    /// (原地修改文件时先写入临时文件，完成后替换目标文件；读写后端先在内存中重写，完成后写回)。
+    ///
+    /// # 参数
+    /// - `output_path`: 输出文件路径(可选，相对路径相对于目标文件所在目录；读写后端不支持)
+    /// - `progress`: 进度回调
+    ///
+    /// # 返回值
+    /// - Ok(())
+    /// - Err(err): 提交失败(目标文件保持不变)
+    pub fn commit(self, output_path: Option<&Path>, progress: ProgressCallback) -> Result<()> {
+        let removed: Vec<(usize, ResourceHead)> = self
+            .resources
+            .iter()
+            .filter(|resource| resource.id.is_none())
+            .map(|resource| (resource.start, resource.config.clone()))
+            .collect();
+        // 原资源ID -> 新资源ID
+        let renames: HashMap<String, String> = self
+            .resources
+            .iter()
+            .filter_map(|resource| {
+                let id = resource.id.as_ref()?;
+                (id != resource.config.id().trim())
+                    .then(|| (resource.config.id().to_string(), id.clone()))
+            })
+            .collect();
+        let changes = Changes {
+            records: &self.records,
+            removed: &removed,
+            renames: &renames,
+        };
+        let rewrite = !removed.is_empty() || !renames.is_empty();
+
+        match self.target {
+            Target::File(path) => {
+                let output_path = match output_path {
+                    Some(output_path) => resolve_path(&path, output_path)?,
+                    None => path.clone(),
+                };
+                match rewrite {
+                    true => rewrite_file(&path, &output_path, &changes, self.pending, progress),
+                    false => append_file(&path, &output_path, &changes, self.pending, progress),
+                }
+            }
+            Target::Backend(backend) => {
+                if output_path.is_some() {
+                    return Err(Error::Unsupported(
+                        "Output paths are only supported for containers opened from a file"
+                            .to_string(),
+                    ));
+                }
+                match rewrite {
+                    true => rewrite_backend(backend, &changes, self.pending, progress),
+                    false => append_backend(backend, &changes, self.pending, progress),
+                }
+            }
+        }
+    }
+
+    /// 按资源ID查找提交后的资源
+    ///
+    /// # 参数
+    /// - `id`: 资源ID
+    ///
+    /// # 返回值
+    /// - Ok(Found): 找到的资源
+    /// - Err(err): 资源不存在
+    fn find(&self, id: &str) -> Result<Found> {
+        let id = id.trim();
+        if let Some(index) = self
+            .resources
+            .iter()
+            .position(|resource| resource.id.as_deref() == Some(id))
+        {
+            return Ok(Found::Resource(index));
+        }
+        self.pending
+            .iter()
+            .position(|pending| pending.id == id)
+            .map(Found::Pending)
+            .ok_or_else(|| Error::NotFound(id.to_string()))
+    }
+
+    /// 加入待追加的资源
+    ///
+    /// # 参数
+    /// - `id`: 资源ID
+    /// - `source`: 数据来源
+    /// - `options`: 增加资源选项
+    ///
+    /// # 返回值
+    /// - Ok(&mut Container)
+    /// - Err(err): 资源ID无效或已存在
+    fn push(&mut self, id: &str, source: Source, options: &AddOptions) -> Result<&mut Self> {
+        let id = id.trim();
+        check_id(id)?;
+        if self.find(id).is_ok() {
+            return Err(Error::DuplicateId(id.to_string()));
+        }
+        self.pending.push(Pending {
+            id: id.to_string(),
+            source,
+            options: options.clone(),
+        });
+        Ok(self)
+    }
+}
+
+/// 提交的修改
+struct Changes<'a> {
+    /// 目标数据中的全部顶层记录(资源起始位置, 资源配置)
+    records: &'a [(usize, ResourceHead)],
+    /// 删除的(资源起始位置, 资源配置)列表
+    removed: &'a [(usize, ResourceHead)],
+    /// 原资源ID -> 新资源ID
+    renames: &'a HashMap<String, String>,
+}
+
+/// 重写目标数据的计划
+struct Rewrite {
+    /// 删除资源时对文件的修改
+    removal: Removal,
+    /// 需要修改的区间(记录区间, 重写后的资源头与数据区间；删除时为 None)
+    edits: Vec<(Range, Option<(ResourceHead, Range)>)>,
+    /// 保留的资源(追加资源时用于去重与分块)
+    kept: Vec<ResourceHead>,
+    /// 重写后(追加资源前)的长度
+    len: u64,
+}
+
+/// 提交只增加资源的修改到文件（追加到文件末尾）
+///
+/// # 参数
+/// - `target_file_path`: 目标文件路径
+/// - `output_path`: 输出文件路径
+/// - `changes`: 提交的修改
+/// - `pending`: 待追加的资源
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err)
+fn append_file(
+    target_file_path: &Path,
+    output_path: &Path,
+    changes: &Changes,
+    pending: Vec<Pending>,
+    progress: ProgressCallback,
+) -> Result<()> {
+    let in_place = is_same_file(output_path, target_file_path)?;
+    if !in_place {
+        fs::copy(target_file_path, output_path)?;
+    }
+    if pending.is_empty() {
+        return Ok(());
+    }
+    let mut target_file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(output_path)?;
+    let original_len = target_file.seek(SeekFrom::End(0))?;
+
+    // 写入失败时截断已写入的部分，保持目标文件不变
+    let mut created = Vec::new();
+    let result = append_pending(
+        Some(target_file_path),
+        Some(output_path),
+        &mut target_file,
+        changes.records.iter().map(|(_, config)| config),
+        pending,
+        &mut created,
+        progress,
+    );
+    if let Err(e) = result {
+        remove_files(&created);
+        if in_place {
+            target_file.set_len(original_len)?;
+        } else {
+            drop(target_file);
+            let _ = fs::remove_file(output_path);
+        }
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// 提交包含删除、更新或重命名的修改到文件（重写一次目标文件）
+///
+/// # 参数
+/// - `target_file_path`: 目标文件路径
+/// - `output_path`: 输出文件路径
+/// - `changes`: 提交的修改
+/// - `pending`: 待追加的资源
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err)
+fn rewrite_file(
+    target_file_path: &Path,
+    output_path: &Path,
+    changes: &Changes,
+    pending: Vec<Pending>,
+    progress: ProgressCallback,
+) -> Result<()> {
+    let in_place = is_same_file(output_path, target_file_path)?;
+    let mut source_file = FileSource::open(target_file_path)?;
+    let rewrite = plan_rewrite(&mut source_file, Some(target_file_path), changes)?;
+
+    // 原地修改时写入临时文件，完成后替换目标文件
+    let (write_path, mut output_file) = if in_place {
+        create_temp_file(output_path)?
+    } else {
+        (output_path.to_path_buf(), File::create(output_path)?)
+    };
+    let mut created = Vec::new();
+    let result = (|| -> Result<()> {
+        let mut writer = BufWriter::with_capacity(BUFFER_SIZE, &mut output_file);
+        write_rewritten(&mut source_file, &rewrite, &mut writer, &mut *progress)?;
+        writer.flush()?;
+        drop(writer);
+        append_pending(
+            Some(target_file_path),
+            Some(output_path),
+            &mut output_file,
+            &rewrite.kept,
+            pending,
+            &mut created,
+            progress,
+        )
+    })();
+    drop(output_file);
+    if let Err(e) = result {
+        remove_files(&created);
+        let _ = fs::remove_file(&write_path);
+        return Err(e);
+    }
+    drop(source_file);
+    if in_place {
+        let replaced = fs::metadata(target_file_path)
+            .and_then(|metadata| fs::set_permissions(&write_path, metadata.permissions()))
+            .and_then(|_| fs::rename(&write_path, output_path));
+        if let Err(e) = replaced {
+            remove_files(&created);
+            let _ = fs::remove_file(&write_path);
+            return Err(e.into());
+        }
+        // 目标文件已替换，残留的分卷文件不影响提交结果(输出到新文件时原文件仍在使用分卷文件)
+        remove_files(&rewrite.removal.volumes);
+    }
+    Ok(())
+}
+
+/// 提交只增加资源的修改到读写后端（追加到末尾）
+///
+/// # 参数
+/// - `backend`: 读写后端
+/// - `changes`: 提交的修改
+/// - `pending`: 待追加的资源
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err)
+fn append_backend(
+    backend: &mut dyn Backend,
+    changes: &Changes,
+    pending: Vec<Pending>,
+    progress: ProgressCallback,
+) -> Result<()> {
+    let original_len = backend.seek(SeekFrom::End(0))?;
+    let result = append_pending(
+        None,
+        None,
+        &mut *backend,
+        changes.records.iter().map(|(_, config)| config),
+        pending,
+        &mut Vec::new(),
+        progress,
+    );
+    if let Err(e) = result {
+        backend.set_len(original_len)?;
+        return Err(e);
+    }
+    backend.flush()?;
+    Ok(())
+}
+
+/// 提交包含删除、更新或重命名的修改到读写后端（在内存中重写，完成后写回）
+///
+/// # 参数
+/// - `backend`: 读写后端
+/// - `changes`: 提交的修改
+/// - `pending`: 待追加的资源
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err)
+fn rewrite_backend(
+    backend: &mut dyn Backend,
+    changes: &Changes,
+    pending: Vec<Pending>,
+    progress: ProgressCallback,
+) -> Result<()> {
+    let mut image = Cursor::new(Vec::new());
+    let kept = {
+        let mut source_file = FileSource::from_reader(&mut *backend)?;
+        let rewrite = plan_rewrite(&mut source_file, None, changes)?;
+        write_rewritten(&mut source_file, &rewrite, &mut image, &mut *progress)?;
+        rewrite.kept
+    };
+    append_pending(
+        None,
+        None,
+        &mut image,
+        &kept,
+        pending,
+        &mut Vec::new(),
+        progress,
+    )?;
+
+    let image = image.into_inner();
+    backend.seek(SeekFrom::Start(0))?;
+    backend.write_all(&image)?;
+    backend.set_len(image.len() as u64)?;
+    backend.flush()?;
+    Ok(())
+}
+
+/// 计算重写目标数据的计划
+///
+/// # 参数
+/// - `source_file`: 目标数据
+/// - `target_file_path`: 目标文件路径(读写后端为 None)
+/// - `changes`: 提交的修改
+///
+/// # 返回值
+/// - Ok(Rewrite): 重写计划
+/// - Err(err)
+fn plan_rewrite(
+    source_file: &mut FileSource,
+    target_file_path: Option<&Path>,
+    changes: &Changes,
+) -> Result<Rewrite> {
+    let mut removal = plan_removals(source_file, target_file_path, changes.removed)?;
+    for (head, _) in &mut removal.promoted {
+        if let Some(id) = changes.renames.get(head.id()) {
+            head.set_id(id)?;
+        }
+    }
+    let dropped: HashSet<u64> = removal.ranges.iter().map(|(start, _)| *start).collect();
+
+    let mut edits: Vec<(Range, Option<(ResourceHead, Range)>)> =
+        removal.ranges.iter().map(|range| (*range, None)).collect();
+    let mut renamed = HashMap::new();
+    for (start, config) in changes.records {
+        let Some(id) = changes.renames.get(config.id()) else {
+            continue;
+        };
+        let internal = matches!(
+            config.storage(),
+            Storage::Chunk | Storage::Volume | Storage::Binding
+        );
+        if internal || dropped.contains(&(*start as u64)) {
+            continue;
+        }
+        let mut head = config.clone();
+        head.set_id(id)?;
+        if head.get_len() > MAX_HEADER_SIZE {
+            return Err(Error::InvalidInput(format!(
+                "Resource header exceeds maximum size of {} bytes",
+                MAX_HEADER_SIZE
+            )));
+        }
+        let end = resource_end(source_file, *start, config)?;
+        let (data_start, length) = payload_range(source_file, *start, config)?;
+        edits.push((
+            (*start as u64, end as u64),
+            Some((head.clone(), (data_start, data_start + length))),
+        ));
+        renamed.insert(*start, head);
+    }
+    edits.sort_by_key(|(range, _)| *range);
+
+    let kept = changes
+        .records
+        .iter()
+        .filter(|(start, _)| !dropped.contains(&(*start as u64)))
+        .map(|(start, config)| renamed.get(start).unwrap_or(config).clone())
+        .chain(removal.promoted.iter().map(|(head, _)| head.clone()))
+        .collect();
+
+    let written = edits
+        .iter()
+        .filter_map(|(_, rewritten)| rewritten.as_ref())
+        .chain(&removal.promoted)
+        .map(|(head, (start, end))| record_len(head, end - start))
+        .sum::<u64>();
+    let removed_len: u64 = edits.iter().map(|((start, end), _)| end - start).sum();
+    let len = source_file.len()? - removed_len + written;
+    Ok(Rewrite {
+        removal,
+        edits,
+        kept,
+        len,
+    })
+}
+
+/// 按重写计划写入保留的数据、重写的资源与提升的引用资源
+///
+/// # 参数
+/// - `source_file`: 目标数据
+/// - `rewrite`: 重写计划
+/// - `writer`: 输出数据流
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err)
+fn write_rewritten(
+    source_file: &mut FileSource,
+    rewrite: &Rewrite,
+    writer: &mut dyn Write,
+    progress: ProgressCallback,
+) -> Result<()> {
+    let file_len = source_file.len()?;
+    let mut tracker = ProgressTracker::new(progress, Stage::Rewrite, Some(rewrite.len));
+    let mut pos = 0;
+    for ((start, end), rewritten) in &rewrite.edits {
+        copy_range(source_file, pos, start - pos, writer, &mut tracker)?;
+        if let Some((head, (data_start, data_end))) = rewritten {
+            let mut data = source_file.range_reader(*data_start, data_end - data_start)?;
+            write_record(writer, head, &mut data)?;
+        }
+        pos = *end;
+    }
+    copy_range(source_file, pos, file_len - pos, writer, &mut tracker)?;
+    for (head, (start, end)) in &rewrite.removal.promoted {
+        let mut data = source_file.range_reader(*start, end - start)?;
+        write_record(writer, head, &mut data)?;
+    }
+    tracker.set(rewrite.len);
+    tracker.report();
+    Ok(())
+}
+
+/// 检查资源ID是否有效
+///
+/// # 参数
+/// - `id`: 资源ID
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err): 资源ID为空或超过长度上限
+fn check_id(id: &str) -> Result<()> {
+    if id.is_empty() {
+        return Err(Error::InvalidId("must not be empty".to_string()));
+    }
+    ResourceHead::new(id, 0, 0, "", CompressMode::None).map(|_| ())
+}
+
+/// 在输出数据末尾依次写入待追加的资源
+///
+/// # 参数
+/// - `target_file_path`: 原目标文件路径(资源文件的相对路径相对于其所在目录；读写后端为 None)
+/// - `output_path`: 输出文件路径(分卷文件位于同一目录；读写后端为 None，不支持分卷资源)
+/// - `output`: 输出数据
+/// - `kept`: 输出文件中保留的资源配置
+/// - `pending`: 待追加的资源
+/// - `created`: 已创建的分卷文件(失败时由调用方删除)
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err)
+fn append_pending<'a>(
+    target_file_path: Option<&Path>,
+    output_path: Option<&Path>,
+    output: &mut dyn Backend,
+    kept: impl IntoIterator<Item = &'a ResourceHead>,
+    pending: Vec<Pending>,
+    created: &mut Vec<PathBuf>,
+    progress: ProgressCallback,
+) -> Result<()> {
+    let mut target = AppendTarget::new(output_path, output, kept);
+    for Pending {
+        id,
+        source,
+        options,
+    } in pending
+    {
+        let mut file;
+        let mut reader;
+        let (payload, mut head, source_size) = match source {
+            Source::File(path) => {
+                let path = match target_file_path {
+                    Some(target_file_path) => resolve_path(target_file_path, &path)?,
+                    None => std::path::absolute(&path)?,
+                };
+                let head;
+                (file, head) = open_source(&path, &path, &id, &options)?;
+                let size = file.metadata()?.len();
+                (Payload::Seekable(&mut file), head, Some(size))
+            }
+            Source::Reader(boxed) => {
+                reader = boxed;
+                (
+                    Payload::Stream(&mut reader),
+                    stream_head(&id, &options)?,
+                    None,
+                )
+            }
+        };
+        target.append(payload, source_size, &mut head, &options, &mut *progress)?;
+        if let Some(output_path) = output_path {
+            created.extend(volume_files(output_path, &head));
+        }
+    }
+    Ok(())
+}
+
+/// 删除分卷文件（尽力删除，忽略错误）
+///
+/// 用于删除提交失败前创建的分卷文件，以及提交成功后不再使用的分卷文件。
+fn remove_files(paths: &[PathBuf]) {
+    for path in paths {
+        let _ = fs::remove_file(path);
+    }
+}
@@ -1,12 +1,25 @@
+use crate::container::Container;
+use crate::delta::{self, DeltaPatch};
+use crate::error::{Error, Result};
+use crate::plan::Change;
+use crate::progress::{ProgressCallback, ProgressReader, ProgressTracker, Stage};
+use crate::select::Selector;
+use crate::source::{FileSource, ReadSeek};
 use crate::util::compare_version;
-use crate::util::{compression_file, decompress_file};
-use anyhow::{anyhow, Result};
-use memchr::memmem;
+use crate::util::{
+    check_file_name, checksum, compression_stream, decompress_stream, detect_mime, file_modified,
+    file_permissions, parallel_compression_stream, restore_file_attributes, ContentChunker,
+    CountingReader, HashingReader, HashingWriter, PERMISSION_BITS,
+};
+use bincode::Options;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::fs::{File, OpenOptions};
-use std::io::{Read, Seek, SeekFrom, Write};
-use std::path::Path;
+use std::io::{self, copy, BufWriter, Read, Seek, SeekFrom, Write};
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
 
 /// 缓冲区大小（512KB）
 pub const BUFFER_SIZE: usize = 1024 * 512;
@@ -20,6 +33,33 @@ pub const MAX_ID_LENGTH: usize = 64;
 /// 最大文件名长度
 pub const MAX_NAME_LENGTH: usize = 255;
 
+/// 最大可能的资源头大小
+pub const MAX_HEADER_SIZE: usize = 4096;
+
+/// 资源头中长度与大小字段的最大位数（旧版资源头为 16 位，保证位置计算不会溢出）
+const MAX_NUMBER_DIGITS: usize = 16;
+
+/// 资源版本号的最大长度
+const MAX_VERSION_LENGTH: usize = 32;
+
+/// 单个资源的最大分卷文件数量
+pub const MAX_VOLUMES: u32 = 9999;
+
+/// 校验和长度（SHA-256 十六进制字符数）
+pub(crate) const CHECKSUM_LENGTH: usize = 64;
+
+/// 检测 MIME 类型时读取的字节数
+const MIME_SNIFF_SIZE: usize = 16;
+
+/// 去重时缓存数据流的内存上限(超过后缓存到临时文件)
+const SPOOL_MEMORY_SIZE: usize = 16 * 1024 * 1024;
+
+/// 当前资源格式版本
+const RESOURCE_VERSION: &str = "1.1.0";
+
+/// 旧版资源格式版本（无元数据）
+const LEGACY_VERSION: &str = "1.0.0";
+
 /// 压缩模式
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
 pub enum CompressMode {
@@ -30,12 +70,56 @@ pub enum CompressMode {
 }
 
 /// 资源文件魔数
-const RESOURCE_MAGIC: &[u8] = &[
+pub(crate) const RESOURCE_MAGIC: &[u8] = &[
     0x89, b'O', b'v', b'e', b'r', b'l', b'a', b'y', b'D', b'a', b't', b'a', 0x0d, 0x0a, 0x1a, 0x0a,
 ];
 
+/// 资源数据存储方式
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone, Copy)]
+pub enum Storage {
+    /// 数据存储在资源内
+    #[default]
+    Inline,
+    /// 引用校验和相同的其他资源的数据(自身不含数据)
+    Reference,
+    /// 数据为数据块校验和列表，按顺序拼接数据块即为原始数据
+    Chunked,
+    /// 分块存储的数据块(内部资源，不作为资源列出)
+    Chunk,
+    /// 数据为相对同 ID 旧版本的差异补丁，按补丁还原旧版本即为原始数据
+    Delta,
+    /// 数据的第一部分，其余部分依次存储在分卷文件中
+    Split,
+    /// 分卷文件中存储的部分数据(内部资源，不作为资源列出)
+    Volume,
+    /// 旁路文件与宿主文件的绑定记录(校验和为宿主文件的校验和，不含数据)
+    Binding,
+}
+
+/// 分卷信息（分卷文件为宿主文件旁的 `<文件名>.a01`、`<文件名>.a02` 等）
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone, Copy)]
+pub struct Volumes {
+    /// 第一个分卷文件的序号
+    pub first: u32,
+    /// 分卷文件数量(不含宿主文件)
+    pub count: u32,
+}
+
+/// 资源元数据
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone)]
+pub struct Metadata {
+    /// 修改时间(Unix 时间戳，秒)
+    pub modified: Option<u64>,
+    /// 权限位(Unix mode)
+    pub permissions: Option<u32>,
+    /// MIME 类型
+    pub mime: Option<String>,
+    /// 自定义标签(键, 值)
+    pub tags: Vec<(String, String)>,
+}
+
 /// 资源文件头
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ResourceHead {
     /// 资源版本(不应与程序版本号绑定)
     version: String,
@@ -49,11 +133,32 @@ pub struct ResourceHead {
     size: String,
     /// 压缩模式
     compress: CompressMode,
+    /// 资源元数据
+    metadata: Metadata,
+    /// 原始数据的 SHA-256 校验和(十六进制，旧版资源为空)
+    checksum: String,
+    /// 数据存储方式
+    storage: Storage,
+    /// 是否已被同 ID 的新版本取代(作为差异补丁的基础版本保留，不作为资源列出)
+    superseded: bool,
+    /// 分卷信息(仅分卷存储的资源)
+    volumes: Volumes,
+}
+
+/// 旧版资源文件头(1.0.0)
+#[derive(Serialize, Deserialize)]
+struct LegacyResourceHead {
+    version: String,
+    id: String,
+    name: String,
+    length: String,
+    size: String,
+    compress: CompressMode,
 }
 
 impl ResourceHead {
     pub(crate) fn default() -> Self {
-        ResourceHead::new("", 0, 0, "", CompressMode::None)
+        ResourceHead::unchecked("", 0, 0, "", CompressMode::None)
     }
 
     /// 获取文件头魔数（标识）
@@ -71,35 +176,100 @@ impl ResourceHead {
     /// - `compress`: 压缩模式
     ///
     /// # 返回值
-    /// - ResourceHead: 资源文件头
-    pub fn new(id: &str, length: u64, size: u64, name: &str, compress: CompressMode) -> Self {
+    /// - Ok(ResourceHead): 资源文件头
+    /// - Err(err): 资源ID或文件名超过长度上限
+    pub fn new(
+        id: &str,
+        length: u64,
+        size: u64,
+        name: &str,
+        compress: CompressMode,
+    ) -> Result<Self> {
         // 验证输入字符数不超过限制
-        assert!(
-            id.chars().count() <= MAX_ID_LENGTH,
-            "Resource ID exceeds maximum length of {} characters",
-            MAX_ID_LENGTH
-        );
-        assert!(
-            name.chars().count() <= MAX_NAME_LENGTH,
-            "Resource name exceeds maximum length of {} characters",
-            MAX_NAME_LENGTH
-        );
+        if id.chars().count() > MAX_ID_LENGTH {
+            return Err(Error::InvalidId(format!(
+                "exceeds maximum length of {} characters",
+                MAX_ID_LENGTH
+            )));
+        }
+        if name.chars().count() > MAX_NAME_LENGTH {
+            return Err(Error::InvalidName(format!(
+                "exceeds maximum length of {} characters",
+                MAX_NAME_LENGTH
+            )));
+        }
+        Ok(ResourceHead::unchecked(id, length, size, name, compress))
+    }
 
+    /// 创建资源文件头(不校验长度)
+    fn unchecked(id: &str, length: u64, size: u64, name: &str, compress: CompressMode) -> Self {
         ResourceHead {
-            version: "1.0.0".to_string(),
+            version: RESOURCE_VERSION.to_string(),
             id: id.to_string(),
             name: name.to_string(),
-            length: format!(
-                "{:0>width$}",
-                length,
-                width = MAX_LENGTH_SIZE.to_string().len()
-            ),
-            size: format!(
-                "{:0>width$}",
-                size,
-                width = MAX_LENGTH_SIZE.to_string().len()
-            ),
+            length: pad_number(length),
+            size: pad_number(size),
             compress,
+            metadata: Metadata::default(),
+            checksum: String::new(),
+            storage: Storage::Inline,
+            superseded: false,
+            volumes: Volumes::default(),
+        }
+    }
+
+    /// 设置资源元数据
+    pub fn with_metadata(mut self, metadata: Metadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// 设置资源ID（改变资源头长度，需重写资源记录）
+    ///
+    /// # 参数
+    /// - `id`: 资源ID
+    ///
+    /// # 返回值
+    /// - Ok(())
+    /// - Err(err): 资源ID超过长度上限
+    pub(crate) fn set_id(&mut self, id: &str) -> Result<()> {
+        if id.chars().count() > MAX_ID_LENGTH {
+            return Err(Error::InvalidId(format!(
+                "exceeds maximum length of {} characters",
+                MAX_ID_LENGTH
+            )));
+        }
+        self.id = id.to_string();
+        Ok(())
+    }
+
+    /// 设置资源长度（定宽字段，不改变资源头长度）
+    pub(crate) fn set_length(&mut self, length: u64) {
+        self.length = pad_number(length);
+    }
+
+    /// 设置资源大小（定宽字段，不改变资源头长度）
+    pub(crate) fn set_size(&mut self, size: u64) {
+        self.size = pad_number(size);
+    }
+
+    /// 设置校验和（定宽字段，不改变资源头长度）
+    pub(crate) fn set_checksum(&mut self, checksum: String) {
+        self.checksum = checksum;
+    }
+
+    /// 设置数据存储方式
+    pub(crate) fn set_storage(&mut self, storage: Storage) {
+        self.storage = storage;
+    }
+
+    /// 获取尾部长度字段大小（旧版资源为 0）
+    pub(crate) fn trailer_len(&self) -> usize {
+        // 旧版资源没有尾部长度字段
+        if self.version == LEGACY_VERSION {
+            0
+        } else {
+            TRAILER_SIZE
         }
     }
 
@@ -112,12 +282,111 @@ impl ResourceHead {
 
     /// 转换为字节
     pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        // 旧版资源头需按原布局序列化，保证长度与文件中一致
+        if self.version == LEGACY_VERSION {
+            return Ok(bincode::serialize(&LegacyResourceHead {
+                version: self.version.clone(),
+                id: self.id.clone(),
+                name: self.name.clone(),
+                length: self.length.clone(),
+                size: self.size.clone(),
+                compress: self.compress,
+            })?);
+        }
         Ok(bincode::serialize(&self)?)
     }
 
     /// 将字节解析为当前数据
+    ///
+    /// 数据可能来自不可信的文件：最多读取 [`MAX_HEADER_SIZE`] 字节，并校验各字段。
+    ///
+    /// # 参数
+    /// - `data`: 魔数之后的字节
+    ///
+    /// # 返回值
+    /// - Ok(ResourceHead): 资源头
+    /// - Err(err): 不是有效的资源头
     pub fn from(data: &[u8]) -> Result<Self> {
-        Ok(bincode::deserialize(data)?)
+        let head = ResourceHead::parse(data).map_err(|e| match e {
+            Error::Io(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                Error::corrupted(None, "Resource header is truncated")
+            }
+            e => e,
+        })?;
+        head.validate()?;
+        Ok(head)
+    }
+
+    /// 反序列化资源头(不校验字段)
+    fn parse(data: &[u8]) -> Result<Self> {
+        // 版本号位于资源头最前，先读取版本再决定布局
+        let version: String = header_options().deserialize(data)?;
+        if version.len() > MAX_VERSION_LENGTH {
+            return Err(Error::corrupted(None, "Resource version is too long"));
+        }
+        if compare_version(&version, LEGACY_VERSION)?.is_eq() {
+            let legacy: LegacyResourceHead = header_options().deserialize(data)?;
+            return Ok(ResourceHead {
+                version: legacy.version,
+                id: legacy.id,
+                name: legacy.name,
+                length: legacy.length,
+                size: legacy.size,
+                compress: legacy.compress,
+                metadata: Metadata::default(),
+                checksum: String::new(),
+                storage: Storage::Inline,
+                superseded: false,
+                volumes: Volumes::default(),
+            });
+        }
+        Ok(header_options().deserialize(data)?)
+    }
+
+    /// 校验资源头字段
+    fn validate(&self) -> Result<()> {
+        let invalid = |reason: &str| Err(Error::corrupted(None, reason));
+        if self.id.chars().count() > MAX_ID_LENGTH {
+            return invalid("Resource ID exceeds maximum length");
+        }
+        if self.name.chars().count() > MAX_NAME_LENGTH {
+            return invalid("Resource name exceeds maximum length");
+        }
+        for number in [&self.length, &self.size] {
+            let digits = number.trim();
+            if digits.is_empty()
+                || digits.len() > MAX_NUMBER_DIGITS
+                || !digits.bytes().all(|b| b.is_ascii_digit())
+            {
+                return invalid("Resource length or size is not a valid number");
+            }
+        }
+        if !self.checksum.is_empty()
+            && (self.checksum.len() != CHECKSUM_LENGTH
+                || !self.checksum.bytes().all(|b| b.is_ascii_hexdigit()))
+        {
+            return invalid("Resource checksum is not a valid SHA-256 digest");
+        }
+        if self
+            .metadata
+            .permissions
+            .is_some_and(|mode| mode & !PERMISSION_BITS != 0)
+        {
+            return invalid("Resource permissions are out of range");
+        }
+        if self
+            .metadata
+            .modified
+            .is_some_and(|secs| UNIX_EPOCH.checked_add(Duration::from_secs(secs)).is_none())
+        {
+            return invalid("Resource modification time is out of range");
+        }
+        if self.volumes.count > MAX_VOLUMES
+            || self.volumes.first.checked_add(self.volumes.count).is_none()
+        {
+            return invalid("Resource volume numbers are out of range");
+        }
+        Ok(())
     }
 
     /// 获取资源ID
@@ -130,6 +399,16 @@ impl ResourceHead {
         &self.name
     }
 
+    /// 获取资源版本
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// 获取资源长度（存储长度，压缩后）
+    pub fn length(&self) -> &str {
+        &self.length
+    }
+
     /// 获取资源大小
     pub fn size(&self) -> &str {
         &self.size
@@ -139,10 +418,132 @@ impl ResourceHead {
     pub fn compress(&self) -> CompressMode {
         self.compress
     }
+
+    /// 获取资源元数据
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// 获取原始数据的校验和(十六进制，旧版资源为空)
+    pub fn checksum(&self) -> &str {
+        &self.checksum
+    }
+
+    /// 获取数据存储方式
+    pub fn storage(&self) -> Storage {
+        self.storage
+    }
+
+    /// 是否已被同 ID 的新版本取代
+    pub fn superseded(&self) -> bool {
+        self.superseded
+    }
+
+    /// 获取分卷信息
+    pub fn volumes(&self) -> Volumes {
+        self.volumes
+    }
+
+    /// 是否为内部记录(数据块、分卷数据、绑定记录或被取代的旧版本，不作为资源列出)
+    pub fn is_hidden(&self) -> bool {
+        matches!(
+            self.storage,
+            Storage::Chunk | Storage::Volume | Storage::Binding
+        ) || self.superseded
+    }
+}
+
+/// 资源头的反序列化选项
+///
+/// 编码与 `bincode::serialize` 一致，但最多读取 [`MAX_HEADER_SIZE`] 字节，
+/// 伪造的长度前缀无法申请超出资源头大小的内存。
+fn header_options() -> impl Options {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+        .with_limit(MAX_HEADER_SIZE as u64)
+}
+
+/// 将数值格式化为定宽字符串
+fn pad_number(value: u64) -> String {
+    format!(
+        "{:0>width$}",
+        value,
+        width = MAX_LENGTH_SIZE.to_string().len()
+    )
 }
 
 /// 资源文件尾(ODEND)
-const END_IDENTIFIER: [u8; 5] = [0x4F, 0x44, 0x45, 0x4E, 0x44];
+pub(crate) const END_IDENTIFIER: [u8; 5] = [0x4F, 0x44, 0x45, 0x4E, 0x44];
+
+/// 嵌套资源路径分隔符(`外层ID/内层ID`)
+pub const NESTED_SEPARATOR: char = '/';
+
+/// 资源尾部长度字段大小（位于尾部标识之前，记录整个资源的长度，用于从文件末尾反向查找资源）
+pub(crate) const TRAILER_SIZE: usize = 8;
+
+/// 增加资源选项
+#[derive(Debug, Default, Clone)]
+pub struct AddOptions {
+    /// 资源文件名(默认取资源文件路径中的文件名，数据流默认取资源ID)
+    pub name: Option<String>,
+    /// 压缩等级(0-9)，None 为不压缩
+    ///     - 0: 不压缩
+    ///     - 1: 为优化编码的最佳速度
+    ///     - 9: 针对正在编码的数据大小进行优化。
+    pub compression_grade: Option<u32>,
+    /// 压缩线程数(0 或 1 为单线程，多线程时输出为多个 gzip 成员拼接)
+    pub threads: usize,
+    /// 输出文件路径(可选，相对路径相对于目标文件所在目录)
+    pub output_path: Option<PathBuf>,
+    /// 自定义标签(键, 值)
+    pub tags: Vec<(String, String)>,
+    /// 分块存储(内容定义分块，相同数据块只存储一次)
+    pub chunked: bool,
+    /// 分卷大小(每个文件中存储的数据字节数上限，超出部分写入分卷文件)
+    pub split_size: Option<u64>,
+}
+
+/// 释放资源选项
+#[derive(Debug, Default, Clone)]
+pub struct ExportOptions {
+    /// 输出路径为目录时，不检查资源文件名直接使用(资源文件名可能包含 `..` 或绝对路径)
+    pub allow_unsafe_names: bool,
+}
+
+/// 可读写的目标数据（文件或内存缓冲区）
+pub trait Backend: Read + Write + Seek {
+    /// 设置数据长度（截断，或以 0 填充）
+    fn set_len(&mut self, len: u64) -> io::Result<()>;
+}
+
+impl Backend for File {
+    fn set_len(&mut self, len: u64) -> io::Result<()> {
+        File::set_len(self, len)
+    }
+}
+
+impl<T: AsRef<[u8]> + AsMut<Vec<u8>>> Backend for io::Cursor<T>
+where
+    io::Cursor<T>: Write,
+{
+    fn set_len(&mut self, len: u64) -> io::Result<()> {
+        self.get_mut().as_mut().resize(len as usize, 0);
+        Ok(())
+    }
+}
+
+impl<T: Backend + ?Sized> Backend for &mut T {
+    fn set_len(&mut self, len: u64) -> io::Result<()> {
+        (**self).set_len(len)
+    }
+}
+
+impl<T: Backend + ?Sized> Backend for Box<T> {
+    fn set_len(&mut self, len: u64) -> io::Result<()> {
+        (**self).set_len(len)
+    }
+}
 
 /// 增加资源(Overlay 附加数据)
 ///
@@ -150,11 +551,8 @@ const END_IDENTIFIER: [u8; 5] = [0x4F, 0x44, 0x45, 0x4E, 0x44];
 /// - `target_file_path`: 目标文件路径
 /// - `source_file_path`: 资源文件路径
 /// - `id`: 资源ID（不可重复）
-/// - `compression_grade`: 压缩等级(0-9)
-///     - 0: 不压缩
-///     - 1: 为优化编码的最佳速度
-///     - 9: 针对正在编码的数据大小进行优化。
-/// - `output_path`: 输出文件路径(可选)
+/// - `options`: 增加资源选项
+/// - `progress`: 进度回调
 ///
 /// # 返回值
 /// - Ok(())
@@ -163,502 +561,3140 @@ pub fn add_resource(
     target_file_path: &Path,
     source_file_path: &Path,
     id: &str,
-    compression_grade: Option<u32>,
-    output_path: Option<&Path>,
+    options: &AddOptions,
+    progress: ProgressCallback,
 ) -> Result<()> {
-    // 打开资源文件
-    let source_file_path_buf = if source_file_path.is_relative() {
-        target_file_path
-            .parent()
-            .ok_or_else(|| anyhow!("Target file has no parent directory"))?
-            .join(source_file_path)
-    } else {
-        source_file_path.to_path_buf()
-    };
-    let mut source_file = File::open(&source_file_path_buf)?;
-    let source_name = &source_file_path_buf
-        .file_name()
-        .ok_or_else(|| anyhow!("Source file has no valid filename"))?
-        .to_string_lossy();
-
-    // 验证资源文件大小
-    let _source_size = source_file.metadata()?.len();
-
-    // 处理压缩资源
-    let temp_file_path = &*source_file_path_buf
-        .parent()
-        .ok_or_else(|| anyhow!("Source file has no parent directory"))?
-        .join("temp");
-    if let Some(grage) = compression_grade {
-        compression_file(&source_file_path_buf, temp_file_path, grage)?;
-        source_file = File::open(temp_file_path)?;
-    }
-    let source_length = source_file.metadata()?.len();
-
-    //以追加模式打开目标文件
-    let target_file_path_buf = if let Some(output_path_param) = output_path {
-        // 处理相对路径
-        let output_path_buf = if output_path_param.is_relative() {
-            target_file_path
-                .parent()
-                .ok_or_else(|| anyhow!("Target file has no parent directory"))?
-                .join(output_path_param)
-        } else {
-            output_path_param.to_path_buf()
-        };
-        fs::copy(target_file_path, &output_path_buf)?;
-        output_path_buf
-    } else {
-        target_file_path.to_path_buf()
-    };
-
-    let mut target_file = OpenOptions::new()
-        .append(true)
-        .open(&target_file_path_buf)?;
-
-    let compress_mode = match compression_grade.is_some() {
-        true => CompressMode::Compress,
-        false => CompressMode::None,
-    };
-
-    // 插入魔数标识
-    target_file.write_all(RESOURCE_MAGIC)?;
-
-    // 插入资源头
-    let head = ResourceHead::new(id, source_length, source_length, source_name, compress_mode)
-        .to_bytes()?;
-    target_file.write_all(&head)?;
-
-    // 缓冲区
-    let mut buffer = [0u8; BUFFER_SIZE];
-
-    // 循环读取并写入资源文件
-    loop {
-        let nbytes = source_file.read(&mut buffer)?;
-        target_file.write_all(&buffer[..nbytes])?;
-        if nbytes < buffer.len() {
-            break;
-        }
-    }
-
-    // 插入尾部标识
-    target_file.write_all(&END_IDENTIFIER)?;
-    // 确保所有数据都写入磁盘
-    target_file.flush()?;
-
-    // 清除临时压缩资源
-    if temp_file_path.exists() {
-        fs::remove_file(temp_file_path)?;
-    }
-    Ok(())
+    check_id_available(target_file_path, id)?;
+    let (mut source_file, head) = open_source(target_file_path, source_file_path, id, options)?;
+    let source_size = source_file.metadata()?.len();
+    append_resource(
+        target_file_path,
+        Payload::Seekable(&mut source_file),
+        Some(source_size),
+        head,
+        options,
+        progress,
+    )
 }
 
-/// 释放资源
+/// 打开资源文件并创建资源头（收集文件名与元数据）
 ///
 /// # 参数
 /// - `target_file_path`: 目标文件路径
+/// - `source_file_path`: 资源文件路径
 /// - `id`: 资源ID
-/// - `output_path`: 输出路径
+/// - `options`: 增加资源选项
 ///
 /// # 返回值
-/// - Ok(())
+/// - Ok((File, ResourceHead)): (资源文件, 资源头)
 /// - Err(err)
-pub fn export_resource(target_file_path: &Path, id: &str, output_path: &Path) -> Result<()> {
-    let magic_finder = memmem::Finder::new(RESOURCE_MAGIC);
-
-    // 打开目标文件
-    let mut source_file = File::open(target_file_path)?;
-    let file_len = source_file.metadata()?.len();
-
-    // 优化：使用更大的缓冲区，并保留重叠区域以避免遗漏跨边界的魔数
-    const SEARCH_BUFFER_SIZE: usize = 1024 * 512; // 512KB 搜索缓冲区
-    const MAX_HEADER_SIZE: usize = 4096; // 最大可能的资源头大小
-    let overlap_size = MAX_HEADER_SIZE + RESOURCE_MAGIC.len(); // 重叠区域大小
-
-    let mut buffer = Vec::with_capacity(SEARCH_BUFFER_SIZE + overlap_size);
-    let mut file_offset: u64 = 0; // 当前缓冲区在文件中的起始位置
-
-    loop {
-        // 读取数据到缓冲区
-        buffer.clear();
-        buffer.resize(SEARCH_BUFFER_SIZE, 0);
-
-        source_file.seek(SeekFrom::Start(file_offset))?;
-        let bytes_read = source_file.read(&mut buffer)?;
-        buffer.truncate(bytes_read);
-
-        if bytes_read == 0 {
-            return Err(anyhow!("Resource not found"));
-        }
-
-        // 在缓冲区中搜索魔数
-        let mut search_start = 0;
-        while let Some(relative_pos) = magic_finder.find(&buffer[search_start..]) {
-            let absolute_pos = search_start + relative_pos;
-            let resource_start = file_offset as usize + absolute_pos;
-
-            // 尝试读取资源头
-            // 需要跳过魔数本身，然后才解析 ResourceHead
-            let magic_len = RESOURCE_MAGIC.len();
-            let config = if absolute_pos + magic_len + MAX_HEADER_SIZE <= buffer.len() {
-                // 资源头完全在当前缓冲区内，跳过魔数后解析
-                ResourceHead::from(&buffer[absolute_pos + magic_len..])
-            } else {
-                // 资源头可能超出缓冲区，需要从文件读取
-                source_file.seek(SeekFrom::Start((resource_start + magic_len) as u64))?;
-                let mut header_buffer = vec![0u8; MAX_HEADER_SIZE];
-                // 读取尽可能多的字节，而不是要求完整的 MAX_HEADER_SIZE
-                let n = source_file.read(&mut header_buffer)?;
-                if n == 0 {
-                    search_start = absolute_pos + 1;
-                    continue;
-                }
-                ResourceHead::from(&header_buffer[..n])
-            };
-
-            let config = match config {
-                Ok(c) => c,
-                Err(_) => {
-                    // 不是有效的资源头，继续搜索
-                    search_start = absolute_pos + 1;
-                    continue;
-                }
-            };
-
-            // 检查 ID 是否匹配
-            if config.id.trim() == id.trim() {
-                // 找到目标资源
-
-                // 验证版本
-                let default_resource_head = ResourceHead::default();
-                let version_ordering =
-                    compare_version(&config.version, &default_resource_head.version)?;
-                if version_ordering.is_ne() {
-                    return Err(anyhow!(
-                        "Resource version mismatch: file has {}, program supports {}",
-                        &config.version,
-                        &default_resource_head.version
-                    ));
-                }
-
-                let magic_len = RESOURCE_MAGIC.len();
-                let header_len = config.get_len();
-                let resource_length = config
-                    .length
-                    .trim()
-                    .parse::<usize>()
-                    .map_err(|e| anyhow!("Failed to parse resource length: {}", e))?;
-
-                // 验证资源完整性（检查结束标识）
-                let end_pos = resource_start + magic_len + header_len + resource_length;
-                if end_pos + END_IDENTIFIER.len() > file_len as usize {
-                    return Err(anyhow!("Resource extends beyond file boundary"));
-                }
-
-                source_file.seek(SeekFrom::Start((end_pos) as u64))?;
-                let mut end_buffer = [0u8; END_IDENTIFIER.len()];
-                source_file.read_exact(&mut end_buffer)?;
-                if end_buffer != END_IDENTIFIER {
-                    return Err(anyhow!(
-                        "Resource end marker not found - file may be corrupted"
-                    ));
-                }
-
-                // 准备输出路径
-                let output_path_buf = if output_path.is_relative() {
-                    target_file_path
-                        .parent()
-                        .ok_or_else(|| anyhow!("Target file has no parent directory"))?
-                        .join(output_path)
-                } else {
-                    output_path.to_path_buf()
-                };
-                let output_path_buf = if output_path_buf.is_dir() {
-                    output_path_buf.join(config.name.trim())
-                } else {
-                    output_path_buf
-                };
-
-                // 读取资源数据
-                source_file.seek(SeekFrom::Start(
-                    (resource_start + magic_len + header_len) as u64,
-                ))?;
-                let mut output_file = File::create(&output_path_buf)?;
-
-                // 使用固定大小的缓冲区读取资源数据
-                let mut data_buffer = vec![0u8; BUFFER_SIZE.min(resource_length)];
-                let mut remaining = resource_length;
-
-                while remaining > 0 {
-                    let to_read = data_buffer.len().min(remaining);
-                    data_buffer.truncate(to_read);
-                    source_file.read_exact(&mut data_buffer)?;
-                    output_file.write_all(&data_buffer)?;
-                    remaining -= to_read;
-                }
-
-                // 处理压缩资源
-                if config.compress == CompressMode::Compress {
-                    let actual_file = output_path_buf
-                        .parent()
-                        .ok_or_else(|| anyhow!("Output path has no parent directory"))?
-                        .join("actualFile");
-                    decompress_file(&output_path_buf, &actual_file)?;
-                    fs::remove_file(&output_path_buf)?;
-                    fs::rename(actual_file, &output_path_buf)?;
-                }
-
-                // 验证输出文件大小
-                let expected_size = config.size.trim().parse::<u64>()?;
-                if output_file.metadata()?.len() != expected_size {
-                    fs::remove_file(&output_path_buf)?;
-                    return Err(anyhow!(
-                        "Exported file size mismatch: expected {}, got {}",
-                        expected_size,
-                        output_file.metadata()?.len()
-                    ));
-                }
-
-                return Ok(());
-            }
-
-            // ID 不匹配，继续搜索下一个可能的魔数
-            search_start = absolute_pos + 1;
-        }
+pub(crate) fn open_source(
+    target_file_path: &Path,
+    source_file_path: &Path,
+    id: &str,
+    options: &AddOptions,
+) -> Result<(File, ResourceHead)> {
+    // 打开资源文件
+    let source_file_path_buf = resolve_path(target_file_path, source_file_path)?;
+    let source_file = File::open(&source_file_path_buf)?;
+    let source_name = match &options.name {
+        Some(name) => name.clone(),
+        None => source_file_path_buf
+            .file_name()
+            .ok_or_else(|| Error::InvalidName("Source file has no valid filename".to_string()))?
+            .to_string_lossy()
+            .to_string(),
+    };
 
-        // 移动到下一个位置，保留重叠区域以防魔数跨边界
-        if file_offset as usize + SEARCH_BUFFER_SIZE >= file_len as usize {
-            // 已到文件末尾
-            return Err(anyhow!("Resource not found"));
-        }
+    // 收集资源元数据
+    let source_metadata = source_file.metadata()?;
+    let metadata = Metadata {
+        modified: file_modified(&source_metadata),
+        permissions: file_permissions(&source_metadata),
+        mime: None,
+        tags: options.tags.clone(),
+    };
 
-        file_offset += SEARCH_BUFFER_SIZE as u64 - overlap_size as u64;
-    }
+    let head =
+        ResourceHead::new(id, 0, 0, &source_name, compress_mode(options))?.with_metadata(metadata);
+    Ok((source_file, head))
 }
 
-/// 寻找资源配置 - 从头至尾
+/// 从数据流增加资源(如标准输入)
+///
+/// 资源长度在写入完成后回填到资源头中，因此无需预先知道数据大小。
 ///
 /// # 参数
 /// - `target_file_path`: 目标文件路径
-/// - `callback`: 回调函数(配置位置, 资源配置)
+/// - `reader`: 资源数据流
+/// - `id`: 资源ID（不可重复）
+/// - `options`: 增加资源选项
+/// - `progress`: 进度回调
 ///
 /// # 返回值
-/// - `Vec<ResourceHead>`: 资源配置列表
+/// - Ok(())
 /// - Err(err)
-pub fn find_resources_config(
+pub fn add_resource_from_reader(
     target_file_path: &Path,
-    callback: fn(start_size: usize, config: &ResourceHead),
-) -> Result<Vec<ResourceHead>> {
-    let magic_finder = memmem::Finder::new(RESOURCE_MAGIC);
-
-    // 打开目标文件
-    let mut source_file = File::open(target_file_path)?;
-    let file_len = source_file.metadata()?.len();
-
-    // 优化：使用更大的缓冲区，并保留重叠区域
-    const SEARCH_BUFFER_SIZE: usize = 1024 * 512; // 512KB 搜索缓冲区
-    const MAX_HEADER_SIZE: usize = 4096; // 最大可能的资源头大小
-    let overlap_size = MAX_HEADER_SIZE + RESOURCE_MAGIC.len(); // 重叠区域大小
-
-    let mut buffer = Vec::with_capacity(SEARCH_BUFFER_SIZE + overlap_size);
-    let mut file_offset: u64 = 0;
-
-    let mut configs = Vec::new();
-
-    loop {
-        // 读取数据到缓冲区
-        buffer.clear();
-        buffer.resize(SEARCH_BUFFER_SIZE, 0);
-
-        source_file.seek(SeekFrom::Start(file_offset))?;
-        let bytes_read = source_file.read(&mut buffer)?;
-        buffer.truncate(bytes_read);
-
-        if bytes_read == 0 {
-            break;
-        }
-
-        // 在缓冲区中搜索魔数
-        let mut search_start = 0;
-        while let Some(relative_pos) = magic_finder.find(&buffer[search_start..]) {
-            let absolute_pos = search_start + relative_pos;
-            let resource_start = file_offset as usize + absolute_pos;
-
-            // 尝试读取资源头
-            // 需要跳过魔数本身，然后才解析 ResourceHead
-            let magic_len = RESOURCE_MAGIC.len();
-            let config = if absolute_pos + magic_len + MAX_HEADER_SIZE <= buffer.len() {
-                // 资源头完全在当前缓冲区内，跳过魔数后解析
-                ResourceHead::from(&buffer[absolute_pos + magic_len..])
-            } else {
-                // 资源头可能超出缓冲区，从文件读取
-                source_file.seek(SeekFrom::Start((resource_start + magic_len) as u64))?;
-                let mut header_buffer = vec![0u8; MAX_HEADER_SIZE];
-                // 读取尽可能多的字节，而不是要求完整的 MAX_HEADER_SIZE
-                let n = source_file.read(&mut header_buffer)?;
-                if n == 0 {
-                    search_start = absolute_pos + 1;
-                    continue;
-                }
-                ResourceHead::from(&header_buffer[..n])
-            };
-
-            let config = match config {
-                Ok(c) => c,
-                Err(_) => {
-                    // 不是有效的资源头，继续搜索
-                    search_start = absolute_pos + 1;
-                    continue;
-                }
-            };
-
-            callback(resource_start, &config);
-            configs.push(config);
-
-            // 继续搜索下一个可能的魔数
-            search_start = absolute_pos + 1;
-        }
-
-        // 移动到下一个位置，保留重叠区域
-        if file_offset as usize + SEARCH_BUFFER_SIZE >= file_len as usize {
-            break;
-        }
-
-        file_offset += SEARCH_BUFFER_SIZE as u64 - overlap_size as u64;
-    }
-
-    Ok(configs)
+    reader: &mut dyn Read,
+    id: &str,
+    options: &AddOptions,
+    progress: ProgressCallback,
+) -> Result<()> {
+    check_id_available(target_file_path, id)?;
+    let head = stream_head(id, options)?;
+    append_resource(
+        target_file_path,
+        Payload::Stream(reader),
+        None,
+        head,
+        options,
+        progress,
+    )
 }
 
-/// 寻找字节（速度较慢）
+/// 向任意读写后端增加资源(如内存缓冲区)
+///
+/// 资源文件的相对路径相对于当前工作目录；读写后端不支持分卷存储与输出路径。
 ///
 /// # 参数
-/// - `haystack`: 主字符串
-/// - `needle`: 子字符串
+/// - `backend`: 读写后端(如 `File` 或 `Cursor<Vec<u8>>`)
+/// - `source_file_path`: 资源文件路径
+/// - `id`: 资源ID（不可重复）
+/// - `options`: 增加资源选项
+/// - `progress`: 进度回调
 ///
 /// # 返回值
-/// - `Option<usize>`: 返回找到的字节位置
-fn _find_subsequence(haystack: &[u8], needle: &[u8]) -> Option<usize> {
-    haystack
-        .windows(needle.len())
-        .position(|window| window == needle)
+/// - Ok(())
+/// - Err(err)
+pub fn add_resource_to_backend(
+    backend: &mut dyn Backend,
+    source_file_path: &Path,
+    id: &str,
+    options: &AddOptions,
+    progress: ProgressCallback,
+) -> Result<()> {
+    let mut container = Container::from_backend(backend)?;
+    container.add(source_file_path, id, options)?;
+    container.commit(options.output_path.as_deref(), progress)
 }
 
-/// 删除资源
+/// 从数据流向任意读写后端增加资源
 ///
 /// # 参数
-/// - `target_file_path`: 目标文件路径
-/// - `id`: 资源ID
-/// - `output_path`: 输出文件路径(可选)
+/// - `backend`: 读写后端
+/// - `reader`: 资源数据流
+/// - `id`: 资源ID（不可重复）
+/// - `options`: 增加资源选项
+/// - `progress`: 进度回调
 ///
 /// # 返回值
 /// - Ok(())
 /// - Err(err)
-pub fn remove_resource(
-    target_file_path: &Path,
+pub fn add_resource_from_reader_to_backend(
+    backend: &mut dyn Backend,
+    reader: impl Read + 'static,
     id: &str,
-    output_path: Option<&Path>,
+    options: &AddOptions,
+    progress: ProgressCallback,
 ) -> Result<()> {
-    let magic_finder = memmem::Finder::new(RESOURCE_MAGIC);
-
-    // 打开目标文件
-    let mut source_file = File::open(target_file_path)?;
-    let file_len = source_file.metadata()?.len();
-
-    // 读取整个文件到内存
-    let mut file_data = Vec::with_capacity(file_len as usize);
-    source_file.read_to_end(&mut file_data)?;
+    let mut container = Container::from_backend(backend)?;
+    container.add_reader(reader, id, options)?;
+    container.commit(options.output_path.as_deref(), progress)
+}
 
-    // 搜索目标资源
-    const MAX_HEADER_SIZE: usize = 4096;
-    let mut resource_start: Option<usize> = None;
-    let mut resource_end: Option<usize> = None;
-
-    let mut search_pos = 0;
-    while let Some(relative_pos) = magic_finder.find(&file_data[search_pos..]) {
-        let absolute_pos = search_pos + relative_pos;
-
-        // 尝试读取资源头
-        let magic_len = RESOURCE_MAGIC.len();
-        let config = if absolute_pos + magic_len + MAX_HEADER_SIZE <= file_data.len() {
-            ResourceHead::from(&file_data[absolute_pos + magic_len..])
-        } else {
-            let available = file_data.len() - absolute_pos - magic_len;
-            if available == 0 {
-                search_pos = absolute_pos + 1;
-                continue;
-            }
-            ResourceHead::from(&file_data[absolute_pos + magic_len..])
-        };
+/// 创建数据流资源的资源头(文件名默认取资源ID)
+///
+/// # 参数
+/// - `id`: 资源ID
+/// - `options`: 增加资源选项
+///
+/// # 返回值
+/// - Ok(ResourceHead): 资源头
+/// - Err(err): 资源ID或文件名超过长度上限
+pub(crate) fn stream_head(id: &str, options: &AddOptions) -> Result<ResourceHead> {
+    let metadata = Metadata {
+        tags: options.tags.clone(),
+        ..Metadata::default()
+    };
+    let name = options.name.as_deref().unwrap_or(id);
+    Ok(ResourceHead::new(id, 0, 0, name, compress_mode(options))?.with_metadata(metadata))
+}
 
-        let config = match config {
-            Ok(c) => c,
-            Err(_) => {
-                search_pos = absolute_pos + 1;
-                continue;
+/// 检查资源ID是否未被使用
+///
+/// # 参数
+/// - `target_file_path`: 目标文件路径
+/// - `id`: 资源ID
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err): 已存在相同ID的资源
+pub(crate) fn check_id_available(target_file_path: &Path, id: &str) -> Result<()> {
+    let mut source_file = FileSource::open(target_file_path)?;
+    let existing = locate_resource_by(&mut source_file, &mut |_| {}, |_, config| {
+        !config.is_hidden() && config.id.trim() == id.trim()
+    })?;
+    match existing {
+        Some(_) => Err(Error::DuplicateId(id.trim().to_string())),
+        None => Ok(()),
+    }
+}
+
+/// 更新资源(以资源文件替换同 ID 资源的数据)
+///
+/// 默认删除旧版本并追加完整的新版本(一次重写完成，失败时目标文件保持不变)；
+/// `delta` 为 true 时只追加相对旧版本的差异补丁，
+/// 旧版本标记为已取代并保留，导出时自动还原最新版本。
+/// 旧版资源(没有校验和)或差异补丁不小于新版本时改为完整更新。
+///
+/// # 参数
+/// - `target_file_path`: 目标文件路径
+/// - `source_file_path`: 新版本资源文件路径
+/// - `id`: 资源ID
+/// - `options`: 增加资源选项
+/// - `delta`: 是否以差异补丁存储新版本
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err)
+pub fn update_resource(
+    target_file_path: &Path,
+    source_file_path: &Path,
+    id: &str,
+    options: &AddOptions,
+    delta: bool,
+    progress: ProgressCallback,
+) -> Result<()> {
+    let mut source_file = FileSource::open(target_file_path)?;
+    let (base_start, mut base) = locate_resource(&mut source_file, id, &mut *progress)?;
+    if delta && !base.checksum.is_empty() {
+        let (mut reader, mut head) = open_source(target_file_path, source_file_path, id, options)?;
+        let source_size = reader.metadata()?.len();
+        let mut data = Vec::new();
+        ProgressReader::new(
+            &mut reader,
+            ProgressTracker::new(&mut *progress, Stage::Add, Some(source_size)),
+        )
+        .read_to_end(&mut data)?;
+
+        // 计算相对旧版本的差异
+        let mut base_data = Vec::new();
+        read_resource_data(
+            &mut source_file,
+            base_start,
+            &base,
+            &mut base_data,
+            &mut |_| {},
+        )?;
+        drop(source_file);
+        let patch = bincode::serialize(&DeltaPatch {
+            base_checksum: base.checksum.clone(),
+            ops: delta::diff(&base_data, &data),
+        })?;
+        if patch.len() < data.len() {
+            if head.metadata.mime.is_none() {
+                let sniff = &data[..data.len().min(MIME_SNIFF_SIZE)];
+                head.metadata.mime = Some(detect_mime(sniff, &head.name));
+            }
+            head.storage = Storage::Delta;
+            base.superseded = true;
+
+            // 输出到新文件时先复制目标文件，失败时删除复制的文件
+            let output_path = match &options.output_path {
+                Some(output_path) => {
+                    let output_path = resolve_path(target_file_path, output_path)?;
+                    fs::copy(target_file_path, &output_path)?;
+                    output_path
+                }
+                None => target_file_path.to_path_buf(),
+            };
+            let result = append_delta(
+                &output_path,
+                &patch,
+                &data,
+                head,
+                (base_start, &base),
+                options,
+            );
+            if result.is_err() && options.output_path.is_some() {
+                let _ = fs::remove_file(&output_path);
             }
+            return result;
+        }
+    } else {
+        drop(source_file);
+    }
+
+    // 完整更新：删除旧版本(包括差异更新保留的旧版本)并追加新版本，一次重写完成
+    let mut container = Container::open(target_file_path)?;
+    container.update(source_file_path, id, options)?;
+    container.commit(options.output_path.as_deref(), progress)
+}
+
+/// 更新任意读写后端中的资源（删除旧版本并追加完整的新版本，不支持差异更新）
+///
+/// # 参数
+/// - `backend`: 读写后端
+/// - `source_file_path`: 新版本资源文件路径(相对路径相对于当前工作目录)
+/// - `id`: 资源ID
+/// - `options`: 增加资源选项
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err)
+pub fn update_resource_in_backend(
+    backend: &mut dyn Backend,
+    source_file_path: &Path,
+    id: &str,
+    options: &AddOptions,
+    progress: ProgressCallback,
+) -> Result<()> {
+    let mut container = Container::from_backend(backend)?;
+    container.update(source_file_path, id, options)?;
+    container.commit(options.output_path.as_deref(), progress)
+}
+
+/// 追加差异补丁资源，并将旧版本标记为已取代
+///
+/// # 参数
+/// - `target_file_path`: 目标文件路径
+/// - `patch`: 差异补丁(序列化后)
+/// - `data`: 新版本数据
+/// - `head`: 新版本资源头
+/// - `base`: (旧版本起始位置, 已标记为取代的旧版本资源头)
+/// - `options`: 增加资源选项(压缩等级与线程数)
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err)
+fn append_delta(
+    target_file_path: &Path,
+    patch: &[u8],
+    data: &[u8],
+    mut head: ResourceHead,
+    base: (usize, &ResourceHead),
+    options: &AddOptions,
+) -> Result<()> {
+    check_head_size(&mut head)?;
+
+    let mut target_file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(target_file_path)?;
+    let original_len = target_file.seek(SeekFrom::End(0))?;
+    let magic_len = RESOURCE_MAGIC.len() as u64;
+    let result = (|| -> Result<()> {
+        write_resource(&mut target_file, &mut &*patch, &mut head, options)?;
+
+        // 大小与校验和按还原后的数据回填(资源头长度不变)
+        head.set_size(data.len() as u64);
+        head.set_checksum(checksum(data));
+        target_file.seek(SeekFrom::Start(original_len + magic_len))?;
+        target_file.write_all(&head.to_bytes()?)?;
+
+        // 标记旧版本(定宽字段，不改变资源头长度)
+        let (base_start, base) = base;
+        target_file.seek(SeekFrom::Start(base_start as u64 + magic_len))?;
+        target_file.write_all(&base.to_bytes()?)?;
+        target_file.flush()?;
+        Ok(())
+    })();
+    if let Err(e) = result {
+        target_file.set_len(original_len)?;
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// 读取数据开头并检测 MIME 类型(资源头未指定时)
+///
+/// # 参数
+/// - `reader`: 资源数据流
+/// - `head`: 资源头
+///
+/// # 返回值
+/// - `Ok(Vec<u8>)`: 已读取的数据开头(需与剩余数据拼接)
+/// - Err(err)
+pub(crate) fn sniff_mime(reader: &mut dyn Read, head: &mut ResourceHead) -> Result<Vec<u8>> {
+    let mut header = Vec::with_capacity(MIME_SNIFF_SIZE);
+    reader
+        .take(MIME_SNIFF_SIZE as u64)
+        .read_to_end(&mut header)?;
+    if head.metadata.mime.is_none() {
+        head.metadata.mime = Some(detect_mime(&header, &head.name));
+    }
+    Ok(header)
+}
+
+/// 以占位校验和校验资源头大小（长度与校验和字段为定宽，回填后长度不变）
+///
+/// # 参数
+/// - `head`: 资源头
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err): 资源头超过大小上限
+pub(crate) fn check_head_size(head: &mut ResourceHead) -> Result<()> {
+    head.set_checksum("0".repeat(CHECKSUM_LENGTH));
+    if head.get_len() > MAX_HEADER_SIZE {
+        return Err(Error::InvalidInput(format!(
+            "Resource header exceeds maximum size of {} bytes (too many tags?)",
+            MAX_HEADER_SIZE
+        )));
+    }
+    Ok(())
+}
+
+/// 根据选项获取压缩模式
+pub(crate) fn compress_mode(options: &AddOptions) -> CompressMode {
+    match options.compression_grade.is_some() {
+        true => CompressMode::Compress,
+        false => CompressMode::None,
+    }
+}
+
+/// 追加资源到目标文件末尾
+///
+/// # 参数
+/// - `target_file_path`: 目标文件路径
+/// - `payload`: 资源数据
+/// - `source_size`: 资源大小(未知时为 None)
+/// - `head`: 资源头(未指定 MIME 类型时根据数据自动检测)
+/// - `options`: 增加资源选项
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err)
+fn append_resource(
+    target_file_path: &Path,
+    payload: Payload,
+    source_size: Option<u64>,
+    mut head: ResourceHead,
+    options: &AddOptions,
+    progress: ProgressCallback,
+) -> Result<()> {
+    // 打开目标文件
+    let target_file_path_buf = if let Some(output_path_param) = &options.output_path {
+        let output_path_buf = resolve_path(target_file_path, output_path_param)?;
+        fs::copy(target_file_path, &output_path_buf)?;
+        output_path_buf
+    } else {
+        target_file_path.to_path_buf()
+    };
+    let records = all_records(&mut FileSource::open(&target_file_path_buf)?)?;
+    let mut target_file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(&target_file_path_buf)?;
+    let original_len = target_file.seek(SeekFrom::End(0))?;
+
+    // 写入失败时截断已写入的部分，保持目标文件不变
+    let result = AppendTarget::new(
+        Some(&target_file_path_buf),
+        &mut target_file,
+        records.iter().map(|(_, config)| config),
+    )
+    .append(payload, source_size, &mut head, options, progress);
+    if let Err(e) = result {
+        target_file.set_len(original_len)?;
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// 追加的资源数据
+pub(crate) enum Payload<'a> {
+    /// 可随机读取的数据(如资源文件)，去重时先计算校验和再从头读取
+    Seekable(&'a mut dyn ReadSeek),
+    /// 只能顺序读取的数据流(如标准输入)，去重时先缓存数据并计算校验和
+    Stream(&'a mut dyn Read),
+}
+
+/// 临时文件（释放时关闭并删除文件）
+struct TempFile {
+    file: Option<File>,
+    path: PathBuf,
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        // 先关闭文件，部分平台无法删除打开中的文件
+        drop(self.file.take());
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// 缓存的数据流（不超过内存上限时缓存在内存中，否则缓存到临时文件）
+enum Spool {
+    Memory(io::Cursor<Vec<u8>>),
+    File(TempFile),
+}
+
+impl Spool {
+    /// 读取并缓存全部数据
+    ///
+    /// # 参数
+    /// - `reader`: 数据流
+    ///
+    /// # 返回值
+    /// - Ok((Spool, u64, String)): (缓存, 数据大小, 校验和)
+    /// - Err(err)
+    fn new(reader: &mut dyn Read) -> Result<(Self, u64, String)> {
+        let mut input = HashingReader::new(CountingReader::new(reader));
+        let mut buffer = Vec::new();
+        (&mut input)
+            .take(SPOOL_MEMORY_SIZE as u64 + 1)
+            .read_to_end(&mut buffer)?;
+        let spool = if buffer.len() <= SPOOL_MEMORY_SIZE {
+            Spool::Memory(io::Cursor::new(buffer))
+        } else {
+            let (path, file) = create_temp_file(&std::env::temp_dir().join("appender-spool"))?;
+            let mut temp = TempFile {
+                file: Some(file),
+                path,
+            };
+            let file = temp.file.as_mut().expect("temporary file is open");
+            let mut writer = BufWriter::with_capacity(BUFFER_SIZE, &mut *file);
+            writer.write_all(&buffer)?;
+            copy(&mut input, &mut writer)?;
+            writer.flush()?;
+            drop(writer);
+            file.seek(SeekFrom::Start(0))?;
+            Spool::File(temp)
         };
+        let size = input.get_ref().count();
+        Ok((spool, size, input.checksum()))
+    }
+}
+
+impl Read for Spool {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Spool::Memory(cursor) => cursor.read(buf),
+            Spool::File(temp) => temp.file.as_mut().map_or(Ok(0), |file| file.read(buf)),
+        }
+    }
+}
+
+/// 追加资源的目标文件（记录已存储的数据，追加多个资源时无需重复扫描目标文件）
+pub(crate) struct AppendTarget<'a> {
+    /// 目标文件路径(分卷文件位于同一目录；内存缓冲区等没有路径的后端为 None)
+    path: Option<&'a Path>,
+    /// 目标文件
+    file: &'a mut dyn Backend,
+    /// 存储数据且未被取代的资源(可被引用)
+    resources: Vec<ResourceHead>,
+    /// 数据块校验和
+    chunks: HashSet<String>,
+    /// 是否只计算写入长度(不创建分卷文件，用于预演)
+    measure_only: bool,
+    /// 已写入的记录与分卷文件
+    changes: Vec<Change>,
+}
 
-        // 检查 ID 是否匹配
-        if config.id.trim() == id.trim() {
-            let magic_len = RESOURCE_MAGIC.len();
-            let header_len = config.get_len();
-            let resource_length = config
-                .length
-                .trim()
-                .parse::<usize>()
-                .map_err(|e| anyhow!("Failed to parse resource length: {}", e))?;
-
-            // 计算资源结束位置
-            let end_pos = absolute_pos + magic_len + header_len + resource_length;
-
-            // 验证结束标识
-            if end_pos + END_IDENTIFIER.len() > file_data.len() {
-                return Err(anyhow!("Resource extends beyond file boundary"));
+impl<'a> AppendTarget<'a> {
+    /// 创建追加目标
+    ///
+    /// # 参数
+    /// - `path`: 目标文件路径(分卷文件位于同一目录；没有路径时不支持分卷存储)
+    /// - `file`: 目标文件
+    /// - `records`: 目标文件中保留的资源配置
+    pub(crate) fn new<'b>(
+        path: Option<&'a Path>,
+        file: &'a mut dyn Backend,
+        records: impl IntoIterator<Item = &'b ResourceHead>,
+    ) -> Self {
+        let mut resources = Vec::new();
+        let mut chunks = HashSet::new();
+        for config in records {
+            match config.storage {
+                Storage::Inline if !config.superseded => resources.push(config.clone()),
+                Storage::Chunk => {
+                    chunks.insert(config.checksum.clone());
+                }
+                _ => {}
             }
+        }
+        AppendTarget {
+            path,
+            file,
+            resources,
+            chunks,
+            measure_only: false,
+            changes: Vec::new(),
+        }
+    }
+
+    /// 只计算写入长度：分卷数据写入空设备，不创建分卷文件(目标文件应为丢弃数据的后端)
+    pub(crate) fn measure_only(mut self) -> Self {
+        self.measure_only = true;
+        self
+    }
+
+    /// 获取已写入的记录与分卷文件(按写入顺序)
+    pub(crate) fn into_changes(self) -> Vec<Change> {
+        self.changes
+    }
 
-            if file_data[end_pos..end_pos + END_IDENTIFIER.len()] != END_IDENTIFIER {
-                return Err(anyhow!(
-                    "Resource end marker not found - file may be corrupted"
-                ));
+    /// 在目标文件末尾写入资源（按选项分卷、分块或去重存储）
+    ///
+    /// 完整存储数据时先计算校验和，已存储相同数据时只写入引用，不写入数据。
+    ///
+    /// # 参数
+    /// - `payload`: 资源数据
+    /// - `source_size`: 资源大小(未知时为 None)
+    /// - `head`: 资源头(未指定 MIME 类型时根据数据自动检测，写入完成后回填)
+    /// - `options`: 增加资源选项
+    /// - `progress`: 进度回调
+    ///
+    /// # 返回值
+    /// - Ok(())
+    /// - Err(err): 写入失败(已写入的部分需由调用方截断)
+    pub(crate) fn append(
+        &mut self,
+        payload: Payload,
+        source_size: Option<u64>,
+        head: &mut ResourceHead,
+        options: &AddOptions,
+        progress: ProgressCallback,
+    ) -> Result<()> {
+        let deduplicate =
+            options.split_size.is_none() && !options.chunked && !self.resources.is_empty();
+        let mut spool = None;
+        let (reader, known): (&mut dyn Read, _) = match payload {
+            Payload::Seekable(reader) if deduplicate => {
+                let start = reader.stream_position()?;
+                let mut input = HashingReader::new(CountingReader::new(&mut *reader));
+                copy(&mut input, &mut io::sink())?;
+                let size = input.get_ref().count();
+                let checksum = input.checksum();
+                reader.seek(SeekFrom::Start(start))?;
+                (reader, Some((size, checksum)))
             }
+            Payload::Stream(reader) if deduplicate => {
+                let (spooled, size, checksum) = Spool::new(reader)?;
+                (spool.insert(spooled), Some((size, checksum)))
+            }
+            Payload::Seekable(reader) => (reader, None),
+            Payload::Stream(reader) => (reader, None),
+        };
 
-            resource_start = Some(absolute_pos);
-            resource_end = Some(end_pos + END_IDENTIFIER.len());
-            break;
+        // 读取数据开头用于检测 MIME 类型，随后与剩余数据拼接
+        let header = sniff_mime(reader, head)?;
+        let mut tracker = ProgressTracker::new(progress, Stage::Add, source_size);
+        check_head_size(head)?;
+
+        // 已存在校验和相同的资源时只写入引用(只保留资源头)
+        if let Some((size, checksum)) = known
+            && let Some(existing) = self
+                .resources
+                .iter()
+                .find(|config| config.id != head.id && config.checksum == checksum)
+        {
+            head.storage = Storage::Reference;
+            head.compress = existing.compress;
+            head.set_size(size);
+            head.set_checksum(checksum);
+            let offset = self.file.seek(SeekFrom::End(0))?;
+            let mut writer = BufWriter::new(&mut *self.file);
+            write_record(&mut writer, head, &mut io::empty())?;
+            writer.flush()?;
+            self.changes.push(Change::Append {
+                id: head.id.trim().to_string(),
+                storage: Storage::Reference,
+                offset,
+                length: record_len(head, 0),
+            });
+            tracker.set(size);
+            tracker.report();
+            return Ok(());
+        }
+
+        let reader = header.as_slice().chain(reader);
+        let mut reader = ProgressReader::new(reader, tracker);
+        let mut volumes = Vec::new();
+        if let Some(split_size) = options.split_size {
+            let path = self.path.ok_or_else(|| {
+                Error::Unsupported(
+                    "Split resources can only be written to files on disk".to_string(),
+                )
+            })?;
+            self.file.seek(SeekFrom::End(0))?;
+            volumes = write_split_resource(
+                path,
+                self.file,
+                &mut reader,
+                head,
+                options,
+                split_size,
+                self.measure_only,
+            )?;
+        } else if options.chunked {
+            write_chunked_resource(
+                self.file,
+                &mut reader,
+                head,
+                options,
+                &mut self.chunks,
+                &mut self.changes,
+            )?;
+        } else {
+            self.file.seek(SeekFrom::End(0))?;
+            write_resource(self.file, &mut reader, head, options)?;
+            self.resources.push(head.clone());
         }
+        // 资源记录位于目标文件末尾(分块存储时位于新数据块之后)
+        let length = record_len(head, head.length.trim().parse().unwrap_or(0));
+        let offset = self.file.seek(SeekFrom::End(0))? - length;
+        self.changes.push(Change::Append {
+            id: head.id.trim().to_string(),
+            storage: head.storage,
+            offset,
+            length,
+        });
+        self.changes.extend(
+            volumes
+                .into_iter()
+                .map(|(path, length)| Change::CreateVolume { path, length }),
+        );
+        Ok(())
+    }
+}
 
-        search_pos = absolute_pos + 1;
+/// 分块写入资源：先追加目标文件中尚不存在的数据块，再追加记录数据块列表的资源
+///
+/// # 参数
+/// - `target_file`: 目标文件
+/// - `reader`: 资源数据流
+/// - `head`: 资源头(写入完成后回填长度、大小与校验和)
+/// - `options`: 增加资源选项(数据块的压缩等级与线程数)
+/// - `known`: 目标文件中已存储的数据块校验和(写入后加入新数据块)
+/// - `changes`: 已写入的记录(写入后加入新数据块)
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err)
+fn write_chunked_resource(
+    target_file: &mut dyn Backend,
+    reader: &mut dyn Read,
+    head: &mut ResourceHead,
+    options: &AddOptions,
+    known: &mut HashSet<String>,
+    changes: &mut Vec<Change>,
+) -> Result<()> {
+    let mut input = HashingReader::new(CountingReader::new(reader));
+    let mut chunker = ContentChunker::new(&mut input);
+    let mut chunks = Vec::new();
+    while let Some(chunk) = chunker.next_chunk()? {
+        let chunk_checksum = checksum(&chunk);
+        if known.insert(chunk_checksum.clone()) {
+            let mut chunk_head = ResourceHead::new(&chunk_checksum, 0, 0, "", head.compress)?;
+            chunk_head.storage = Storage::Chunk;
+            chunk_head.set_checksum(chunk_checksum.clone());
+            let offset = target_file.seek(SeekFrom::End(0))?;
+            write_resource(target_file, &mut chunk.as_slice(), &mut chunk_head, options)?;
+            changes.push(Change::Append {
+                id: chunk_checksum.clone(),
+                storage: Storage::Chunk,
+                offset,
+                length: target_file.seek(SeekFrom::End(0))? - offset,
+            });
+        }
+        chunks.push(chunk_checksum);
+    }
+    drop(chunker);
+
+    let size = input.get_ref().count();
+    if size > MAX_LENGTH_SIZE {
+        return Err(Error::InvalidInput(format!(
+            "Resource exceeds maximum size of {} bytes",
+            MAX_LENGTH_SIZE
+        )));
     }
+    let list = bincode::serialize(&chunks)?;
+    head.storage = Storage::Chunked;
+    head.set_length(list.len() as u64);
+    head.set_size(size);
+    head.set_checksum(input.checksum());
+
+    target_file.seek(SeekFrom::End(0))?;
+    let mut writer = BufWriter::with_capacity(BUFFER_SIZE, &mut *target_file);
+    write_record(&mut writer, head, &mut list.as_slice())?;
+    writer.flush()?;
+    Ok(())
+}
 
-    let (start, end) = match (resource_start, resource_end) {
-        (Some(s), Some(e)) => (s, e),
-        _ => return Err(anyhow!("Resource not found")),
+/// 分卷写入资源：数据超过分卷大小后依次写入分卷文件，每个分卷文件为一个完整的资源
+///
+/// # 参数
+/// - `target_file_path`: 目标文件路径(分卷文件位于同一目录)
+/// - `target_file`: 目标文件(已定位到写入位置)
+/// - `reader`: 资源数据流
+/// - `head`: 资源头(写入完成后回填长度、大小、校验和与分卷信息)
+/// - `options`: 增加资源选项(压缩等级与线程数)
+/// - `split_size`: 分卷大小
+/// - `measure_only`: 是否只计算分卷长度(不创建分卷文件)
+///
+/// # 返回值
+/// - `Ok(Vec<(PathBuf, u64)>)`: (分卷文件路径, 分卷文件长度)列表
+/// - Err(err)
+fn write_split_resource(
+    target_file_path: &Path,
+    target_file: &mut dyn Backend,
+    reader: &mut dyn Read,
+    head: &mut ResourceHead,
+    options: &AddOptions,
+    split_size: u64,
+    measure_only: bool,
+) -> Result<Vec<(PathBuf, u64)>> {
+    if split_size == 0 {
+        return Err(Error::InvalidInput(
+            "Split size must be greater than zero".to_string(),
+        ));
+    }
+    let start = target_file.stream_position()?;
+    let magic_len = RESOURCE_MAGIC.len() as u64;
+
+    // 分卷序号接在已有分卷之后，避免与其他资源的分卷冲突
+    head.storage = Storage::Split;
+    head.volumes = Volumes {
+        first: next_volume_number(target_file_path)?,
+        count: 0,
     };
+    let mut volume_head = head.clone();
+    volume_head.storage = Storage::Volume;
+
+    let mut created = Vec::new();
+    let result = (|| -> Result<Vec<(PathBuf, u64)>> {
+        let mut writer = BufWriter::with_capacity(BUFFER_SIZE, &mut *target_file);
+        writer.write_all(RESOURCE_MAGIC)?;
+        writer.write_all(&head.to_bytes()?)?;
+
+        // 写入资源数据(宿主文件中的部分写满后转入分卷文件)
+        let mut input = HashingReader::new(CountingReader::new(reader));
+        let mut output = VolumeWriter {
+            host: &mut writer,
+            target_file_path,
+            head: &volume_head,
+            split_size,
+            lengths: vec![0],
+            volume: None,
+            created: &mut created,
+            measure_only,
+        };
+        match options.compression_grade {
+            Some(grade) if options.threads > 1 => {
+                parallel_compression_stream(&mut input, &mut output, grade, options.threads)?
+            }
+            Some(grade) => compression_stream(&mut input, &mut output, grade)?,
+            None => copy(&mut input, &mut output)?,
+        };
+        let lengths = output.finish()?;
+        let size = input.get_ref().count();
+        if size > MAX_LENGTH_SIZE {
+            return Err(Error::InvalidInput(format!(
+                "Resource exceeds maximum size of {} bytes",
+                MAX_LENGTH_SIZE
+            )));
+        }
 
-    // 构建新文件数据（移除资源部分）
-    let mut new_data = Vec::with_capacity(file_data.len() - (end - start));
-    new_data.extend_from_slice(&file_data[..start]);
-    new_data.extend_from_slice(&file_data[end..]);
+        // 插入宿主文件中的资源长度与尾部标识
+        writer.write_all(&record_len(head, lengths[0]).to_le_bytes())?;
+        writer.write_all(&END_IDENTIFIER)?;
+        writer.flush()?;
+        drop(writer);
+
+        // 回填宿主文件与各分卷文件中的资源头
+        let checksum = input.checksum();
+        for (index, length) in lengths.iter().enumerate() {
+            let head = if index == 0 {
+                &mut *head
+            } else {
+                &mut volume_head
+            };
+            head.volumes.count = (lengths.len() - 1) as u32;
+            head.set_length(*length);
+            head.set_size(size);
+            head.set_checksum(checksum.clone());
+            if index == 0 {
+                target_file.seek(SeekFrom::Start(start + magic_len))?;
+                target_file.write_all(&head.to_bytes()?)?;
+                target_file.flush()?;
+            } else if !measure_only {
+                let mut volume = OpenOptions::new().write(true).open(&created[index - 1])?;
+                volume.seek(SeekFrom::Start(magic_len))?;
+                volume.write_all(&head.to_bytes()?)?;
+                volume.flush()?;
+            }
+        }
+        Ok(created
+            .iter()
+            .cloned()
+            .zip(
+                lengths[1..]
+                    .iter()
+                    .map(|length| record_len(&volume_head, *length)),
+            )
+            .collect())
+    })();
+    if result.is_err() && !measure_only {
+        for path in &created {
+            let _ = fs::remove_file(path);
+        }
+    }
+    result
+}
 
-    // 确定输出路径
-    let output_path_buf = if let Some(output_path_param) = output_path {
-        if output_path_param.is_relative() {
-            target_file_path
-                .parent()
-                .ok_or_else(|| anyhow!("Target file has no parent directory"))?
-                .join(output_path_param)
+/// 分卷写入流
+///
+/// 当前文件写满分卷大小后关闭当前分卷(写入资源长度与尾部标识)，
+/// 再创建下一个分卷文件(写入魔数与占位资源头)。
+struct VolumeWriter<'a> {
+    /// 宿主文件中的资源数据流
+    host: &'a mut dyn Write,
+    /// 目标文件路径
+    target_file_path: &'a Path,
+    /// 分卷资源头(占位)
+    head: &'a ResourceHead,
+    /// 分卷大小
+    split_size: u64,
+    /// 各部分数据长度(宿主文件, 分卷文件...)
+    lengths: Vec<u64>,
+    /// 当前分卷文件
+    volume: Option<BufWriter<Box<dyn Write>>>,
+    /// 已创建的分卷文件
+    created: &'a mut Vec<PathBuf>,
+    /// 是否只计算分卷长度(分卷数据写入空设备，不创建分卷文件)
+    measure_only: bool,
+}
+
+impl VolumeWriter<'_> {
+    /// 关闭当前分卷并创建下一个分卷文件
+    fn next_volume(&mut self) -> io::Result<()> {
+        self.close_volume()?;
+        if self.created.len() as u32 >= MAX_VOLUMES {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Resource needs more than {} volumes, use a larger split size",
+                    MAX_VOLUMES
+                ),
+            ));
+        }
+        let number = self.head.volumes.first + self.created.len() as u32;
+        let path = volume_path(self.target_file_path, number);
+        let file: Box<dyn Write> = if self.measure_only {
+            Box::new(io::sink())
         } else {
-            output_path_param.to_path_buf()
+            Box::new(
+                OpenOptions::new()
+                    .write(true)
+                    .create_new(true)
+                    .open(&path)?,
+            )
+        };
+        let mut volume = BufWriter::with_capacity(BUFFER_SIZE, file);
+        self.created.push(path);
+        volume.write_all(RESOURCE_MAGIC)?;
+        volume.write_all(&self.head.to_bytes().map_err(io::Error::other)?)?;
+        self.volume = Some(volume);
+        self.lengths.push(0);
+        Ok(())
+    }
+
+    /// 写入当前分卷的资源长度与尾部标识
+    fn close_volume(&mut self) -> io::Result<()> {
+        if let Some(mut volume) = self.volume.take() {
+            let length = self.lengths.last().copied().unwrap_or(0);
+            volume.write_all(&record_len(self.head, length).to_le_bytes())?;
+            volume.write_all(&END_IDENTIFIER)?;
+            volume.flush()?;
         }
-    } else {
-        target_file_path.to_path_buf()
+        Ok(())
+    }
+
+    /// 完成写入
+    ///
+    /// # 返回值
+    /// - `Ok(Vec<u64>)`: 各部分数据长度(宿主文件, 分卷文件...)
+    /// - Err(err)
+    fn finish(mut self) -> io::Result<Vec<u64>> {
+        self.close_volume()?;
+        self.host.flush()?;
+        Ok(self.lengths)
+    }
+}
+
+impl Write for VolumeWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.lengths.last() == Some(&self.split_size) {
+            self.next_volume()?;
+        }
+        let remaining = self.split_size - self.lengths.last().copied().unwrap_or(0);
+        let buf = &buf[..buf.len().min(remaining as usize)];
+        let n = match &mut self.volume {
+            Some(volume) => volume.write(buf)?,
+            None => self.host.write(buf)?,
+        };
+        if let Some(length) = self.lengths.last_mut() {
+            *length += n as u64;
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if let Some(volume) = &mut self.volume {
+            volume.flush()?;
+        }
+        self.host.flush()
+    }
+}
+
+/// 获取分卷文件路径(`app.exe` 的第 1 个分卷为 `app.a01`)
+///
+/// # 参数
+/// - `target_file_path`: 目标文件路径
+/// - `number`: 分卷序号
+///
+/// # 返回值
+/// - PathBuf: 分卷文件路径
+pub fn volume_path(target_file_path: &Path, number: u32) -> PathBuf {
+    target_file_path.with_extension(format!("a{:02}", number))
+}
+
+/// 获取资源的全部分卷文件路径
+///
+/// # 参数
+/// - `target_file_path`: 目标文件路径
+/// - `config`: 资源配置
+///
+/// # 返回值
+/// - `Vec<PathBuf>`: 分卷文件路径列表(非分卷资源为空)
+pub(crate) fn volume_files(target_file_path: &Path, config: &ResourceHead) -> Vec<PathBuf> {
+    if config.storage != Storage::Split {
+        return Vec::new();
+    }
+    let Volumes { first, count } = config.volumes;
+    (first..first + count)
+        .map(|number| volume_path(target_file_path, number))
+        .collect()
+}
+
+/// 获取下一个未使用的分卷序号(已有分卷的最大序号加一)
+///
+/// # 参数
+/// - `target_file_path`: 目标文件路径
+///
+/// # 返回值
+/// - Ok(u32): 分卷序号
+/// - Err(err)
+pub(crate) fn next_volume_number(target_file_path: &Path) -> Result<u32> {
+    let prefix = match target_file_path.file_stem() {
+        Some(stem) => format!("{}.a", stem.to_string_lossy()),
+        None => return Ok(1),
+    };
+    let dir = match target_file_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
     };
+    let mut last = 0;
+    for entry in fs::read_dir(dir)? {
+        let name = entry?.file_name().to_string_lossy().to_string();
+        if let Some(number) = name
+            .strip_prefix(&prefix)
+            .and_then(|n| n.parse::<u32>().ok())
+        {
+            last = last.max(number);
+        }
+    }
+    Ok(last + 1)
+}
 
-    // 写入新文件
-    let mut output_file = File::create(&output_path_buf)?;
-    output_file.write_all(&new_data)?;
-    output_file.flush()?;
+/// 打开资源的全部分卷文件(校验分卷存在且属于该资源)
+///
+/// # 参数
+/// - `source_file`: 目标文件(需为磁盘上的文件)
+/// - `config`: 分卷资源配置
+///
+/// # 返回值
+/// - `Ok(Vec<(FileSource, u64, u64)>)`: (分卷文件, 数据起始位置, 数据长度)列表
+/// - Err(err)
+fn open_volumes(
+    source_file: &FileSource,
+    config: &ResourceHead,
+) -> Result<Vec<(FileSource<'static>, u64, u64)>> {
+    let target_file_path = source_file.path().ok_or_else(|| {
+        Error::Unsupported("Split resources can only be read from files on disk".to_string())
+    })?;
+    let magic_len = RESOURCE_MAGIC.len();
+    let mut volumes = Vec::new();
+    for path in volume_files(target_file_path, config) {
+        let mut volume = FileSource::open(&path).map_err(|e| match e {
+            Error::Io(e) if e.kind() == io::ErrorKind::NotFound => {
+                Error::NotFound(format!("volume {}", path.display()))
+            }
+            e => e,
+        })?;
+        let mut magic = vec![0u8; magic_len];
+        let mut header = vec![
+            0u8;
+            (volume.len()? as usize)
+                .saturating_sub(magic_len)
+                .min(MAX_HEADER_SIZE)
+        ];
+        volume.read_exact_at(0, &mut magic)?;
+        volume.read_exact_at(magic_len as u64, &mut header)?;
+        let head = ResourceHead::from(&header).ok().filter(|head| {
+            magic == RESOURCE_MAGIC
+                && head.storage == Storage::Volume
+                && head.id == config.id
+                && head.checksum == config.checksum
+                && head.volumes == config.volumes
+        });
+        let Some(head) = head else {
+            return Err(Error::corrupted(
+                0,
+                format!("Volume {} does not belong to this resource", path.display()),
+            ));
+        };
+        let (data_start, length) = payload_range(&mut volume, 0, &head).map_err(|e| match e {
+            Error::Corrupted { offset, reason } => Error::Corrupted {
+                offset,
+                reason: format!("Volume {}: {}", path.display(), reason),
+            },
+            e => e,
+        })?;
+        volumes.push((volume, data_start, length));
+    }
+    Ok(volumes)
+}
 
+/// 获取目标文件中的全部数据块
+///
+/// # 参数
+/// - `source_file`: 目标文件
+///
+/// # 返回值
+/// - `Ok(HashMap<String, (usize, ResourceHead)>)`: 校验和 -> (数据块起始位置, 数据块配置)
+/// - Err(err)
+pub(crate) fn chunk_index(
+    source_file: &mut FileSource,
+) -> Result<HashMap<String, (usize, ResourceHead)>> {
+    Ok(all_records(source_file)?
+        .into_iter()
+        .filter(|(_, config)| config.storage == Storage::Chunk)
+        .map(|(pos, config)| (config.checksum.clone(), (pos, config)))
+        .collect())
+}
+
+/// 获取目标文件中的全部顶层资源(包含数据块)
+///
+/// # 参数
+/// - `source_file`: 目标文件
+///
+/// # 返回值
+/// - `Ok(Vec<(usize, ResourceHead)>)`: (资源起始位置, 资源配置)列表
+/// - Err(err)
+pub(crate) fn all_records(source_file: &mut FileSource) -> Result<Vec<(usize, ResourceHead)>> {
+    scan_records(source_file, &mut |_| {})
+}
+
+/// 写入长度已确定的完整资源（魔数 + 资源头 + 数据 + 资源长度 + 尾部标识）
+///
+/// # 参数
+/// - `writer`: 输出数据流
+/// - `head`: 资源头
+/// - `data`: 资源数据(已按资源头中的压缩模式编码)
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err)
+pub(crate) fn write_record(
+    writer: &mut dyn Write,
+    head: &ResourceHead,
+    data: &mut dyn Read,
+) -> Result<()> {
+    let header = head.to_bytes()?;
+    writer.write_all(RESOURCE_MAGIC)?;
+    writer.write_all(&header)?;
+    let length = copy(data, writer)?;
+    writer.write_all(&record_len(head, length).to_le_bytes())?;
+    writer.write_all(&END_IDENTIFIER)?;
+    Ok(())
+}
+
+/// 计算资源记录的总长度（魔数 + 资源头 + 数据 + 资源长度 + 尾部标识）
+///
+/// # 参数
+/// - `head`: 资源头
+/// - `length`: 数据长度
+///
+/// # 返回值
+/// - u64: 记录长度
+pub(crate) fn record_len(head: &ResourceHead, length: u64) -> u64 {
+    (RESOURCE_MAGIC.len() + head.get_len() + head.trailer_len() + END_IDENTIFIER.len()) as u64
+        + length
+}
+
+/// 在文件当前位置写入资源（魔数 + 资源头 + 数据 + 尾部标识）
+///
+/// # 参数
+/// - `target_file`: 目标文件(已定位到写入位置)
+/// - `reader`: 资源数据流
+/// - `head`: 资源头(写入完成后回填长度与大小)
+/// - `options`: 增加资源选项(压缩等级与线程数)
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err)
+pub(crate) fn write_resource(
+    target_file: &mut dyn Backend,
+    reader: &mut dyn Read,
+    head: &mut ResourceHead,
+    options: &AddOptions,
+) -> Result<()> {
+    let start = target_file.stream_position()?;
+    let mut writer = BufWriter::with_capacity(BUFFER_SIZE, &mut *target_file);
+
+    // 插入魔数标识
+    writer.write_all(head.get_head())?;
+
+    // 插入资源头(占位)
+    writer.write_all(&head.to_bytes()?)?;
+
+    // 写入资源数据
+    let mut input = HashingReader::new(CountingReader::new(reader));
+    let length = match options.compression_grade {
+        Some(grade) if options.threads > 1 => {
+            parallel_compression_stream(&mut input, &mut writer, grade, options.threads)?
+        }
+        Some(grade) => compression_stream(&mut input, &mut writer, grade)?,
+        None => copy(&mut input, &mut writer)?,
+    };
+    let size = input.get_ref().count();
+    if size > MAX_LENGTH_SIZE {
+        return Err(Error::InvalidInput(format!(
+            "Resource exceeds maximum size of {} bytes",
+            MAX_LENGTH_SIZE
+        )));
+    }
+
+    // 插入资源长度与尾部标识
+    writer.write_all(&record_len(head, length).to_le_bytes())?;
+    writer.write_all(&END_IDENTIFIER)?;
+    writer.flush()?;
+    drop(writer);
+
+    // 回填资源头
+    head.set_length(length);
+    head.set_size(size);
+    head.set_checksum(input.checksum());
+    target_file.seek(SeekFrom::Start(start + RESOURCE_MAGIC.len() as u64))?;
+    target_file.write_all(&head.to_bytes()?)?;
+    // 确保所有数据都写入磁盘
+    target_file.flush()?;
+    Ok(())
+}
+
+/// 释放资源
+///
+/// 输出路径为目录时使用资源文件名，资源文件名不安全(见 [`check_file_name`])时返回错误。
+///
+/// # 参数
+/// - `target_file_path`: 目标文件路径
+/// - `id`: 资源ID
+/// - `output_path`: 输出路径
+/// - `options`: 释放资源选项
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err)
+pub fn export_resource(
+    target_file_path: &Path,
+    id: &str,
+    output_path: &Path,
+    options: &ExportOptions,
+    progress: ProgressCallback,
+) -> Result<()> {
+    // 打开目标文件
+    let (mut source_file, resource_start, config) =
+        locate_nested_resource(FileSource::open(target_file_path)?, id, &mut *progress)?;
+
+    // 准备输出路径
+    let output_path_buf = resolve_path(target_file_path, output_path)?;
+    let output_path_buf = if output_path_buf.is_dir() {
+        named_output_path(&output_path_buf, &config, options)?
+    } else {
+        output_path_buf
+    };
+    write_resource_file(
+        &mut source_file,
+        resource_start,
+        &config,
+        &output_path_buf,
+        progress,
+    )
+}
+
+/// 释放全部匹配的资源到目录（一次遍历目标文件）
+///
+/// 输出文件名为资源文件名；任一资源释放失败时删除本次已释放的文件。
+/// 选择器包含 `/` 时同时查找嵌套资源(嵌套容器的数据会被解码到内存中)。
+///
+/// # 参数
+/// - `target_file_path`: 目标文件路径
+/// - `selector`: 资源选择器
+/// - `output_dir`: 输出目录
+/// - `options`: 释放资源选项
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - `Ok(Vec<(String, PathBuf)>)`: (资源路径, 输出文件路径)列表
+/// - Err(err): 没有匹配的资源、多个资源的文件名相同或释放失败
+pub fn export_resources(
+    target_file_path: &Path,
+    selector: &Selector,
+    output_dir: &Path,
+    options: &ExportOptions,
+    progress: ProgressCallback,
+) -> Result<Vec<(String, PathBuf)>> {
+    let output_dir = resolve_path(target_file_path, output_dir)?;
+    if !output_dir.is_dir() {
+        return Err(Error::InvalidInput(format!(
+            "Output path {} is not a directory",
+            output_dir.display()
+        )));
+    }
+    let mut source_file = FileSource::open(target_file_path)?;
+    let mut exported = Vec::new();
+    let result = export_matching(
+        &mut source_file,
+        "",
+        selector,
+        &output_dir,
+        options,
+        &mut exported,
+        progress,
+    );
+    if let Err(e) = result {
+        for (_, path) in &exported {
+            let _ = fs::remove_file(path);
+        }
+        return Err(e);
+    }
+    if exported.is_empty() {
+        return Err(Error::NotFound(selector.to_string()));
+    }
+    Ok(exported)
+}
+
+/// 释放容器中全部匹配的资源
+///
+/// # 参数
+/// - `source_file`: 容器数据
+/// - `prefix`: 资源路径前缀
+/// - `selector`: 资源选择器
+/// - `output_dir`: 输出目录
+/// - `options`: 释放资源选项
+/// - `exported`: 已释放的(资源路径, 输出文件路径)列表
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err)
+fn export_matching(
+    source_file: &mut FileSource,
+    prefix: &str,
+    selector: &Selector,
+    output_dir: &Path,
+    options: &ExportOptions,
+    exported: &mut Vec<(String, PathBuf)>,
+    progress: ProgressCallback,
+) -> Result<()> {
+    for (pos, config) in scan_records(source_file, &mut *progress)? {
+        if config.is_hidden() {
+            continue;
+        }
+        let path = format!("{}{}", prefix, config.id.trim());
+        if selector.matches(&path) {
+            let output_path = named_output_path(output_dir, &config, options)?;
+            if let Some((other, _)) = exported.iter().find(|(_, p)| *p == output_path) {
+                return Err(Error::InvalidInput(format!(
+                    "Resources {} and {} would both be exported to {}",
+                    other,
+                    path,
+                    output_path.display()
+                )));
+            }
+            exported.push((path.clone(), output_path.clone()));
+            write_resource_file(source_file, pos, &config, &output_path, &mut *progress)?;
+        }
+
+        if selector.nested()
+            && let Some(mut container) = nested_container(source_file, pos, &config)
+        {
+            export_matching(
+                &mut container,
+                &format!("{}{}", path, NESTED_SEPARATOR),
+                selector,
+                output_dir,
+                options,
+                exported,
+                &mut *progress,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// 获取输出目录中以资源文件名命名的输出路径
+///
+/// # 参数
+/// - `output_dir`: 输出目录
+/// - `config`: 资源配置
+/// - `options`: 释放资源选项
+///
+/// # 返回值
+/// - Ok(PathBuf): 输出文件路径
+/// - Err(err): 资源文件名不安全
+fn named_output_path(
+    output_dir: &Path,
+    config: &ResourceHead,
+    options: &ExportOptions,
+) -> Result<PathBuf> {
+    if !options.allow_unsafe_names {
+        check_file_name(config.name.trim())?;
+    }
+    Ok(output_dir.join(config.name.trim()))
+}
+
+/// 释放资源到文件（失败时删除不完整的输出文件，成功后还原修改时间与权限）
+///
+/// # 参数
+/// - `source_file`: 目标文件
+/// - `resource_start`: 资源起始位置
+/// - `config`: 资源配置
+/// - `output_path`: 输出文件路径
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err)
+fn write_resource_file(
+    source_file: &mut FileSource,
+    resource_start: usize,
+    config: &ResourceHead,
+    output_path: &Path,
+    progress: ProgressCallback,
+) -> Result<()> {
+    let mut output_file = File::create(output_path)?;
+    let result = read_resource_data(
+        source_file,
+        resource_start,
+        config,
+        &mut output_file,
+        progress,
+    );
+    drop(output_file);
+    if let Err(e) = result {
+        fs::remove_file(output_path)?;
+        return Err(e);
+    }
+
+    // 还原修改时间与权限
+    restore_file_attributes(
+        output_path,
+        config.metadata.modified,
+        config.metadata.permissions,
+    )?;
+    Ok(())
+}
+
+/// 释放资源到数据流(如标准输出)
+///
+/// # 参数
+/// - `target_file_path`: 目标文件路径
+/// - `id`: 资源ID
+/// - `writer`: 输出数据流
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok(ResourceHead): 资源配置
+/// - Err(err)
+pub fn export_resource_to_writer(
+    target_file_path: &Path,
+    id: &str,
+    writer: &mut dyn Write,
+    progress: ProgressCallback,
+) -> Result<ResourceHead> {
+    let (mut source_file, resource_start, config) =
+        locate_nested_resource(FileSource::open(target_file_path)?, id, &mut *progress)?;
+    read_resource_data(&mut source_file, resource_start, &config, writer, progress)?;
+    Ok(config)
+}
+
+/// 校验资源（解码全部数据并检查结束标识与大小，不输出数据）
+///
+/// # 参数
+/// - `target_file_path`: 目标文件路径
+/// - `id`: 资源ID(支持嵌套资源路径)
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok(ResourceHead): 资源配置
+/// - Err(err)
+pub fn verify_resource(
+    target_file_path: &Path,
+    id: &str,
+    progress: ProgressCallback,
+) -> Result<ResourceHead> {
+    export_resource_to_writer(target_file_path, id, &mut io::sink(), progress)
+}
+
+/// 从任意数据源释放资源到数据流(如内存缓冲区)
+///
+/// # 参数
+/// - `reader`: 目标数据(如 `Cursor<Vec<u8>>`)
+/// - `id`: 资源ID(支持嵌套资源路径)
+/// - `writer`: 输出数据流
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok(ResourceHead): 资源配置
+/// - Err(err): 找不到资源或资源数据位于分卷文件中
+pub fn export_resource_from_reader(
+    reader: impl Read + Seek,
+    id: &str,
+    writer: &mut dyn Write,
+    progress: ProgressCallback,
+) -> Result<ResourceHead> {
+    let (mut source_file, resource_start, config) =
+        locate_nested_resource(FileSource::from_reader(reader)?, id, &mut *progress)?;
+    read_resource_data(&mut source_file, resource_start, &config, writer, progress)?;
+    Ok(config)
+}
+
+/// 从任意数据源校验资源（解码全部数据并检查结束标识与大小，不输出数据）
+///
+/// # 参数
+/// - `reader`: 目标数据
+/// - `id`: 资源ID(支持嵌套资源路径)
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok(ResourceHead): 资源配置
+/// - Err(err)
+pub fn verify_resource_from_reader(
+    reader: impl Read + Seek,
+    id: &str,
+    progress: ProgressCallback,
+) -> Result<ResourceHead> {
+    export_resource_from_reader(reader, id, &mut io::sink(), progress)
+}
+
+/// 定位资源（支持 `外层ID/内层ID` 形式的嵌套资源路径）
+///
+/// 完整 ID 优先匹配顶层资源；未找到时按路径分隔符拆分，将外层资源数据加载到内存中继续查找。
+///
+/// # 参数
+/// - `source_file`: 目标文件
+/// - `id`: 资源ID或嵌套资源路径
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok((FileSource, usize, ResourceHead)): (资源所在容器, 资源起始位置, 资源配置)
+/// - Err(err)
+pub(crate) fn locate_nested_resource<'a>(
+    mut source_file: FileSource<'a>,
+    id: &str,
+    progress: ProgressCallback,
+) -> Result<(FileSource<'a>, usize, ResourceHead)> {
+    let error = match locate_resource(&mut source_file, id, &mut *progress) {
+        Ok((start, config)) => return Ok((source_file, start, config)),
+        Err(e) => e,
+    };
+    for (index, _) in id.match_indices(NESTED_SEPARATOR) {
+        let Ok((start, config)) = locate_resource(&mut source_file, &id[..index], &mut *progress)
+        else {
+            continue;
+        };
+        let mut data = Vec::new();
+        read_resource_data(&mut source_file, start, &config, &mut data, &mut *progress)?;
+        return locate_nested_resource(FileSource::Memory(data.into()), &id[index + 1..], progress);
+    }
+    Err(error)
+}
+
+/// 定位资源
+///
+/// # 参数
+/// - `source_file`: 目标文件
+/// - `id`: 资源ID
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok((usize, ResourceHead)): (资源起始位置, 资源配置)
+/// - Err(err)
+pub(crate) fn locate_resource(
+    source_file: &mut FileSource,
+    id: &str,
+    progress: ProgressCallback,
+) -> Result<(usize, ResourceHead)> {
+    locate_resource_by(source_file, progress, |_, config| {
+        !config.is_hidden() && config.id.trim() == id.trim()
+    })?
+    .ok_or_else(|| Error::NotFound(id.trim().to_string()))
+}
+
+/// 按条件定位第一个匹配的顶层资源
+///
+/// 多个资源匹配时取最早的资源。
+///
+/// # 参数
+/// - `source_file`: 目标文件
+/// - `progress`: 进度回调
+/// - `matches`: 匹配条件(资源起始位置, 资源配置)
+///
+/// # 返回值
+/// - `Ok(Option<(usize, ResourceHead)>)`: (资源起始位置, 资源配置)
+/// - Err(err)
+pub(crate) fn locate_resource_by(
+    source_file: &mut FileSource,
+    progress: ProgressCallback,
+    mut matches: impl FnMut(usize, &ResourceHead) -> bool,
+) -> Result<Option<(usize, ResourceHead)>> {
+    Ok(scan_records(source_file, progress)?
+        .into_iter()
+        .find(|(pos, config)| matches(*pos, config)))
+}
+
+/// 定位全部匹配的顶层资源
+///
+/// # 参数
+/// - `source_file`: 目标文件
+/// - `selector`: 资源选择器
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - `Ok(Vec<(usize, ResourceHead)>)`: (资源起始位置, 资源配置)列表
+/// - Err(err): 没有匹配的资源
+pub(crate) fn locate_resources(
+    source_file: &mut FileSource,
+    selector: &Selector,
+    progress: ProgressCallback,
+) -> Result<Vec<(usize, ResourceHead)>> {
+    let found: Vec<_> = scan_records(source_file, progress)?
+        .into_iter()
+        .filter(|(_, config)| !config.is_hidden() && selector.matches(&config.id))
+        .collect();
+    if found.is_empty() {
+        return Err(Error::NotFound(selector.to_string()));
+    }
+    Ok(found)
+}
+
+/// 获取全部顶层资源记录并报告进度
+///
+/// 先从文件末尾反向遍历资源，再从头扫描到反向遍历到的最早资源为止：
+/// 两次追加之间可能插入了其他数据(如签名)，此时更早的资源只能通过从头扫描找到。
+/// 反向遍历之前的数据中没有资源时直接采用反向遍历的结果，否则继续从头扫描整个文件。
+///
+/// # 参数
+/// - `source_file`: 目标文件
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - `Ok(Vec<(usize, ResourceHead)>)`: (资源起始位置, 资源配置)列表
+/// - Err(err)
+fn scan_records(
+    source_file: &mut FileSource,
+    progress: ProgressCallback,
+) -> Result<Vec<(usize, ResourceHead)>> {
+    let file_len = source_file.len()?;
+    let mut tracker = ProgressTracker::new(progress, Stage::Scan, Some(file_len));
+    let walked = walk_resources_backward(source_file)?.filter(|records| !records.is_empty());
+    let overlay_start = walked.as_ref().map(|records| records[0].0);
+    let mut records = Vec::new();
+    let mut reached_overlay = false;
+    scan_resources(source_file, &mut tracker, |pos, config| {
+        if records.is_empty() && overlay_start.is_some_and(|start| pos >= start) {
+            reached_overlay = true;
+            return ControlFlow::Break(());
+        }
+        records.push((pos, config));
+        ControlFlow::Continue(())
+    })?;
+    if reached_overlay && let Some(walked) = walked {
+        tracker.set(file_len);
+        tracker.report();
+        return Ok(walked);
+    }
+    Ok(records)
+}
+
+/// 从文件末尾反向遍历资源（依据尾部记录的资源长度逐个跳转）
+///
+/// # 参数
+/// - `source_file`: 目标文件
+///
+/// # 返回值
+/// - `Ok(Some(Vec<(usize, ResourceHead)>))`: (资源起始位置, 资源配置)列表，按文件顺序排列
+/// - Ok(None): 遇到无法反向定位的数据(如旧版资源或损坏的资源)，需要从头扫描
+/// - Err(err)
+fn walk_resources_backward(
+    source_file: &mut FileSource,
+) -> Result<Option<Vec<(usize, ResourceHead)>>> {
+    let magic_len = RESOURCE_MAGIC.len();
+    let min_record_len = magic_len + TRAILER_SIZE + END_IDENTIFIER.len();
+    let mut resources = Vec::new();
+    let mut pos = source_file.len()? as usize;
+    let mut end_buffer = [0u8; END_IDENTIFIER.len()];
+    let mut magic_buffer = vec![0u8; magic_len];
+    let mut trailer = [0u8; TRAILER_SIZE];
+
+    // 末尾不是尾部标识时说明已到达宿主文件
+    while pos >= END_IDENTIFIER.len() {
+        source_file.read_exact_at((pos - END_IDENTIFIER.len()) as u64, &mut end_buffer)?;
+        if end_buffer != END_IDENTIFIER {
+            break;
+        }
+        if pos < min_record_len {
+            return Ok(None);
+        }
+
+        // 读取资源长度并跳转到资源起始位置
+        source_file.read_exact_at(
+            (pos - END_IDENTIFIER.len() - TRAILER_SIZE) as u64,
+            &mut trailer,
+        )?;
+        let record_len = u64::from_le_bytes(trailer) as usize;
+        if record_len < min_record_len || record_len > pos {
+            return Ok(None);
+        }
+        let start = pos - record_len;
+        source_file.read_exact_at(start as u64, &mut magic_buffer)?;
+        if magic_buffer != RESOURCE_MAGIC {
+            return Ok(None);
+        }
+
+        // 解析并校验资源头
+        let mut header = vec![0u8; (record_len - magic_len).min(MAX_HEADER_SIZE)];
+        source_file.read_exact_at((start + magic_len) as u64, &mut header)?;
+        let Ok(config) = ResourceHead::from(&header) else {
+            return Ok(None);
+        };
+        if !matches!(resource_end(source_file, start, &config), Ok(end) if end == pos) {
+            return Ok(None);
+        }
+        resources.push((start, config));
+        pos = start;
+    }
+    resources.reverse();
+    Ok(Some(resources))
+}
+
+/// 扫描顶层资源（在每个魔数位置尝试解析资源头）
+///
+/// 资源完整时直接跳过其数据，因此资源数据中嵌套的资源不会被报告；
+/// 资源不完整(如结束标识缺失)时从下一个字节继续搜索。
+///
+/// # 参数
+/// - `source_file`: 目标文件
+/// - `tracker`: 进度计数器
+/// - `visit`: 回调函数(资源起始位置, 资源配置)，返回是否继续扫描
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err)
+fn scan_resources(
+    source_file: &mut FileSource,
+    tracker: &mut ProgressTracker,
+    mut visit: impl FnMut(usize, ResourceHead) -> ControlFlow<()>,
+) -> Result<()> {
+    let file_len = source_file.len()?;
+    let magic_len = RESOURCE_MAGIC.len() as u64;
+    let mut header = vec![0u8; MAX_HEADER_SIZE];
+    let mut from = 0;
+    while let Some(pos) = source_file.find(RESOURCE_MAGIC, from, tracker)? {
+        from = pos + 1;
+
+        // 不是有效的资源头，继续搜索
+        let header_len = (file_len - pos - magic_len).min(MAX_HEADER_SIZE as u64) as usize;
+        source_file.read_exact_at(pos + magic_len, &mut header[..header_len])?;
+        let Ok(config) = ResourceHead::from(&header[..header_len]) else {
+            continue;
+        };
+
+        // 跳过完整资源的数据
+        if let Ok(end) = resource_end(source_file, pos as usize, &config) {
+            from = end as u64;
+        }
+        if visit(pos as usize, config).is_break() {
+            break;
+        }
+    }
+    tracker.report();
+    Ok(())
+}
+
+/// 读取资源数据（校验版本与结束标识，并解压）
+///
+/// # 参数
+/// - `source_file`: 目标文件
+/// - `resource_start`: 资源起始位置
+/// - `config`: 资源配置
+/// - `writer`: 输出数据流
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err)
+pub(crate) fn read_resource_data(
+    source_file: &mut FileSource,
+    resource_start: usize,
+    config: &ResourceHead,
+    writer: &mut dyn Write,
+    progress: ProgressCallback,
+) -> Result<()> {
+    // 验证版本（兼容旧版资源）
+    let default_resource_head = ResourceHead::default();
+    let version_ordering = compare_version(&config.version, &default_resource_head.version)
+        .map_err(|e| e.at(resource_start as u64))?;
+    if version_ordering.is_gt() {
+        return Err(Error::VersionMismatch {
+            found: config.version.clone(),
+            supported: default_resource_head.version.clone(),
+        });
+    }
+
+    let expected_size = config.size.trim().parse::<u64>().map_err(|e| {
+        Error::corrupted(
+            resource_start as u64,
+            format!("Failed to parse resource size ({})", e),
+        )
+    })?;
+    let mut output = HashingWriter::new(writer);
+    let actual_size = match config.storage {
+        Storage::Inline | Storage::Chunk => {
+            decode_payload(source_file, resource_start, config, &mut output, progress)?
+        }
+        // 引用资源读取被引用资源的数据，按自身的大小与校验和校验
+        Storage::Reference => {
+            let (start, referenced) = locate_resource_by(source_file, &mut |_| {}, |_, c| {
+                c.storage == Storage::Inline && c.checksum == config.checksum
+            })?
+            .ok_or_else(|| {
+                Error::corrupted(resource_start as u64, "Referenced resource data not found")
+            })?;
+            decode_payload(source_file, start, &referenced, &mut output, progress)?
+        }
+        // 分块资源按数据块列表依次解码数据块
+        Storage::Chunked => {
+            let list = read_payload(source_file, resource_start, config)?;
+            let chunks: Vec<String> = bincode::deserialize(&list)
+                .map_err(|e| Error::from(e).at(resource_start as u64))?;
+            let index = chunk_index(source_file)?;
+            let mut tracker = ProgressTracker::new(progress, Stage::Export, Some(expected_size));
+            let mut size = 0;
+            for chunk_checksum in &chunks {
+                let (start, chunk) = index.get(chunk_checksum).ok_or_else(|| {
+                    Error::corrupted(
+                        resource_start as u64,
+                        format!("Resource chunk {} not found", chunk_checksum),
+                    )
+                })?;
+                let n = decode_payload(source_file, *start, chunk, &mut output, &mut |_| {})?;
+                tracker.advance(n);
+                size += n;
+            }
+            tracker.report();
+            size
+        }
+        // 分卷数据只是资源的一部分，需通过宿主文件中的分卷资源读取
+        Storage::Volume => {
+            return Err(Error::Unsupported(
+                "Volume data can only be read through its split resource".to_string(),
+            ))
+        }
+        Storage::Binding => {
+            return Err(Error::Unsupported(
+                "Binding records carry no resource data".to_string(),
+            ))
+        }
+        // 分卷资源依次读取宿主文件与各分卷文件中的数据
+        Storage::Split => {
+            let mut volumes = open_volumes(source_file, config)?;
+            let (data_start, length) = payload_range(source_file, resource_start, config)?;
+            let total = length + volumes.iter().map(|(_, _, len)| len).sum::<u64>();
+            let mut data = source_file.range_reader(data_start, length)?;
+            for (volume, start, len) in &mut volumes {
+                data = Box::new(data.chain(volume.range_reader(*start, *len)?));
+            }
+            let mut data = ProgressReader::new(
+                data,
+                ProgressTracker::new(progress, Stage::Export, Some(total)),
+            );
+            match config.compress {
+                CompressMode::Compress => decompress_stream(data, &mut output)?,
+                CompressMode::None => copy(&mut data, &mut output)?,
+            }
+        }
+        // 差异资源沿差异链找到最早的完整版本，还原后由旧到新依次应用补丁
+        Storage::Delta => {
+            let mut patches = Vec::new();
+            let mut visited = HashSet::new();
+            let (mut start, mut base) = (resource_start, config.clone());
+            while base.storage == Storage::Delta {
+                // 已访问过的记录说明差异链构成循环
+                if !visited.insert(start) {
+                    return Err(Error::corrupted(resource_start as u64, "Delta chain cycle"));
+                }
+                let mut patch = Vec::new();
+                decode_payload(source_file, start, &base, &mut patch, &mut |_| {})?;
+                let patch: DeltaPatch =
+                    bincode::deserialize(&patch).map_err(|e| Error::from(e).at(start as u64))?;
+                (start, base) = locate_resource_by(source_file, &mut |_| {}, |_, c| {
+                    c.superseded && c.id == config.id && c.checksum == patch.base_checksum
+                })?
+                .ok_or_else(|| {
+                    Error::corrupted(resource_start as u64, "Delta base version not found")
+                })?;
+                patches.push(patch);
+            }
+            let mut data = Vec::new();
+            read_resource_data(source_file, start, &base, &mut data, progress)?;
+            // patches 由新到旧排列，第一个为当前资源的补丁
+            let (newest, older) = patches
+                .split_first()
+                .expect("delta resources carry at least one patch");
+            for patch in older.iter().rev() {
+                let mut restored = Vec::new();
+                delta::apply(&data, &patch.ops, &mut restored)?;
+                data = restored;
+            }
+            delta::apply(&data, &newest.ops, &mut output)?
+        }
+    };
+    output.flush()?;
+
+    // 验证输出大小
+    // 旧版压缩资源记录的是压缩后大小，无法校验
+    let legacy_compressed =
+        config.version == LEGACY_VERSION && config.compress == CompressMode::Compress;
+    if !legacy_compressed && actual_size != expected_size {
+        return Err(Error::corrupted(
+            resource_start as u64,
+            format!(
+                "Exported file size mismatch: expected {}, got {}",
+                expected_size, actual_size
+            ),
+        ));
+    }
+
+    // 验证校验和(旧版资源没有校验和)
+    let checksum = output.checksum();
+    if !config.checksum.is_empty() && checksum != config.checksum {
+        return Err(Error::corrupted(
+            resource_start as u64,
+            format!(
+                "Exported data checksum mismatch: expected {}, got {}",
+                config.checksum, checksum
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// 解码资源自身存储的数据（按压缩模式解压）
+///
+/// # 参数
+/// - `source_file`: 目标文件
+/// - `resource_start`: 资源起始位置
+/// - `config`: 资源配置
+/// - `writer`: 输出数据流
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok(u64): 解码后的字节数
+/// - Err(err)
+fn decode_payload(
+    source_file: &mut FileSource,
+    resource_start: usize,
+    config: &ResourceHead,
+    writer: &mut dyn Write,
+    progress: ProgressCallback,
+) -> Result<u64> {
+    let (data_start, resource_length) = payload_range(source_file, resource_start, config)?;
+    let data = source_file.range_reader(data_start, resource_length)?;
+    let mut data = ProgressReader::new(
+        data,
+        ProgressTracker::new(progress, Stage::Export, Some(resource_length)),
+    );
+    Ok(match config.compress {
+        CompressMode::Compress => decompress_stream(data, writer)?,
+        CompressMode::None => copy(&mut data, writer)?,
+    })
+}
+
+/// 读取资源自身存储的原始数据（不解压）
+///
+/// # 参数
+/// - `source_file`: 目标文件
+/// - `resource_start`: 资源起始位置
+/// - `config`: 资源配置
+///
+/// # 返回值
+/// - `Ok(Vec<u8>)`: 原始数据
+/// - Err(err)
+fn read_payload(
+    source_file: &mut FileSource,
+    resource_start: usize,
+    config: &ResourceHead,
+) -> Result<Vec<u8>> {
+    let (data_start, resource_length) = payload_range(source_file, resource_start, config)?;
+    let mut data = Vec::new();
+    source_file
+        .range_reader(data_start, resource_length)?
+        .read_to_end(&mut data)?;
+    Ok(data)
+}
+
+/// 获取资源数据区间（校验资源长度与结束标识）
+///
+/// # 参数
+/// - `source_file`: 目标文件
+/// - `resource_start`: 资源起始位置
+/// - `config`: 资源配置
+///
+/// # 返回值
+/// - Ok((u64, u64)): (数据起始位置, 数据长度)
+/// - Err(err)
+pub(crate) fn payload_range(
+    source_file: &mut FileSource,
+    resource_start: usize,
+    config: &ResourceHead,
+) -> Result<(u64, u64)> {
+    let end_pos = resource_end(source_file, resource_start, config)?;
+    let data_start = resource_start + RESOURCE_MAGIC.len() + config.get_len();
+    let resource_length = end_pos - END_IDENTIFIER.len() - config.trailer_len() - data_start;
+    Ok((data_start as u64, resource_length as u64))
+}
+
+/// 获取资源结束位置（校验资源长度与结束标识）
+///
+/// # 参数
+/// - `source_file`: 目标文件
+/// - `resource_start`: 资源起始位置
+/// - `config`: 资源配置
+///
+/// # 返回值
+/// - Ok(usize): 资源结束位置(尾部标识之后)
+/// - Err(err)
+pub(crate) fn resource_end(
+    source_file: &mut FileSource,
+    resource_start: usize,
+    config: &ResourceHead,
+) -> Result<usize> {
+    let file_len = source_file.len()?;
+    let magic_len = RESOURCE_MAGIC.len();
+    let header_len = config.get_len();
+    let resource_length = config.length.trim().parse::<usize>().map_err(|e| {
+        Error::corrupted(
+            resource_start as u64,
+            format!("Failed to parse resource length ({})", e),
+        )
+    })?;
+
+    // 验证资源完整性（检查结束标识）
+    let trailer_pos = resource_start + magic_len + header_len + resource_length;
+    let end_pos = trailer_pos + config.trailer_len();
+    if end_pos + END_IDENTIFIER.len() > file_len as usize {
+        return Err(Error::corrupted(
+            resource_start as u64,
+            "Resource extends beyond file boundary",
+        ));
+    }
+
+    let mut end_buffer = [0u8; END_IDENTIFIER.len()];
+    source_file.read_exact_at(end_pos as u64, &mut end_buffer)?;
+    if end_buffer != END_IDENTIFIER {
+        return Err(Error::corrupted(
+            end_pos as u64,
+            "Resource end marker not found",
+        ));
+    }
+
+    // 验证尾部记录的资源长度
+    if config.trailer_len() > 0 {
+        let mut trailer = [0u8; TRAILER_SIZE];
+        source_file.read_exact_at(trailer_pos as u64, &mut trailer)?;
+        let record_len = u64::from_le_bytes(trailer) as usize;
+        if record_len != end_pos + END_IDENTIFIER.len() - resource_start {
+            return Err(Error::corrupted(
+                trailer_pos as u64,
+                "Resource trailer length mismatch",
+            ));
+        }
+    }
+    Ok(end_pos + END_IDENTIFIER.len())
+}
+
+/// 判断两个路径是否指向同一文件（路径形式可能不同）
+///
+/// # 参数
+/// - `path`: 待判断的路径
+/// - `target_file_path`: 目标文件路径
+///
+/// # 返回值
+/// - Ok(bool): 是否为同一文件
+/// - Err(err)
+pub(crate) fn is_same_file(path: &Path, target_file_path: &Path) -> Result<bool> {
+    Ok(path == target_file_path
+        || (path.exists() && fs::canonicalize(path)? == fs::canonicalize(target_file_path)?))
+}
+
+/// 在输出文件旁创建临时文件（名称唯一，不会覆盖已有文件）
+///
+/// # 参数
+/// - `output_path`: 输出文件路径
+///
+/// # 返回值
+/// - Ok((PathBuf, File)): (临时文件路径, 临时文件)
+/// - Err(err)
+pub(crate) fn create_temp_file(output_path: &Path) -> Result<(PathBuf, File)> {
+    let mut attempt = 0u32;
+    loop {
+        let mut path = output_path.as_os_str().to_owned();
+        path.push(format!(".{}-{}.tmp", std::process::id(), attempt));
+        match OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)
+        {
+            Ok(file) => return Ok((PathBuf::from(path), file)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists && attempt < 100 => attempt += 1,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// 解析相对路径（相对于目标文件所在目录）
+///
+/// # 参数
+/// - `target_file_path`: 目标文件路径
+/// - `path`: 待解析的路径
+///
+/// # 返回值
+/// - Ok(PathBuf): 解析后的路径
+/// - Err(err)
+pub(crate) fn resolve_path(target_file_path: &Path, path: &Path) -> Result<PathBuf> {
+    if path.is_relative() {
+        Ok(target_file_path
+            .parent()
+            .ok_or_else(|| Error::InvalidInput("Target file has no parent directory".to_string()))?
+            .join(path))
+    } else {
+        Ok(path.to_path_buf())
+    }
+}
+
+/// 寻找资源配置 - 从头至尾
+///
+/// 只报告顶层资源，资源数据中嵌套的资源会被跳过。
+///
+/// # 参数
+/// - `target_file_path`: 目标文件路径
+/// - `callback`: 回调函数(配置位置, 资源配置)
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - `Vec<ResourceHead>`: 资源配置列表
+/// - Err(err)
+pub fn find_resources_config(
+    target_file_path: &Path,
+    mut callback: impl FnMut(usize, &ResourceHead),
+    progress: ProgressCallback,
+) -> Result<Vec<ResourceHead>> {
+    // 数据块与被取代的旧版本为内部记录，不列出
+    let mut source_file = FileSource::open(target_file_path)?;
+    Ok(scan_records(&mut source_file, progress)?
+        .into_iter()
+        .filter(|(_, config)| !config.is_hidden())
+        .map(|(pos, config)| {
+            callback(pos, &config);
+            config
+        })
+        .collect())
+}
+
+/// 从任意数据源寻找资源配置(如内存缓冲区)
+///
+/// # 参数
+/// - `reader`: 目标数据
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - `Vec<ResourceHead>`: 资源配置列表
+/// - Err(err)
+pub fn find_resources_config_from_reader(
+    reader: impl Read + Seek,
+    progress: ProgressCallback,
+) -> Result<Vec<ResourceHead>> {
+    let mut source_file = FileSource::from_reader(reader)?;
+    Ok(scan_records(&mut source_file, progress)?
+        .into_iter()
+        .map(|(_, config)| config)
+        .filter(|config| !config.is_hidden())
+        .collect())
+}
+
+/// 寻找全部顶层资源配置（包含数据块与被取代的旧版本）
+///
+/// # 参数
+/// - `source_file`: 目标数据
+/// - `callback`: 回调函数(配置位置, 资源配置)
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - `Vec<ResourceHead>`: 资源配置列表
+/// - Err(err)
+fn find_records(
+    source_file: &mut FileSource,
+    mut callback: impl FnMut(usize, &ResourceHead),
+    progress: ProgressCallback,
+) -> Result<Vec<ResourceHead>> {
+    let file_len = source_file.len()?;
+    let mut tracker = ProgressTracker::new(progress, Stage::Scan, Some(file_len));
+
+    let mut configs = Vec::new();
+    scan_resources(source_file, &mut tracker, |pos, config| {
+        callback(pos, &config);
+        configs.push(config);
+        ControlFlow::Continue(())
+    })?;
+    Ok(configs)
+}
+
+/// 寻找资源配置（包含嵌套资源）
+///
+/// 资源数据本身携带资源时，以 `外层ID/内层ID` 形式报告内层资源。
+/// 嵌套容器的数据会被解码到内存中扫描。
+///
+/// # 参数
+/// - `target_file_path`: 目标文件路径
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - `Ok(Vec<(String, ResourceHead)>)`: (资源路径, 资源配置)列表
+/// - Err(err)
+pub fn find_nested_resources(
+    target_file_path: &Path,
+    progress: ProgressCallback,
+) -> Result<Vec<(String, ResourceHead)>> {
+    let mut source_file = FileSource::open(target_file_path)?;
+    let mut resources = Vec::new();
+    collect_nested_resources(&mut source_file, "", &mut resources, progress)?;
+    Ok(resources)
+}
+
+/// 递归收集容器中的资源
+///
+/// # 参数
+/// - `source_file`: 容器数据
+/// - `prefix`: 资源路径前缀
+/// - `resources`: 资源列表
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err)
+fn collect_nested_resources(
+    source_file: &mut FileSource,
+    prefix: &str,
+    resources: &mut Vec<(String, ResourceHead)>,
+    progress: ProgressCallback,
+) -> Result<()> {
+    for (pos, config) in scan_records(source_file, &mut *progress)? {
+        if config.is_hidden() {
+            continue;
+        }
+        let path = format!("{}{}", prefix, config.id.trim());
+        let container = nested_container(source_file, pos, &config);
+        resources.push((path.clone(), config));
+        if let Some(mut container) = container {
+            let prefix = format!("{}{}", path, NESTED_SEPARATOR);
+            collect_nested_resources(&mut container, &prefix, resources, &mut *progress)?;
+        }
+    }
+    Ok(())
+}
+
+/// 解码可能携带嵌套资源的资源数据
+///
+/// 先确认解码后的数据以尾部标识结尾(未压缩的普通资源直接读取文件中的末尾，其余流式解码且不保留数据)，
+/// 只有可能携带资源时才将数据解码到内存中。
+///
+/// # 参数
+/// - `source_file`: 容器数据
+/// - `pos`: 资源起始位置
+/// - `config`: 资源配置
+///
+/// # 返回值
+/// - Some(FileSource): 解码到内存中的资源数据
+/// - None: 资源数据不携带资源或无法解码(不作为容器处理)
+fn nested_container(
+    source_file: &mut FileSource,
+    pos: usize,
+    config: &ResourceHead,
+) -> Option<FileSource<'static>> {
+    let marker_len = END_IDENTIFIER.len() as u64;
+    let ends_with_marker = match (config.storage, config.compress) {
+        (Storage::Inline, CompressMode::None) => {
+            let (data_start, length) = payload_range(source_file, pos, config).ok()?;
+            let mut tail = [0u8; END_IDENTIFIER.len()];
+            length >= marker_len
+                && source_file
+                    .read_exact_at(data_start + length - marker_len, &mut tail)
+                    .is_ok()
+                && tail == END_IDENTIFIER
+        }
+        _ => {
+            let mut tail = TailWriter::default();
+            read_resource_data(source_file, pos, config, &mut tail, &mut |_| {}).is_ok()
+                && tail.0.ends_with(&END_IDENTIFIER)
+        }
+    };
+    if !ends_with_marker {
+        return None;
+    }
+    let mut data = Vec::new();
+    read_resource_data(source_file, pos, config, &mut data, &mut |_| {}).ok()?;
+    Some(FileSource::Memory(data.into()))
+}
+
+/// 只保留末尾数据(尾部标识长度)的数据流
+#[derive(Default)]
+struct TailWriter(Vec<u8>);
+
+impl Write for TailWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let keep = END_IDENTIFIER.len();
+        self.0
+            .extend_from_slice(&buf[buf.len().saturating_sub(keep)..]);
+        let excess = self.0.len().saturating_sub(keep);
+        self.0.drain(..excess);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// 资源详细信息
+#[derive(Debug)]
+pub struct ResourceInfo {
+    /// 资源配置
+    pub head: ResourceHead,
+    /// 资源起始位置(魔数位置)
+    pub offset: u64,
+    /// 资源头长度(不含魔数)
+    pub header_len: u64,
+    /// 数据起始位置
+    pub data_offset: u64,
+    /// 数据长度(存储长度)
+    pub length: u64,
+    /// 结束标识是否完整
+    pub end_marker: bool,
+}
+
+impl ResourceInfo {
+    /// 获取资源结束位置(尾部标识之后)
+    pub fn end(&self) -> u64 {
+        self.data_offset
+            + self.length
+            + self.head.trailer_len() as u64
+            + END_IDENTIFIER.len() as u64
+    }
+
+    /// 获取压缩率(存储长度 / 原始大小，引用资源没有自身数据)
+    pub fn ratio(&self) -> Option<f64> {
+        if self.head.storage == Storage::Reference {
+            return None;
+        }
+        let size = self.head.size.trim().parse::<u64>().ok()?;
+        (size > 0).then(|| self.length as f64 / size as f64)
+    }
+}
+
+/// 获取资源详细信息（位置、长度及完整性）
+///
+/// # 参数
+/// - `target_file_path`: 目标文件路径
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - `Ok(Vec<ResourceInfo>)`: 资源详细信息列表
+/// - Err(err)
+pub fn inspect_resources(
+    target_file_path: &Path,
+    progress: ProgressCallback,
+) -> Result<Vec<ResourceInfo>> {
+    inspect_records(&mut FileSource::open(target_file_path)?, progress)
+}
+
+/// 获取任意数据源中的资源详细信息(如内存缓冲区)
+///
+/// # 参数
+/// - `reader`: 目标数据
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - `Ok(Vec<ResourceInfo>)`: 资源详细信息列表
+/// - Err(err)
+pub fn inspect_resources_from_reader(
+    reader: impl Read + Seek,
+    progress: ProgressCallback,
+) -> Result<Vec<ResourceInfo>> {
+    inspect_records(&mut FileSource::from_reader(reader)?, progress)
+}
+
+/// 获取资源详细信息
+///
+/// # 参数
+/// - `source_file`: 目标数据
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - `Ok(Vec<ResourceInfo>)`: 资源详细信息列表
+/// - Err(err)
+fn inspect_records(
+    source_file: &mut FileSource,
+    progress: ProgressCallback,
+) -> Result<Vec<ResourceInfo>> {
+    let mut positions = Vec::new();
+    let configs = find_records(source_file, |pos, _| positions.push(pos), progress)?;
+
+    let magic_len = RESOURCE_MAGIC.len() as u64;
+    Ok(positions
+        .into_iter()
+        .zip(configs)
+        .map(|(pos, head)| {
+            let header_len = head.get_len() as u64;
+            let end_marker = resource_end(source_file, pos, &head).is_ok();
+            ResourceInfo {
+                offset: pos as u64,
+                header_len,
+                data_offset: pos as u64 + magic_len + header_len,
+                length: head.length.trim().parse().unwrap_or(0),
+                end_marker,
+                head,
+            }
+        })
+        .collect())
+}
+
+/// 剥离全部资源，还原宿主文件
+///
+/// 从最早的资源位置开始，要求其后的数据均为首尾相接的完整资源。
+///
+/// # 参数
+/// - `target_file_path`: 目标文件路径
+/// - `output_path`: 输出文件路径(可选)
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - `Ok((u64, Vec<ResourceHead>))`: (宿主文件大小, 被剥离的资源配置列表)
+/// - Err(err)
+pub fn strip_resources(
+    target_file_path: &Path,
+    output_path: Option<&Path>,
+    progress: ProgressCallback,
+) -> Result<(u64, Vec<ResourceHead>)> {
+    let mut source_file = FileSource::open(target_file_path)?;
+    let (host_len, chain) = overlay_chain(&mut source_file, &mut *progress)?;
+
+    // 确定输出路径
+    let output_path_buf = if let Some(output_path_param) = output_path {
+        resolve_path(target_file_path, output_path_param)?
+    } else {
+        target_file_path.to_path_buf()
+    };
+    let in_place = is_same_file(&output_path_buf, target_file_path)?;
+
+    let mut tracker = ProgressTracker::new(progress, Stage::Rewrite, Some(host_len));
+    if in_place {
+        drop(source_file);
+        OpenOptions::new()
+            .write(true)
+            .open(&output_path_buf)?
+            .set_len(host_len)?;
+        tracker.set(host_len);
+        let volumes = chain
+            .iter()
+            .flat_map(|config| volume_files(target_file_path, config))
+            .collect::<Vec<_>>();
+        remove_volume_files(&volumes)?;
+    } else {
+        let mut output_file =
+            BufWriter::with_capacity(BUFFER_SIZE, File::create(&output_path_buf)?);
+        copy_range(
+            &mut source_file,
+            0,
+            host_len,
+            &mut output_file,
+            &mut tracker,
+        )?;
+        output_file.flush()?;
+    }
+    tracker.report();
+
+    // 仅返回资源链中的资源（排除内部记录）
+    let stripped = chain
+        .into_iter()
+        .filter(|config| !config.is_hidden())
+        .collect();
+    Ok((host_len, stripped))
+}
+
+/// 剥离任意读写后端中的全部资源(如内存缓冲区)
+///
+/// # 参数
+/// - `backend`: 读写后端
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - `Ok((u64, Vec<ResourceHead>))`: (宿主数据大小, 被剥离的资源配置列表)
+/// - Err(err)
+pub fn strip_resources_from_backend(
+    backend: &mut dyn Backend,
+    progress: ProgressCallback,
+) -> Result<(u64, Vec<ResourceHead>)> {
+    let (host_len, chain) =
+        overlay_chain(&mut FileSource::from_reader(&mut *backend)?, &mut *progress)?;
+    let mut tracker = ProgressTracker::new(progress, Stage::Rewrite, Some(host_len));
+    backend.set_len(host_len)?;
+    backend.flush()?;
+    tracker.set(host_len);
+    tracker.report();
+    let stripped = chain
+        .into_iter()
+        .filter(|config| !config.is_hidden())
+        .collect();
+    Ok((host_len, stripped))
+}
+
+/// 寻找宿主数据之后首尾相接直到数据末尾的资源链
+///
+/// # 参数
+/// - `source_file`: 目标数据
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - `Ok((u64, Vec<ResourceHead>))`: (宿主数据大小, 资源链中的资源配置列表)
+/// - Err(err): 没有资源，或第一个资源之后的数据不是完整的资源
+fn overlay_chain(
+    source_file: &mut FileSource,
+    progress: ProgressCallback,
+) -> Result<(u64, Vec<ResourceHead>)> {
+    // 查找所有资源位置
+    let mut positions = Vec::new();
+    let configs = find_records(source_file, |pos, _| positions.push(pos), progress)?;
+    if configs.is_empty() {
+        return Err(Error::NotFound(String::new()));
+    }
+
+    // 从最早的候选位置开始，寻找能首尾相接直到文件末尾的资源链
+    let file_len = source_file.len()? as usize;
+    let mut host_len = None;
+    for (index, &start) in positions.iter().enumerate() {
+        let mut pos = start;
+        let mut chain = Vec::new();
+        while pos < file_len {
+            let Some(next) = positions[index..].iter().position(|&p| p == pos) else {
+                break;
+            };
+            match resource_end(source_file, pos, &configs[index + next]) {
+                Ok(end) => {
+                    chain.push(index + next);
+                    pos = end;
+                }
+                Err(_) => break,
+            }
+        }
+        if pos == file_len && !chain.is_empty() {
+            host_len = Some((start, chain));
+            break;
+        }
+    }
+    let Some((host_len, chain)) = host_len else {
+        return Err(Error::corrupted(
+            None,
+            "Data after the first resource is not a well-formed overlay",
+        ));
+    };
+    let chain = chain
+        .into_iter()
+        .map(|index| configs[index].clone())
+        .collect();
+    Ok((host_len as u64, chain))
+}
+
+/// 寻找字节（速度较慢）
+///
+/// # 参数
+/// - `haystack`: 主字符串
+/// - `needle`: 子字符串
+///
+/// # 返回值
+/// - `Option<usize>`: 返回找到的字节位置
+fn _find_subsequence(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// 删除资源
+///
+/// # 参数
+/// - `target_file_path`: 目标文件路径
+/// - `id`: 资源ID
+/// - `output_path`: 输出文件路径(可选)
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err)
+pub fn remove_resource(
+    target_file_path: &Path,
+    id: &str,
+    output_path: Option<&Path>,
+    progress: ProgressCallback,
+) -> Result<()> {
+    // 打开目标文件
+    let mut source_file = FileSource::open(target_file_path)?;
+
+    // 搜索目标资源
+    let found = locate_resource(&mut source_file, id, &mut *progress)?;
+
+    // 确定输出路径
+    let output_path_buf = if let Some(output_path_param) = output_path {
+        resolve_path(target_file_path, output_path_param)?
+    } else {
+        target_file_path.to_path_buf()
+    };
+    remove_records(
+        source_file,
+        target_file_path,
+        &output_path_buf,
+        &[found],
+        progress,
+    )
+}
+
+/// 删除任意读写后端中的资源
+///
+/// # 参数
+/// - `backend`: 读写后端
+/// - `id`: 资源ID
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err)
+pub fn remove_resource_from_backend(
+    backend: &mut dyn Backend,
+    id: &str,
+    progress: ProgressCallback,
+) -> Result<()> {
+    let mut container = Container::from_backend(backend)?;
+    container.remove(id)?;
+    container.commit(None, progress)
+}
+
+/// 删除全部匹配的顶层资源（一次重写目标文件）
+///
+/// # 参数
+/// - `target_file_path`: 目标文件路径
+/// - `selector`: 资源选择器
+/// - `output_path`: 输出文件路径(可选)
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - `Ok(Vec<String>)`: 删除的资源ID列表
+/// - Err(err): 没有匹配的资源或删除失败
+pub fn remove_resources(
+    target_file_path: &Path,
+    selector: &Selector,
+    output_path: Option<&Path>,
+    progress: ProgressCallback,
+) -> Result<Vec<String>> {
+    let mut source_file = FileSource::open(target_file_path)?;
+    let found = locate_resources(&mut source_file, selector, &mut *progress)?;
+    let output_path_buf = if let Some(output_path_param) = output_path {
+        resolve_path(target_file_path, output_path_param)?
+    } else {
+        target_file_path.to_path_buf()
+    };
+    let ids = found
+        .iter()
+        .map(|(_, config)| config.id.trim().to_string())
+        .collect();
+    remove_records(
+        source_file,
+        target_file_path,
+        &output_path_buf,
+        &found,
+        progress,
+    )?;
+    Ok(ids)
+}
+
+/// 删除若干资源记录
+///
+/// # 参数
+/// - `source_file`: 目标文件
+/// - `target_file_path`: 目标文件路径
+/// - `output_path`: 输出文件路径(与目标文件相同时原地删除)
+/// - `removed`: (资源起始位置, 资源配置)列表
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err)
+fn remove_records(
+    mut source_file: FileSource,
+    target_file_path: &Path,
+    output_path: &Path,
+    removed: &[(usize, ResourceHead)],
+    progress: ProgressCallback,
+) -> Result<()> {
+    let removal = plan_removals(&mut source_file, Some(target_file_path), removed)?;
+    if removal.promoted.is_empty() {
+        remove_ranges(
+            source_file,
+            target_file_path,
+            output_path,
+            &removal.ranges,
+            progress,
+        )?;
+    } else {
+        promote_references(
+            source_file,
+            target_file_path,
+            output_path,
+            &removal,
+            progress,
+        )?;
+    }
+
+    // 原地删除时一并删除分卷文件(输出到新文件时原文件仍在使用)
+    if is_same_file(output_path, target_file_path)? {
+        remove_volume_files(&removal.volumes)?;
+    }
+    Ok(())
+}
+
+/// 删除资源时对文件的修改
+pub(crate) struct Removal {
+    /// 需要删除的区间(按位置排序)
+    pub(crate) ranges: Vec<Range>,
+    /// 需要删除的分卷文件
+    pub(crate) volumes: Vec<PathBuf>,
+    /// (提升后的资源头, 数据区间) 改为存储数据并移动到文件末尾的引用资源
+    pub(crate) promoted: Vec<(ResourceHead, Range)>,
+}
+
+/// 计算删除若干资源时对文件的修改
+///
+/// 被删除的资源的数据仍被保留的资源引用时，将第一个引用资源提升为存储数据的资源。
+///
+/// # 参数
+/// - `source_file`: 目标文件
+/// - `target_file_path`: 目标文件路径(没有路径的后端为 None，不删除分卷文件)
+/// - `removed`: (资源起始位置, 资源配置)列表
+///
+/// # 返回值
+/// - Ok(Removal): 对文件的修改
+/// - Err(err)
+pub(crate) fn plan_removals(
+    source_file: &mut FileSource,
+    target_file_path: Option<&Path>,
+    removed: &[(usize, ResourceHead)],
+) -> Result<Removal> {
+    let removed_starts: HashSet<usize> = removed.iter().map(|(pos, _)| *pos).collect();
+    let mut records = None;
+    let mut targets = Vec::with_capacity(removed.len());
+    let mut promoted = Vec::new();
+    let mut promoted_ranges = Vec::new();
+    for (start, config) in removed {
+        let end = resource_end(source_file, *start, config)?;
+        targets.push(((*start as u64, end as u64), config));
+        if config.storage != Storage::Inline || config.checksum.is_empty() {
+            continue;
+        }
+
+        // 其他资源引用该资源的数据时，将第一个引用提升为存储数据的资源
+        let records = match &mut records {
+            Some(records) => records,
+            None => records.insert(all_records(source_file)?),
+        };
+        let reference = records.iter().find(|(pos, c)| {
+            c.storage == Storage::Reference
+                && c.checksum == config.checksum
+                && !removed_starts.contains(pos)
+                && !promoted_ranges.iter().any(|(s, _)| *s == *pos as u64)
+        });
+        let Some((reference_start, reference)) = reference else {
+            continue;
+        };
+        let reference_end = resource_end(source_file, *reference_start, reference)?;
+        let data_start = (start + RESOURCE_MAGIC.len() + config.get_len()) as u64;
+        let length = config.length.trim().parse::<u64>().map_err(|e| {
+            Error::corrupted(
+                *start as u64,
+                format!("Failed to parse resource length ({})", e),
+            )
+        })?;
+        let mut head = reference.clone();
+        head.storage = Storage::Inline;
+        head.compress = config.compress;
+        head.set_length(length);
+        promoted.push((head, (data_start, data_start + length)));
+        promoted_ranges.push((*reference_start as u64, reference_end as u64));
+    }
+
+    let (mut ranges, volumes) = removal_ranges(source_file, target_file_path, &targets)?;
+    ranges.extend(promoted_ranges);
+    ranges.sort();
+    Ok(Removal {
+        ranges,
+        volumes,
+        promoted,
+    })
+}
+
+/// 文件区间(起始位置, 结束位置)
+pub(crate) type Range = (u64, u64);
+
+/// 获取删除资源时需要删除的全部区间与分卷文件
+///
+/// 删除差异资源时一并删除其旧版本(仍被其他资源引用数据的旧版本除外)，
+/// 删除分块资源时一并删除不再被其他资源使用的数据块。
+///
+/// # 参数
+/// - `source_file`: 目标文件
+/// - `target_file_path`: 目标文件路径(没有路径的后端为 None，不删除分卷文件)
+/// - `removed`: ((资源起始位置, 资源结束位置), 资源配置)列表
+///
+/// # 返回值
+/// - `Ok((Vec<(u64, u64)>, Vec<PathBuf>))`: (按位置排序的区间列表, 分卷文件路径列表)
+/// - Err(err)
+pub(crate) fn removal_ranges(
+    source_file: &mut FileSource,
+    target_file_path: Option<&Path>,
+    removed: &[(Range, &ResourceHead)],
+) -> Result<(Vec<Range>, Vec<PathBuf>)> {
+    let volume_files = |config: &ResourceHead| match target_file_path {
+        Some(path) => volume_files(path, config),
+        None => Vec::new(),
+    };
+    let mut ranges: Vec<Range> = removed.iter().map(|(range, _)| *range).collect();
+    let mut removed_starts: HashSet<usize> =
+        ranges.iter().map(|(start, _)| *start as usize).collect();
+    let mut chunked = removed
+        .iter()
+        .any(|(_, config)| config.storage == Storage::Chunked);
+    let mut volumes: Vec<PathBuf> = removed
+        .iter()
+        .flat_map(|(_, config)| volume_files(config))
+        .collect();
+    let delta_ids: Vec<&str> = removed
+        .iter()
+        .filter(|(_, config)| config.storage == Storage::Delta)
+        .map(|(_, config)| config.id.as_str())
+        .collect();
+    if !delta_ids.is_empty() {
+        let records = all_records(source_file)?;
+        for (pos, old) in &records {
+            // 引用该旧版本数据的资源同时被删除时不再保留
+            let referenced = records.iter().any(|(p, r)| {
+                r.storage == Storage::Reference
+                    && r.checksum == old.checksum
+                    && !removed_starts.contains(p)
+            });
+            if old.superseded
+                && delta_ids.contains(&old.id.as_str())
+                && !referenced
+                && !removed_starts.contains(pos)
+            {
+                let old_end = resource_end(source_file, *pos, old)?;
+                ranges.push((*pos as u64, old_end as u64));
+                removed_starts.insert(*pos);
+                chunked |= old.storage == Storage::Chunked;
+                volumes.extend(volume_files(old));
+            }
+        }
+    }
+
+    if chunked {
+        ranges.extend(unused_chunks(source_file, &removed_starts)?);
+    }
+    ranges.sort();
+    Ok((ranges, volumes))
+}
+
+/// 删除分卷文件(忽略已不存在的分卷)
+///
+/// # 参数
+/// - `volumes`: 分卷文件路径列表
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err)
+pub(crate) fn remove_volume_files(volumes: &[PathBuf]) -> Result<()> {
+    for path in volumes {
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// 获取删除分块资源后不再被使用的数据块区间
+///
+/// # 参数
+/// - `source_file`: 目标文件
+/// - `removed_starts`: 被删除的资源的起始位置
+///
+/// # 返回值
+/// - `Ok(Vec<(u64, u64)>)`: (数据块起始位置, 数据块结束位置)列表
+/// - Err(err)
+fn unused_chunks(
+    source_file: &mut FileSource,
+    removed_starts: &HashSet<usize>,
+) -> Result<Vec<(u64, u64)>> {
+    let records = all_records(source_file)?;
+    let mut used = HashSet::new();
+    for (pos, config) in &records {
+        if config.storage == Storage::Chunked && !removed_starts.contains(pos) {
+            let list = read_payload(source_file, *pos, config)?;
+            used.extend(
+                bincode::deserialize::<Vec<String>>(&list)
+                    .map_err(|e| Error::from(e).at(*pos as u64))?,
+            );
+        }
+    }
+
+    let mut ranges = Vec::new();
+    for (pos, config) in &records {
+        if config.storage == Storage::Chunk && !used.contains(&config.checksum) {
+            let end = resource_end(source_file, *pos, config)?;
+            ranges.push((*pos as u64, end as u64));
+        }
+    }
+    Ok(ranges)
+}
+
+/// 删除文件中的若干区间
+///
+/// # 参数
+/// - `source_file`: 目标文件
+/// - `target_file_path`: 目标文件路径
+/// - `output_path`: 输出文件路径(与目标文件相同时原地删除)
+/// - `ranges`: 按位置排序且互不重叠的(起始位置, 结束位置)列表
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err)
+fn remove_ranges(
+    mut source_file: FileSource,
+    target_file_path: &Path,
+    output_path: &Path,
+    ranges: &[(u64, u64)],
+    progress: ProgressCallback,
+) -> Result<()> {
+    let file_len = source_file.len()?;
+    let removed_len: u64 = ranges.iter().map(|(start, end)| end - start).sum();
+    let new_len = file_len - removed_len;
+
+    // 保留的数据区间(起始位置, 长度)
+    let mut kept = Vec::with_capacity(ranges.len() + 1);
+    let mut pos = 0;
+    for &(start, end) in ranges {
+        kept.push((pos, start - pos));
+        pos = end;
+    }
+    kept.push((pos, file_len - pos));
+
+    let mut tracker = ProgressTracker::new(progress, Stage::Rewrite, Some(new_len));
+    if is_same_file(output_path, target_file_path)? {
+        // 原地删除：将保留的数据依次前移，再截断文件
+        drop(source_file);
+        let mut target_file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(output_path)?;
+        let mut buffer = vec![0u8; BUFFER_SIZE];
+        let mut write_pos = 0;
+        for (offset, length) in kept {
+            if offset == write_pos {
+                write_pos += length;
+                tracker.advance(length);
+                continue;
+            }
+            let (mut read_pos, end) = (offset, offset + length);
+            while read_pos < end {
+                let n = buffer.len().min((end - read_pos) as usize);
+                target_file.seek(SeekFrom::Start(read_pos))?;
+                target_file.read_exact(&mut buffer[..n])?;
+                target_file.seek(SeekFrom::Start(write_pos))?;
+                target_file.write_all(&buffer[..n])?;
+                read_pos += n as u64;
+                write_pos += n as u64;
+                tracker.advance(n as u64);
+            }
+        }
+        target_file.set_len(new_len)?;
+        target_file.flush()?;
+    } else {
+        // 输出到新文件：复制保留的数据
+        let mut output_file = BufWriter::with_capacity(BUFFER_SIZE, File::create(output_path)?);
+        for (offset, length) in kept {
+            copy_range(
+                &mut source_file,
+                offset,
+                length,
+                &mut output_file,
+                &mut tracker,
+            )?;
+        }
+        output_file.flush()?;
+    }
+    tracker.report();
+
+    Ok(())
+}
+
+/// 删除资源，并将引用被删除数据的资源提升为存储数据的资源(移动到文件末尾)
+///
+/// # 参数
+/// - `source_file`: 目标文件
+/// - `target_file_path`: 目标文件路径
+/// - `output_path`: 输出文件路径(与目标文件相同时先写入临时文件再替换)
+/// - `removal`: 对文件的修改
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err)
+fn promote_references(
+    mut source_file: FileSource,
+    target_file_path: &Path,
+    output_path: &Path,
+    removal: &Removal,
+    progress: ProgressCallback,
+) -> Result<()> {
+    let file_len = source_file.len()?;
+    let removed_len: u64 = removal.ranges.iter().map(|(start, end)| end - start).sum();
+    let promoted_len: u64 = removal
+        .promoted
+        .iter()
+        .map(|(head, (start, end))| {
+            (RESOURCE_MAGIC.len() + head.get_len() + TRAILER_SIZE + END_IDENTIFIER.len()) as u64
+                + (end - start)
+        })
+        .sum();
+    let new_len = file_len - removed_len + promoted_len;
+    let mut tracker = ProgressTracker::new(progress, Stage::Rewrite, Some(new_len));
+
+    // 原地修改时写入临时文件，完成后替换目标文件
+    let in_place = is_same_file(output_path, target_file_path)?;
+    let (write_path, output_file) = if in_place {
+        create_temp_file(output_path)?
+    } else {
+        (output_path.to_path_buf(), File::create(output_path)?)
+    };
+    let result = (|| -> Result<()> {
+        let mut output_file = BufWriter::with_capacity(BUFFER_SIZE, output_file);
+        let mut pos = 0;
+        for &(start, end) in &removal.ranges {
+            copy_range(
+                &mut source_file,
+                pos,
+                start - pos,
+                &mut output_file,
+                &mut tracker,
+            )?;
+            pos = end;
+        }
+        copy_range(
+            &mut source_file,
+            pos,
+            file_len - pos,
+            &mut output_file,
+            &mut tracker,
+        )?;
+        for (head, (start, end)) in &removal.promoted {
+            let mut data = source_file.range_reader(*start, end - start)?;
+            write_record(&mut output_file, head, &mut data)?;
+        }
+        output_file.flush()?;
+        Ok(())
+    })();
+    if let Err(e) = result {
+        let _ = fs::remove_file(&write_path);
+        return Err(e);
+    }
+    drop(source_file);
+    if in_place {
+        fs::set_permissions(&write_path, fs::metadata(target_file_path)?.permissions())?;
+        fs::rename(&write_path, output_path)?;
+    }
+    tracker.set(new_len);
+    tracker.report();
+    Ok(())
+}
+
+/// 复制文件区间
+///
+/// # 参数
+/// - `source_file`: 源文件
+/// - `offset`: 起始位置
+/// - `length`: 复制长度
+/// - `writer`: 输出数据流
+/// - `tracker`: 进度计数器
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err)
+pub(crate) fn copy_range(
+    source_file: &mut FileSource,
+    offset: u64,
+    length: u64,
+    writer: &mut dyn Write,
+    tracker: &mut ProgressTracker,
+) -> Result<()> {
+    let mut reader = source_file.range_reader(offset, length)?;
+    let mut buffer = vec![0u8; BUFFER_SIZE];
+    loop {
+        let n = reader.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        writer.write_all(&buffer[..n])?;
+        tracker.advance(n as u64);
+    }
     Ok(())
 }
@@ -0,0 +1,158 @@
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+
+/// 匹配块大小（字节），短于该长度的相同数据按插入处理
+const BLOCK_SIZE: usize = 32;
+
+/// 滚动哈希的乘数
+const HASH_BASE: u64 = 0x100_0000_01B3;
+
+/// 差异指令
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub enum DeltaOp {
+    /// 复制旧版本数据(起始位置, 长度)
+    Copy { offset: u64, length: u64 },
+    /// 插入新数据
+    Insert(Vec<u8>),
+}
+
+/// 差异补丁
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct DeltaPatch {
+    /// 旧版本数据的 SHA-256 校验和(十六进制)
+    pub base_checksum: String,
+    /// 差异指令
+    pub ops: Vec<DeltaOp>,
+}
+
+/// 计算新旧版本之间的差异
+///
+/// 对旧版本按块建立滚动哈希索引，在新版本中逐字节查找相同的块并向前后扩展，
+/// 匹配部分生成复制指令，其余部分生成插入指令。
+///
+/// # 参数
+/// - `base`: 旧版本数据
+/// - `target`: 新版本数据
+///
+/// # 返回值
+/// - `Vec<DeltaOp>`: 差异指令
+pub fn diff(base: &[u8], target: &[u8]) -> Vec<DeltaOp> {
+    let mut ops = Vec::new();
+    if base.len() < BLOCK_SIZE || target.len() < BLOCK_SIZE {
+        if !target.is_empty() {
+            ops.push(DeltaOp::Insert(target.to_vec()));
+        }
+        return ops;
+    }
+
+    // 旧版本按块建立索引(哈希 -> 块起始位置)
+    let mut index = HashMap::new();
+    for offset in (0..=base.len() - BLOCK_SIZE).step_by(BLOCK_SIZE) {
+        index
+            .entry(block_hash(&base[offset..offset + BLOCK_SIZE]))
+            .or_insert(offset);
+    }
+
+    // 最高位字节在滚动时需要移除的权重
+    let top_weight = (1..BLOCK_SIZE).fold(1u64, |weight, _| weight.wrapping_mul(HASH_BASE));
+    let mut literal_start = 0;
+    let mut pos = 0;
+    let mut hash = block_hash(&target[..BLOCK_SIZE]);
+    while pos + BLOCK_SIZE <= target.len() {
+        let matched = index.get(&hash).and_then(|&offset| {
+            (base[offset..offset + BLOCK_SIZE] == target[pos..pos + BLOCK_SIZE]).then_some(offset)
+        });
+        let Some(offset) = matched else {
+            // 滚动到下一个位置
+            if pos + BLOCK_SIZE < target.len() {
+                hash = hash
+                    .wrapping_sub((target[pos] as u64).wrapping_mul(top_weight))
+                    .wrapping_mul(HASH_BASE)
+                    .wrapping_add(target[pos + BLOCK_SIZE] as u64);
+            }
+            pos += 1;
+            continue;
+        };
+
+        // 向前扩展匹配(不超过未输出的插入数据)
+        let mut backward = 0;
+        while backward < offset
+            && pos - backward > literal_start
+            && base[offset - backward - 1] == target[pos - backward - 1]
+        {
+            backward += 1;
+        }
+        // 向后扩展匹配
+        let mut length = BLOCK_SIZE;
+        while offset + length < base.len()
+            && pos + length < target.len()
+            && base[offset + length] == target[pos + length]
+        {
+            length += 1;
+        }
+
+        let (copy_start, copy_offset) = (pos - backward, offset - backward);
+        if copy_start > literal_start {
+            ops.push(DeltaOp::Insert(target[literal_start..copy_start].to_vec()));
+        }
+        ops.push(DeltaOp::Copy {
+            offset: copy_offset as u64,
+            length: (length + backward) as u64,
+        });
+        pos += length;
+        literal_start = pos;
+        if pos + BLOCK_SIZE <= target.len() {
+            hash = block_hash(&target[pos..pos + BLOCK_SIZE]);
+        }
+    }
+    if literal_start < target.len() {
+        ops.push(DeltaOp::Insert(target[literal_start..].to_vec()));
+    }
+    ops
+}
+
+/// 应用差异，还原新版本数据
+///
+/// # 参数
+/// - `base`: 旧版本数据
+/// - `ops`: 差异指令
+/// - `output`: 输出数据流
+///
+/// # 返回值
+/// - `Ok(u64)`: 还原后的字节数
+/// - Err(err): 差异指令超出旧版本数据
+pub fn apply(base: &[u8], ops: &[DeltaOp], output: &mut dyn Write) -> Result<u64> {
+    let mut written = 0;
+    for op in ops {
+        match op {
+            DeltaOp::Copy { offset, length } => {
+                let data = (*offset as usize)
+                    .checked_add(*length as usize)
+                    .and_then(|end| base.get(*offset as usize..end))
+                    .ok_or_else(|| Error::corrupted(None, "Delta copy exceeds base data"))?;
+                output.write_all(data)?;
+                written += length;
+            }
+            DeltaOp::Insert(data) => {
+                output.write_all(data)?;
+                written += data.len() as u64;
+            }
+        }
+    }
+    Ok(written)
+}
+
+/// 计算数据块的多项式哈希
+///
+/// # 参数
+/// - `block`: 数据块
+///
+/// # 返回值
+/// - `u64`: 哈希值
+fn block_hash(block: &[u8]) -> u64 {
+    block.iter().fold(0u64, |hash, &byte| {
+        hash.wrapping_mul(HASH_BASE).wrapping_add(byte as u64)
+    })
+}
@@ -0,0 +1,129 @@
+use std::fmt;
+use std::io;
+
+/// 资源操作结果
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// 资源操作错误
+///
+/// 按错误类别区分，便于调用方分别处理(如资源不存在与文件损坏)。
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// 资源不存在(资源ID，未指定资源时为空)
+    NotFound(String),
+    /// 资源ID已存在(资源ID)
+    DuplicateId(String),
+    /// 资源ID无效(原因)
+    InvalidId(String),
+    /// 资源文件名无效(原因)
+    InvalidName(String),
+    /// 参数无效或超出限制(原因)
+    InvalidInput(String),
+    /// 文件数据损坏
+    Corrupted {
+        /// 损坏数据在文件中的位置(未知时为 None)
+        offset: Option<u64>,
+        /// 原因
+        reason: String,
+    },
+    /// 资源版本高于程序支持的版本
+    VersionMismatch {
+        /// 资源版本
+        found: String,
+        /// 程序支持的版本
+        supported: String,
+    },
+    /// 不支持的操作(原因)
+    Unsupported(String),
+    /// 读写错误
+    Io(io::Error),
+}
+
+impl Error {
+    /// 创建数据损坏错误
+    ///
+    /// # 参数
+    /// - `offset`: 损坏数据在文件中的位置
+    /// - `reason`: 原因
+    pub fn corrupted(offset: impl Into<Option<u64>>, reason: impl Into<String>) -> Self {
+        Error::Corrupted {
+            offset: offset.into(),
+            reason: reason.into(),
+        }
+    }
+
+    /// 为位置未知的数据损坏错误补充位置
+    ///
+    /// # 参数
+    /// - `offset`: 损坏数据在文件中的位置
+    pub(crate) fn at(self, offset: u64) -> Self {
+        match self {
+            Error::Corrupted {
+                offset: None,
+                reason,
+            } => Error::Corrupted {
+                offset: Some(offset),
+                reason,
+            },
+            error => error,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotFound(id) if id.is_empty() => write!(f, "Resource not found"),
+            Error::NotFound(id) => write!(f, "Resource not found: {}", id),
+            Error::DuplicateId(id) => write!(f, "Resource ID already exists: {}", id),
+            Error::InvalidId(reason) => write!(f, "Invalid resource ID: {}", reason),
+            Error::InvalidName(reason) => write!(f, "Invalid resource name: {}", reason),
+            Error::InvalidInput(reason) => write!(f, "{}", reason),
+            Error::Corrupted {
+                offset: Some(offset),
+                reason,
+            } => write!(
+                f,
+                "{} at offset {} (0x{:X}) - file may be corrupted",
+                reason, offset, offset
+            ),
+            Error::Corrupted {
+                offset: None,
+                reason,
+            } => write!(f, "{} - file may be corrupted", reason),
+            Error::VersionMismatch { found, supported } => write!(
+                f,
+                "Resource version mismatch: file has {}, program supports {}",
+                found, supported
+            ),
+            Error::Unsupported(reason) => write!(f, "{}", reason),
+            Error::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+/// 序列化错误：读写错误保留原样，其余视为数据损坏
+impl From<bincode::Error> for Error {
+    fn from(e: bincode::Error) -> Self {
+        match *e {
+            bincode::ErrorKind::Io(e) => Error::Io(e),
+            e => Error::corrupted(None, format!("Malformed record data ({})", e)),
+        }
+    }
+}
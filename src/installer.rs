@@ -0,0 +1,283 @@
+use crate::core::{
+    export_resource_to_writer, find_nested_resources, find_resources_config, ResourceHead,
+    NESTED_SEPARATOR,
+};
+use crate::error::{Error, Result};
+use crate::progress::{ProgressCallback, ProgressTracker, Stage};
+use crate::util::{check_file_name, restore_file_attributes, HashingReader};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Component, Path, PathBuf};
+
+/// 覆盖已有文件前的备份文件后缀
+const BACKUP_SUFFIX: &str = ".appender-backup";
+
+/// 资源安装器（事务式释放）
+///
+/// 将多个资源释放到目标目录，记录创建的文件与目录；任一资源释放或校验失败时，
+/// 删除已创建的文件与目录并还原被覆盖的文件，目标目录保持安装前的状态。
+///
+/// ```no_run
+/// use appender::installer::Installer;
+///
+/// let installed = Installer::new(std::env::current_exe()?, "C:/Program Files/MyApp")
+///     .resource("Runtime")
+///     .resource_to("Config", "etc/config.toml")
+///     .install(&mut |_| {})?;
+/// # Ok::<(), appender::error::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Installer {
+    /// 携带资源的文件路径
+    source_file_path: PathBuf,
+    /// 目标目录
+    destination: PathBuf,
+    /// (资源ID, 相对目标目录的路径；None 时使用资源文件名)
+    resources: Vec<(String, Option<PathBuf>)>,
+}
+
+/// 安装过程中对目标目录的修改(用于回滚)
+#[derive(Default)]
+struct Transaction {
+    /// 新建的目录(按创建顺序)
+    created_dirs: Vec<PathBuf>,
+    /// 写入的文件
+    created_files: Vec<PathBuf>,
+    /// (被覆盖的文件, 备份文件)
+    backups: Vec<(PathBuf, PathBuf)>,
+}
+
+impl Installer {
+    /// 创建安装器
+    ///
+    /// # 参数
+    /// - `source_file_path`: 携带资源的文件路径(如 `std::env::current_exe()`)
+    /// - `destination`: 目标目录(不存在时创建)
+    pub fn new(source_file_path: impl Into<PathBuf>, destination: impl Into<PathBuf>) -> Self {
+        Installer {
+            source_file_path: source_file_path.into(),
+            destination: destination.into(),
+            resources: Vec::new(),
+        }
+    }
+
+    /// 添加资源(释放到目标目录下，使用资源文件名)
+    pub fn resource(mut self, id: &str) -> Self {
+        self.resources.push((id.to_string(), None));
+        self
+    }
+
+    /// 添加资源(释放到目标目录下的指定相对路径)
+    pub fn resource_to(mut self, id: &str, path: impl Into<PathBuf>) -> Self {
+        self.resources.push((id.to_string(), Some(path.into())));
+        self
+    }
+
+    /// 安装全部资源
+    ///
+    /// 先确认全部资源存在且路径有效，再依次释放；每个文件写入后重新计算校验和。
+    /// 任一步骤失败时回滚全部修改。
+    ///
+    /// # 参数
+    /// - `progress`: 进度回调(`Stage::Install`，按解码后的字节数统计)
+    ///
+    /// # 返回值
+    /// - `Ok(Vec<PathBuf>)`: 安装的文件路径列表
+    /// - Err(err): 安装失败(已回滚)
+    pub fn install(&self, progress: ProgressCallback) -> Result<Vec<PathBuf>> {
+        let plan = self.plan()?;
+        let total = plan
+            .iter()
+            .map(|(_, _, config)| config.size().trim().parse::<u64>().unwrap_or(0))
+            .sum();
+        let mut tracker = ProgressTracker::new(progress, Stage::Install, Some(total));
+
+        let mut transaction = Transaction::default();
+        let result = (|| -> Result<()> {
+            transaction.create_dir_all(&self.destination)?;
+            for (id, path, config) in &plan {
+                if let Some(parent) = path.parent() {
+                    transaction.create_dir_all(parent)?;
+                }
+                transaction.backup(path)?;
+                transaction.created_files.push(path.clone());
+                self.extract(id, path, config, &mut tracker)?;
+            }
+            Ok(())
+        })();
+        match result {
+            Ok(()) => {
+                transaction.commit();
+                tracker.report();
+                Ok(plan.into_iter().map(|(_, path, _)| path).collect())
+            }
+            Err(e) => {
+                transaction.rollback();
+                Err(e)
+            }
+        }
+    }
+
+    /// 确认全部资源存在并确定释放路径
+    ///
+    /// # 返回值
+    /// - `Ok(Vec<(String, PathBuf, ResourceHead)>)`: (资源ID, 释放路径, 资源配置)列表
+    /// - Err(err): 资源不存在或路径超出目标目录
+    fn plan(&self) -> Result<Vec<(String, PathBuf, ResourceHead)>> {
+        let mut configs = find_resources_config(&self.source_file_path, |_, _| {}, &mut |_| {})?
+            .into_iter()
+            .map(|config| (config.id().trim().to_string(), config))
+            .collect::<Vec<_>>();
+        if self
+            .resources
+            .iter()
+            .any(|(id, _)| id.contains(NESTED_SEPARATOR))
+        {
+            configs.extend(find_nested_resources(&self.source_file_path, &mut |_| {})?);
+        }
+
+        let mut plan = Vec::with_capacity(self.resources.len());
+        for (id, path) in &self.resources {
+            let (_, config) = configs
+                .iter()
+                .find(|(path, _)| path == id.trim())
+                .ok_or_else(|| Error::NotFound(id.trim().to_string()))?;
+            let relative = match path {
+                Some(path) => path.clone(),
+                None => {
+                    check_file_name(config.name().trim())?;
+                    PathBuf::from(config.name().trim())
+                }
+            };
+            if relative.as_os_str().is_empty()
+                || !relative
+                    .components()
+                    .all(|c| matches!(c, Component::Normal(_)))
+            {
+                return Err(Error::InvalidInput(format!(
+                    "Install path {} of resource {} is outside the destination",
+                    relative.display(),
+                    id
+                )));
+            }
+            let path = self.destination.join(relative);
+            if plan.iter().any(|(_, p, _)| *p == path) {
+                return Err(Error::InvalidInput(format!(
+                    "Install path {} is used twice",
+                    path.display()
+                )));
+            }
+            plan.push((id.clone(), path, config.clone()));
+        }
+        Ok(plan)
+    }
+
+    /// 释放单个资源并重新校验写入的文件
+    fn extract(
+        &self,
+        id: &str,
+        path: &Path,
+        config: &ResourceHead,
+        tracker: &mut ProgressTracker,
+    ) -> Result<()> {
+        let mut writer = TrackingWriter {
+            inner: BufWriter::new(File::create(path)?),
+            tracker,
+        };
+        export_resource_to_writer(&self.source_file_path, id, &mut writer, &mut |_| {})?;
+        writer.flush()?;
+        drop(writer);
+
+        // 重新读取写入的文件，确认与资源校验和一致
+        if !config.checksum().is_empty() {
+            let mut reader = HashingReader::new(File::open(path)?);
+            io::copy(&mut reader, &mut io::sink())?;
+            if reader.checksum() != config.checksum() {
+                return Err(Error::corrupted(
+                    None,
+                    format!(
+                        "Installed file {} does not match the resource checksum",
+                        path.display()
+                    ),
+                ));
+            }
+        }
+        restore_file_attributes(
+            path,
+            config.metadata().modified,
+            config.metadata().permissions,
+        )?;
+        Ok(())
+    }
+}
+
+impl Transaction {
+    /// 创建目录(记录新建的各级目录)
+    fn create_dir_all(&mut self, path: &Path) -> Result<()> {
+        let mut missing = Vec::new();
+        let mut current = Some(path);
+        while let Some(dir) = current {
+            if dir.as_os_str().is_empty() || dir.exists() {
+                break;
+            }
+            missing.push(dir.to_path_buf());
+            current = dir.parent();
+        }
+        for dir in missing.into_iter().rev() {
+            fs::create_dir(&dir)?;
+            self.created_dirs.push(dir);
+        }
+        Ok(())
+    }
+
+    /// 备份将被覆盖的文件
+    fn backup(&mut self, path: &Path) -> Result<()> {
+        if !path.exists() {
+            return Ok(());
+        }
+        let mut backup = path.as_os_str().to_owned();
+        backup.push(BACKUP_SUFFIX);
+        let backup = PathBuf::from(backup);
+        fs::rename(path, &backup)?;
+        self.backups.push((path.to_path_buf(), backup));
+        Ok(())
+    }
+
+    /// 提交：删除备份文件
+    fn commit(self) {
+        for (_, backup) in &self.backups {
+            let _ = fs::remove_file(backup);
+        }
+    }
+
+    /// 回滚：删除写入的文件，还原被覆盖的文件，删除新建的目录
+    fn rollback(self) {
+        for path in self.created_files.iter().rev() {
+            let _ = fs::remove_file(path);
+        }
+        for (path, backup) in self.backups.iter().rev() {
+            let _ = fs::rename(backup, path);
+        }
+        for dir in self.created_dirs.iter().rev() {
+            let _ = fs::remove_dir(dir);
+        }
+    }
+}
+
+/// 报告写入进度的数据流
+struct TrackingWriter<'a, 'b, W> {
+    inner: W,
+    tracker: &'a mut ProgressTracker<'b>,
+}
+
+impl<W: Write> Write for TrackingWriter<'_, '_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.tracker.advance(n as u64);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,16 @@
+pub mod container;
+pub mod core;
+pub mod delta;
+pub mod error;
+pub mod installer;
+pub mod plan;
+pub mod progress;
+pub mod select;
+pub mod server;
+pub mod sfx;
+pub mod sidecar;
+mod source;
+pub mod util;
+
+#[cfg(test)]
+mod tests;
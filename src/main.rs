@@ -1,53 +1,118 @@
-use crate::cli::{Cli, Commands};
-use crate::core::{add_resource, export_resource, find_resources_config, remove_resource};
-use clap::Parser;
+use crate::cli::{Cli, Commands, STDIO_PATH};
+use appender::core::{
+    add_resource, add_resource_from_reader, export_resource, export_resource_to_writer,
+    export_resources, find_nested_resources, find_resources_config, inspect_resources,
+    remove_resource, remove_resources, strip_resources, update_resource, verify_resource,
+    volume_path, AddOptions, CompressMode, ExportOptions, Metadata, Storage,
+};
+use appender::error::Error;
+use appender::plan::{
+    plan_add, plan_add_from_reader, plan_remove, plan_remove_resources, plan_update, Change, DryRun,
+};
+use appender::progress::{Progress, Stage};
+use appender::select::Selector;
+use appender::server::Server;
+use appender::sfx::{build_sfx, default_stub_path, SfxOptions};
+use appender::sidecar::{create_sidecar, open_sidecar};
+use appender::util::format_timestamp;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::io;
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 
 mod cli;
-mod core;
-mod util;
 
-#[cfg(test)]
-mod tests;
+/// 输出提示信息(静默模式下不输出)
+macro_rules! status {
+    ($quiet:expr, $($arg:tt)*) => {
+        if !$quiet {
+            println!($($arg)*);
+        }
+    };
+}
 
 fn main() -> ExitCode {
     let cli = Cli::parse();
+    let quiet = cli.quiet;
     match cli.command {
         // 列出资源
-        Commands::List { target_file, id } => {
-            println!("Listing resources from \"{}\":", target_file.display());
-            match find_resources_config(&target_file, |_pos, _config| ()) {
-                Ok(configs) => {
-                    let filtered: Vec<_> = if let Some(ref filter_id) = id {
-                        configs
-                            .iter()
-                            .filter(|c| c.id().trim() == filter_id.trim())
-                            .collect()
-                    } else {
-                        configs.iter().collect()
-                    };
+        Commands::List {
+            target_file,
+            id,
+            glob,
+            regex,
+            recursive,
+            sidecar,
+        } => {
+            let selector = match (id, selector(glob, regex)) {
+                (_, Err(e)) => {
+                    eprintln!("Failed to list resources: {}", e);
+                    return exit_code(&e);
+                }
+                (_, Ok(Some(selector))) => Some(selector),
+                (id, Ok(None)) => id.map(Selector::Exact),
+            };
+            let target_file = match target_path(target_file, sidecar, false) {
+                Ok(target_file) => target_file,
+                Err(e) => {
+                    eprintln!("Failed to open sidecar: {}", e);
+                    return exit_code(&e);
+                }
+            };
+            status!(
+                quiet,
+                "Listing resources from \"{}\":",
+                target_file.display()
+            );
+            // 按嵌套资源路径筛选时需要列出嵌套资源
+            let recursive = recursive || selector.as_ref().is_some_and(Selector::nested);
+            let result = {
+                let mut display = ProgressDisplay::new(quiet);
+                let mut progress = |p| display.update(p);
+                if recursive {
+                    find_nested_resources(&target_file, &mut progress)
+                } else {
+                    find_resources_config(&target_file, |_pos, _config| (), &mut progress).map(
+                        |configs| {
+                            configs
+                                .into_iter()
+                                .map(|c| (c.id().trim().to_string(), c))
+                                .collect()
+                        },
+                    )
+                }
+            };
+            match result {
+                Ok(resources) => {
+                    let filtered: Vec<_> = resources
+                        .iter()
+                        .filter(|(path, _)| selector.as_ref().is_none_or(|s| s.matches(path)))
+                        .collect();
 
                     let count = filtered.len();
-                    for config in filtered {
+                    for (path, config) in filtered {
                         println!(
                             "  ID: {} | Name: {} | Size: {} bytes | Compressed: {}",
-                            config.id().trim(),
+                            path,
                             config.name().trim(),
                             config.size().trim().parse().unwrap_or(0),
-                            if config.compress() == core::CompressMode::Compress {
+                            if config.compress() == CompressMode::Compress {
                                 "Yes"
                             } else {
                                 "No"
                             }
                         );
+                        print_metadata(config.metadata());
                     }
 
-                    println!("Found {} resource(s)", count);
+                    status!(quiet, "Found {} resource(s)", count);
                     ExitCode::SUCCESS
                 }
                 Err(e) => {
                     eprintln!("Failed to list resources: {}", e);
-                    ExitCode::FAILURE
+                    exit_code(&e)
                 }
             }
         }
@@ -57,32 +122,153 @@ fn main() -> ExitCode {
             resources,
             id,
             new_file_path,
+            name,
             compression,
+            threads,
+            tags,
+            chunked,
+            split_size,
+            sidecar,
+            dry_run,
         } => {
-            println!(
+            // 预演时不创建旁路文件
+            let target_file = match target_path(target_file, sidecar, !dry_run) {
+                Ok(target_file) => target_file,
+                Err(e) => {
+                    eprintln!("Failed to open sidecar: {}", e);
+                    return exit_code(&e);
+                }
+            };
+            status!(
+                quiet,
                 "Adding resource \"{}\" (ID: {}) to \"{}\"...",
                 resources.display(),
                 id,
                 target_file.display()
             );
-            match add_resource(
-                &target_file,
-                &resources,
-                &id,
-                if compression == 0 {
+            let options = AddOptions {
+                name,
+                compression_grade: if compression == 0 {
                     None
                 } else {
                     Some(compression)
                 },
-                new_file_path.as_deref(),
-            ) {
+                threads: if threads == 0 {
+                    std::thread::available_parallelism().map_or(1, |n| n.get())
+                } else {
+                    threads
+                },
+                output_path: new_file_path,
+                tags,
+                chunked,
+                split_size,
+            };
+            if dry_run {
+                let result = {
+                    let mut display = ProgressDisplay::new(quiet);
+                    let mut progress = |p| display.update(p);
+                    if resources.as_os_str() == STDIO_PATH {
+                        plan_add_from_reader(
+                            &target_file,
+                            &mut io::stdin().lock(),
+                            &id,
+                            &options,
+                            &mut progress,
+                        )
+                    } else {
+                        plan_add(&target_file, &resources, &id, &options, &mut progress)
+                    }
+                };
+                return report_dry_run(&target_file, result, "add resource");
+            }
+            let result = {
+                let mut display = ProgressDisplay::new(quiet);
+                let mut progress = |p| display.update(p);
+                if resources.as_os_str() == STDIO_PATH {
+                    // 从标准输入读取资源数据
+                    add_resource_from_reader(
+                        &target_file,
+                        &mut io::stdin().lock(),
+                        &id,
+                        &options,
+                        &mut progress,
+                    )
+                } else {
+                    add_resource(&target_file, &resources, &id, &options, &mut progress)
+                }
+            };
+            match result {
                 Ok(()) => {
-                    println!("Resource added successfully");
+                    status!(quiet, "Resource added successfully");
                     ExitCode::SUCCESS
                 }
                 Err(e) => {
                     eprintln!("Failed to add resource: {}", e);
-                    ExitCode::FAILURE
+                    exit_code(&e)
+                }
+            }
+        }
+        // 更新资源
+        Commands::Update {
+            target_file,
+            resources,
+            id,
+            new_file_path,
+            name,
+            compression,
+            tags,
+            delta,
+            sidecar,
+            dry_run,
+        } => {
+            let target_file = match target_path(target_file, sidecar, false) {
+                Ok(target_file) => target_file,
+                Err(e) => {
+                    eprintln!("Failed to open sidecar: {}", e);
+                    return exit_code(&e);
+                }
+            };
+            status!(
+                quiet,
+                "Updating resource (ID: {}) in \"{}\" from \"{}\"...",
+                id,
+                target_file.display(),
+                resources.display()
+            );
+            let options = AddOptions {
+                name,
+                compression_grade: if compression == 0 {
+                    None
+                } else {
+                    Some(compression)
+                },
+                output_path: new_file_path,
+                tags,
+                ..AddOptions::default()
+            };
+            if dry_run {
+                let result = {
+                    let mut display = ProgressDisplay::new(quiet);
+                    plan_update(&target_file, &resources, &id, &options, delta, &mut |p| {
+                        display.update(p)
+                    })
+                };
+                return report_dry_run(&target_file, result, "update resource");
+            }
+            let result = {
+                let mut display = ProgressDisplay::new(quiet);
+                update_resource(&target_file, &resources, &id, &options, delta, &mut |p| {
+                    display.update(p)
+                })
+            };
+            match result {
+                Ok(()) => {
+                    status!(quiet, "Resource updated successfully");
+                    ExitCode::SUCCESS
+                }
+                Err(e) => {
+                    eprintln!("Failed to update resource: {}", e);
+                    exit_code(&e)
                 }
             }
         }
@@ -91,21 +277,95 @@ fn main() -> ExitCode {
             target_file,
             id,
             output_path,
+            glob,
+            regex,
+            sidecar,
+            allow_unsafe_names,
         } => {
-            println!(
+            let options = ExportOptions { allow_unsafe_names };
+            let (id, output_path) = match resource_args("export", glob, regex, id, output_path) {
+                Ok(Resources::Single(id, Some(output_path))) => (id, output_path),
+                Ok(Resources::Single(..)) => missing_argument("export", "<OUTPUT_PATH>"),
+                Ok(Resources::Matching(_, None)) => missing_argument("export", "<OUTPUT_PATH>"),
+                Ok(Resources::Matching(selector, Some(output_dir))) => {
+                    let target_file = match target_path(target_file, sidecar, false) {
+                        Ok(target_file) => target_file,
+                        Err(e) => {
+                            eprintln!("Failed to open sidecar: {}", e);
+                            return exit_code(&e);
+                        }
+                    };
+                    return export_matching(&target_file, &selector, &output_dir, &options, quiet);
+                }
+                Err(e) => {
+                    eprintln!("Failed to export resource: {}", e);
+                    return exit_code(&e);
+                }
+            };
+            let target_file = match target_path(target_file, sidecar, false) {
+                Ok(target_file) => target_file,
+                Err(e) => {
+                    eprintln!("Failed to open sidecar: {}", e);
+                    return exit_code(&e);
+                }
+            };
+            // 输出到标准输出时，提示信息写入标准错误以免污染数据
+            if output_path.as_os_str() == STDIO_PATH {
+                if !quiet {
+                    eprintln!(
+                        "Exporting resource (ID: {}) from \"{}\" to stdout...",
+                        id,
+                        target_file.display()
+                    );
+                }
+                let result = {
+                    let mut display = ProgressDisplay::new(quiet);
+                    export_resource_to_writer(
+                        &target_file,
+                        &id,
+                        &mut io::stdout().lock(),
+                        &mut |p| display.update(p),
+                    )
+                };
+                return match result {
+                    Ok(_) => {
+                        if !quiet {
+                            eprintln!("Resource exported successfully");
+                        }
+                        ExitCode::SUCCESS
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to export resource: {}", e);
+                        exit_code(&e)
+                    }
+                };
+            }
+            status!(
+                quiet,
                 "Exporting resource (ID: {}) from \"{}\" to \"{}\"...",
                 id,
                 target_file.display(),
                 output_path.display()
             );
-            match export_resource(&target_file, &id, &output_path) {
+            let result = {
+                let mut display = ProgressDisplay::new(quiet);
+                export_resource(&target_file, &id, &output_path, &options, &mut |p| {
+                    display.update(p)
+                })
+            };
+            match result {
                 Ok(()) => {
-                    println!("Resource exported successfully");
+                    status!(quiet, "Resource exported successfully");
                     ExitCode::SUCCESS
                 }
                 Err(e) => {
                     eprintln!("Failed to export resource: {}", e);
-                    ExitCode::FAILURE
+                    if matches!(e, Error::InvalidName(_)) {
+                        eprintln!(
+                            "Pass an output file path instead of a directory, or --allow-unsafe-names to use the stored name as-is"
+                        );
+                    }
+                    exit_code(&e)
                 }
             }
         }
@@ -114,22 +374,795 @@ fn main() -> ExitCode {
             target_file,
             id,
             new_file_path,
+            glob,
+            regex,
+            sidecar,
+            dry_run,
         } => {
-            println!(
+            let id = match resource_args("remove", glob, regex, id, new_file_path.clone()) {
+                Ok(Resources::Single(id, _)) => id,
+                Ok(Resources::Matching(selector, new_file_path)) => {
+                    if sidecar && new_file_path.is_some() {
+                        usage_error(
+                            "remove",
+                            ErrorKind::ArgumentConflict,
+                            "the argument '--sidecar' cannot be used with '[NEW_FILE_PATH]'",
+                        );
+                    }
+                    let target_file = match target_path(target_file, sidecar, false) {
+                        Ok(target_file) => target_file,
+                        Err(e) => {
+                            eprintln!("Failed to open sidecar: {}", e);
+                            return exit_code(&e);
+                        }
+                    };
+                    return remove_matching(
+                        &target_file,
+                        &selector,
+                        new_file_path.as_deref(),
+                        dry_run,
+                        quiet,
+                    );
+                }
+                Err(e) => {
+                    eprintln!("Failed to remove resource: {}", e);
+                    return exit_code(&e);
+                }
+            };
+            let target_file = match target_path(target_file, sidecar, false) {
+                Ok(target_file) => target_file,
+                Err(e) => {
+                    eprintln!("Failed to open sidecar: {}", e);
+                    return exit_code(&e);
+                }
+            };
+            status!(
+                quiet,
                 "Removing resource (ID: {}) from \"{}\"...",
                 id,
                 target_file.display()
             );
-            match remove_resource(&target_file, &id, new_file_path.as_deref()) {
+            if dry_run {
+                let result = {
+                    let mut display = ProgressDisplay::new(quiet);
+                    plan_remove(&target_file, &id, new_file_path.as_deref(), &mut |p| {
+                        display.update(p)
+                    })
+                };
+                return report_dry_run(&target_file, result, "remove resource");
+            }
+            let result = {
+                let mut display = ProgressDisplay::new(quiet);
+                remove_resource(&target_file, &id, new_file_path.as_deref(), &mut |p| {
+                    display.update(p)
+                })
+            };
+            match result {
                 Ok(()) => {
-                    println!("Resource removed successfully");
+                    status!(quiet, "Resource removed successfully");
                     ExitCode::SUCCESS
                 }
                 Err(e) => {
                     eprintln!("Failed to remove resource: {}", e);
-                    ExitCode::FAILURE
+                    exit_code(&e)
+                }
+            }
+        }
+        // 资源详细信息
+        Commands::Info {
+            target_file,
+            id,
+            sidecar,
+        } => {
+            let target_file = match target_path(target_file, sidecar, false) {
+                Ok(target_file) => target_file,
+                Err(e) => {
+                    eprintln!("Failed to open sidecar: {}", e);
+                    return exit_code(&e);
+                }
+            };
+            status!(
+                quiet,
+                "Inspecting resources in \"{}\":",
+                target_file.display()
+            );
+            let result = {
+                let mut display = ProgressDisplay::new(quiet);
+                inspect_resources(&target_file, &mut |p| display.update(p))
+            };
+            let infos = match result {
+                Ok(infos) => infos,
+                Err(e) => {
+                    eprintln!("Failed to inspect resources: {}", e);
+                    return exit_code(&e);
+                }
+            };
+            let file_size = std::fs::metadata(&target_file)
+                .map(|m| m.len())
+                .unwrap_or(0);
+
+            let matched: Vec<_> = infos
+                .iter()
+                .filter(|i| id.as_ref().is_none_or(|id| i.head.id().trim() == id.trim()))
+                .collect();
+            if let Some(id) = &id
+                && matched.is_empty()
+            {
+                let e = Error::NotFound(id.trim().to_string());
+                eprintln!("Failed to inspect resources: {}", e);
+                return exit_code(&e);
+            }
+            for info in matched {
+                let head = &info.head;
+                println!("  ID: {}", head.id().trim());
+                println!("    Name:            {}", head.name().trim());
+                println!("    Format version:  {}", head.version());
+                println!("    Header offset:   {} (0x{:X})", info.offset, info.offset);
+                println!("    Header length:   {} bytes", info.header_len);
+                println!(
+                    "    Payload offset:  {} (0x{:X})",
+                    info.data_offset, info.data_offset
+                );
+                println!("    Payload length:  {} bytes", info.length);
+                println!(
+                    "    Decoded size:    {} bytes",
+                    head.size().trim().parse::<u64>().unwrap_or(0)
+                );
+                println!(
+                    "    Compression:     {}",
+                    match (head.compress(), info.ratio()) {
+                        (CompressMode::Compress, Some(ratio)) =>
+                            format!("Yes ({:.1}% of original)", ratio * 100.0),
+                        (CompressMode::Compress, None) => "Yes".to_string(),
+                        (CompressMode::None, _) => "No".to_string(),
+                    }
+                );
+                println!(
+                    "    Storage:         {}",
+                    match head.storage() {
+                        Storage::Inline => "Inline",
+                        Storage::Reference => "Reference (shares data with an identical resource)",
+                        Storage::Chunked => "Chunked (payload lists shared chunks)",
+                        Storage::Chunk => "Chunk (shared by chunked resources)",
+                        Storage::Delta => "Delta (payload patches the previous version)",
+                        Storage::Split => "Split (payload continues in volume files)",
+                        Storage::Volume => "Volume (part of a split resource)",
+                        Storage::Binding => "Binding (ties a sidecar file to its host)",
+                    }
+                );
+                let volumes = head.volumes();
+                if head.storage() == Storage::Split && volumes.count > 0 {
+                    let name = |number| {
+                        volume_path(&target_file, number)
+                            .file_name()
+                            .map(|name| name.to_string_lossy().to_string())
+                            .unwrap_or_default()
+                    };
+                    println!(
+                        "    Volumes:         {} ({} - {})",
+                        volumes.count,
+                        name(volumes.first),
+                        name(volumes.first + volumes.count - 1)
+                    );
+                }
+                if head.superseded() {
+                    println!("    Superseded:      Yes (base of a newer version)");
+                }
+                if !head.checksum().is_empty() {
+                    println!("    SHA-256:         {}", head.checksum());
                 }
+                println!(
+                    "    End marker:      {}",
+                    if info.end_marker { "OK" } else { "Missing" }
+                );
             }
+
+            // 宿主大小取最早的完整资源位置
+            let host_size = infos
+                .iter()
+                .filter(|i| i.end_marker)
+                .map(|i| i.offset)
+                .min()
+                .unwrap_or(file_size);
+            println!("Summary:");
+            println!("  File size:    {} bytes", file_size);
+            println!("  Host size:    {} bytes", host_size);
+            // 内部记录(数据块、分卷数据、绑定记录与旧版本)不计为资源
+            let resources = infos.iter().filter(|i| !i.head.is_hidden()).count();
+            println!(
+                "  Overlay size: {} bytes ({} resource(s) in {} record(s))",
+                file_size - host_size,
+                resources,
+                infos.len()
+            );
+            ExitCode::SUCCESS
+        }
+        // 校验资源
+        Commands::Verify {
+            target_file,
+            id,
+            sidecar,
+        } => {
+            let target_file = match target_path(target_file, sidecar, false) {
+                Ok(target_file) => target_file,
+                Err(e) => {
+                    eprintln!("Failed to open sidecar: {}", e);
+                    return exit_code(&e);
+                }
+            };
+            status!(
+                quiet,
+                "Verifying resources in \"{}\":",
+                target_file.display()
+            );
+            let mut display = ProgressDisplay::new(quiet);
+            let ids = match id {
+                Some(id) => vec![id],
+                None => match find_resources_config(&target_file, |_pos, _config| (), &mut |p| {
+                    display.update(p)
+                }) {
+                    Ok(configs) => configs.iter().map(|c| c.id().trim().to_string()).collect(),
+                    Err(e) => {
+                        eprintln!("Failed to verify resources: {}", e);
+                        return exit_code(&e);
+                    }
+                },
+            };
+
+            // 退出码取第一个失败的资源的错误类别
+            let mut failed = 0;
+            let mut code = ExitCode::SUCCESS;
+            for id in &ids {
+                match verify_resource(&target_file, id, &mut |p| display.update(p)) {
+                    Ok(_) => println!("  OK      {}", id),
+                    Err(e) => {
+                        println!("  FAILED  {} ({})", id, e);
+                        if failed == 0 {
+                            code = exit_code(&e);
+                        }
+                        failed += 1;
+                    }
+                }
+            }
+            drop(display);
+
+            if failed > 0 {
+                eprintln!(
+                    "{} of {} resource(s) failed verification",
+                    failed,
+                    ids.len()
+                );
+                code
+            } else {
+                status!(quiet, "Verified {} resource(s)", ids.len());
+                ExitCode::SUCCESS
+            }
+        }
+        // 提供 HTTP 服务
+        Commands::Serve {
+            target_file,
+            bind,
+            sidecar,
+        } => {
+            let target_file = match target_path(target_file, sidecar, false) {
+                Ok(target_file) => target_file,
+                Err(e) => {
+                    eprintln!("Failed to open sidecar: {}", e);
+                    return exit_code(&e);
+                }
+            };
+            let server = match Server::bind(&target_file, &bind) {
+                Ok(server) => server,
+                Err(e) => {
+                    eprintln!("Failed to listen on {}: {}", bind, e);
+                    return exit_code(&e);
+                }
+            };
+            if let Ok(address) = server.local_addr() {
+                status!(
+                    quiet,
+                    "Serving resources from \"{}\" on http://{}/ (Ctrl+C to stop)",
+                    target_file.display(),
+                    address
+                );
+            }
+            match server.run() {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(e) => {
+                    eprintln!("Server stopped: {}", e);
+                    exit_code(&e)
+                }
+            }
+        }
+        // 生成自解压程序
+        Commands::Sfx {
+            output_path,
+            resources,
+            stub,
+            destination,
+            run,
+            compression,
+        } => {
+            let stub = match stub.map_or_else(default_stub_path, Ok) {
+                Ok(stub) if stub.exists() => stub,
+                Ok(stub) => {
+                    eprintln!(
+                        "Self-extracting stub \"{}\" not found (use --stub)",
+                        stub.display()
+                    );
+                    return ExitCode::FAILURE;
+                }
+                Err(e) => {
+                    eprintln!("Failed to locate self-extracting stub: {}", e);
+                    return ExitCode::FAILURE;
+                }
+            };
+            status!(
+                quiet,
+                "Building self-extracting executable \"{}\" with {} resource(s)...",
+                output_path.display(),
+                resources.len()
+            );
+            let options = SfxOptions {
+                destination,
+                run,
+                compression_grade: if compression == 0 {
+                    None
+                } else {
+                    Some(compression)
+                },
+            };
+            let result = {
+                let mut display = ProgressDisplay::new(quiet);
+                build_sfx(&stub, &output_path, &resources, &options, &mut |p| {
+                    display.update(p)
+                })
+            };
+            match result {
+                Ok(_) => {
+                    status!(quiet, "Self-extracting executable built successfully");
+                    ExitCode::SUCCESS
+                }
+                Err(e) => {
+                    eprintln!("Failed to build self-extracting executable: {}", e);
+                    exit_code(&e)
+                }
+            }
+        }
+        // 剥离资源
+        Commands::Strip {
+            target_file,
+            new_file_path,
+        } => {
+            status!(
+                quiet,
+                "Stripping resources from \"{}\"...",
+                target_file.display()
+            );
+            let result = {
+                let mut display = ProgressDisplay::new(quiet);
+                strip_resources(&target_file, new_file_path.as_deref(), &mut |p| {
+                    display.update(p)
+                })
+            };
+            match result {
+                Ok((host_size, configs)) => {
+                    status!(
+                        quiet,
+                        "Stripped {} resource(s), host file is {} bytes",
+                        configs.len(),
+                        host_size
+                    );
+                    ExitCode::SUCCESS
+                }
+                Err(e) => {
+                    eprintln!("Failed to strip resources: {}", e);
+                    exit_code(&e)
+                }
+            }
+        }
+    }
+}
+
+/// 选择的资源
+enum Resources {
+    /// (资源ID, 输出路径)
+    Single(String, Option<PathBuf>),
+    /// (资源选择器, 输出路径)
+    Matching(Selector, Option<PathBuf>),
+}
+
+/// 创建资源选择器
+///
+/// # 参数
+/// - `glob`: 通配符模式(`--match`)
+/// - `regex`: 正则表达式(`--regex`)
+///
+/// # 返回值
+/// - `Ok(Option<Selector>)`: 资源选择器(均未指定时为 None)
+/// - Err(err): 模式无效
+fn selector(glob: Option<String>, regex: Option<String>) -> Result<Option<Selector>, Error> {
+    match (glob, regex) {
+        (Some(glob), _) => Selector::glob(&glob).map(Some),
+        (None, Some(regex)) => Selector::regex(&regex).map(Some),
+        (None, None) => Ok(None),
+    }
+}
+
+/// 解析资源参数
+///
+/// 使用 `--match` 或 `--regex` 时省略资源ID，第一个位置参数即为输出路径。
+///
+/// # 参数
+/// - `command`: 子命令名称
+/// - `glob`: 通配符模式(`--match`)
+/// - `regex`: 正则表达式(`--regex`)
+/// - `id`: 第一个位置参数
+/// - `path`: 第二个位置参数
+///
+/// # 返回值
+/// - Ok(Resources): 选择的资源(参数缺失或多余时输出用法并退出)
+/// - Err(err): 模式无效
+fn resource_args(
+    command: &str,
+    glob: Option<String>,
+    regex: Option<String>,
+    id: Option<String>,
+    path: Option<PathBuf>,
+) -> Result<Resources, Error> {
+    match (selector(glob, regex)?, id, path) {
+        (Some(_), _, Some(path)) => usage_error(
+            command,
+            ErrorKind::ArgumentConflict,
+            &format!(
+                "a resource ID cannot be used with '--match' or '--regex' (found extra argument '{}')",
+                path.display()
+            ),
+        ),
+        (Some(selector), path, None) => Ok(Resources::Matching(selector, path.map(PathBuf::from))),
+        (None, Some(id), path) => Ok(Resources::Single(id, path)),
+        (None, None, _) => missing_argument(command, "<ID>"),
+    }
+}
+
+/// 输出缺少参数的用法错误并退出
+///
+/// # 参数
+/// - `command`: 子命令名称
+/// - `name`: 参数名称
+fn missing_argument(command: &str, name: &str) -> ! {
+    usage_error(
+        command,
+        ErrorKind::MissingRequiredArgument,
+        &format!(
+            "the following required arguments were not provided: {}",
+            name
+        ),
+    )
+}
+
+/// 输出子命令的用法错误并退出(退出码 2)
+///
+/// # 参数
+/// - `command`: 子命令名称
+/// - `kind`: 错误类型
+/// - `message`: 错误信息
+fn usage_error(command: &str, kind: ErrorKind, message: &str) -> ! {
+    let mut cli = Cli::command();
+    cli.build();
+    match cli.find_subcommand_mut(command) {
+        Some(subcommand) => subcommand.error(kind, message).exit(),
+        None => cli.error(kind, message).exit(),
+    }
+}
+
+/// 释放全部匹配的资源到目录
+///
+/// # 参数
+/// - `target_file`: 目标文件路径
+/// - `selector`: 资源选择器
+/// - `output_dir`: 输出目录
+/// - `options`: 释放资源选项
+/// - `quiet`: 是否静默
+///
+/// # 返回值
+/// - ExitCode: 退出码
+fn export_matching(
+    target_file: &Path,
+    selector: &Selector,
+    output_dir: &Path,
+    options: &ExportOptions,
+    quiet: bool,
+) -> ExitCode {
+    status!(
+        quiet,
+        "Exporting resources matching {} from \"{}\" to \"{}\"...",
+        selector,
+        target_file.display(),
+        output_dir.display()
+    );
+    let result = {
+        let mut display = ProgressDisplay::new(quiet);
+        export_resources(target_file, selector, output_dir, options, &mut |p| {
+            display.update(p)
+        })
+    };
+    match result {
+        Ok(exported) => {
+            for (path, output_path) in &exported {
+                status!(quiet, "  {} -> \"{}\"", path, output_path.display());
+            }
+            status!(quiet, "Exported {} resource(s)", exported.len());
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Failed to export resources: {}", e);
+            if matches!(e, Error::InvalidName(_)) {
+                eprintln!("Pass --allow-unsafe-names to use the stored names as-is");
+            }
+            exit_code(&e)
+        }
+    }
+}
+
+/// 删除全部匹配的资源
+///
+/// # 参数
+/// - `target_file`: 目标文件路径
+/// - `selector`: 资源选择器
+/// - `new_file_path`: 新文件路径(可选)
+/// - `dry_run`: 是否只预演
+/// - `quiet`: 是否静默
+///
+/// # 返回值
+/// - ExitCode: 退出码
+fn remove_matching(
+    target_file: &Path,
+    selector: &Selector,
+    new_file_path: Option<&Path>,
+    dry_run: bool,
+    quiet: bool,
+) -> ExitCode {
+    status!(
+        quiet,
+        "Removing resources matching {} from \"{}\"...",
+        selector,
+        target_file.display()
+    );
+    let mut display = ProgressDisplay::new(quiet);
+    if dry_run {
+        let result = plan_remove_resources(target_file, selector, new_file_path, &mut |p| {
+            display.update(p)
+        });
+        drop(display);
+        return report_dry_run(target_file, result, "remove resources");
+    }
+    let result = remove_resources(target_file, selector, new_file_path, &mut |p| {
+        display.update(p)
+    });
+    drop(display);
+    match result {
+        Ok(ids) => {
+            for id in &ids {
+                status!(quiet, "  Removed {}", id);
+            }
+            status!(quiet, "Removed {} resource(s)", ids.len());
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Failed to remove resources: {}", e);
+            exit_code(&e)
+        }
+    }
+}
+
+/// 获取操作的文件（旁路模式下为校验绑定后的旁路文件）
+///
+/// # 参数
+/// - `target_file`: 目标文件路径
+/// - `sidecar`: 是否为旁路模式
+/// - `create`: 旁路文件不存在时是否创建
+///
+/// # 返回值
+/// - Ok(PathBuf): 操作的文件路径
+/// - Err(err): 旁路文件不存在或绑定的不是当前宿主文件
+fn target_path(target_file: PathBuf, sidecar: bool, create: bool) -> Result<PathBuf, Error> {
+    if !sidecar {
+        return Ok(target_file);
+    }
+    if create {
+        create_sidecar(&target_file)
+    } else {
+        open_sidecar(&target_file)
+    }
+}
+
+/// 输出预演结果(计划的修改与文件大小变化)
+///
+/// # 参数
+/// - `target_file`: 目标文件路径
+/// - `result`: 预演结果
+/// - `action`: 操作名称(用于错误信息)
+///
+/// # 返回值
+/// - ExitCode: 操作将会失败时为 FAILURE
+fn report_dry_run(target_file: &Path, result: Result<DryRun, Error>, action: &str) -> ExitCode {
+    let dry_run = match result {
+        Ok(dry_run) => dry_run,
+        Err(e) => {
+            eprintln!("Failed to {}: {}", action, e);
+            return exit_code(&e);
+        }
+    };
+    println!("Dry run, nothing was written. Planned changes:");
+    if dry_run.output_path != target_file {
+        println!(
+            "  Copy \"{}\" to \"{}\" ({} bytes)",
+            target_file.display(),
+            dry_run.output_path.display(),
+            dry_run.original_len
+        );
+    }
+    for change in &dry_run.changes {
+        match change {
+            Change::Append {
+                id,
+                storage,
+                offset,
+                length,
+            } => println!(
+                "  Append  {:?} record (ID: {}) at offset {} (0x{:X}): {} bytes",
+                storage, id, offset, offset, length
+            ),
+            Change::Remove { id, offset, length } => println!(
+                "  Remove  record (ID: {}) at offset {} (0x{:X}): {} bytes, following data moves back",
+                id, offset, offset, length
+            ),
+            Change::Rewrite { id, offset, length } => println!(
+                "  Rewrite header (ID: {}) at offset {} (0x{:X}): {} bytes",
+                id, offset, offset, length
+            ),
+            Change::CreateVolume { path, length } => {
+                println!("  Create  volume \"{}\": {} bytes", path.display(), length)
+            }
+            Change::RemoveVolume { path } => println!("  Delete  volume \"{}\"", path.display()),
+        }
+    }
+    println!(
+        "  File size: {} -> {} bytes ({:+})",
+        dry_run.original_len,
+        dry_run.new_len,
+        dry_run.new_len as i128 - dry_run.original_len as i128
+    );
+    ExitCode::SUCCESS
+}
+
+/// 获取错误类别对应的退出码
+///
+/// | 退出码 | 错误类别 |
+/// |------|------|
+/// | 1 | 其他错误 |
+/// | 2 | 命令行参数错误(由 clap 返回) |
+/// | 3 | 资源不存在 |
+/// | 4 | 资源ID已存在 |
+/// | 5 | 资源ID无效 |
+/// | 6 | 资源文件名无效 |
+/// | 7 | 参数无效或超出限制 |
+/// | 8 | 文件数据损坏 |
+/// | 9 | 资源版本不受支持 |
+/// | 10 | 不支持的操作 |
+/// | 11 | 读写错误 |
+///
+/// # 参数
+/// - `error`: 错误
+///
+/// # 返回值
+/// - ExitCode: 退出码
+fn exit_code(error: &Error) -> ExitCode {
+    ExitCode::from(match error {
+        Error::NotFound(_) => 3,
+        Error::DuplicateId(_) => 4,
+        Error::InvalidId(_) => 5,
+        Error::InvalidName(_) => 6,
+        Error::InvalidInput(_) => 7,
+        Error::Corrupted { .. } => 8,
+        Error::VersionMismatch { .. } => 9,
+        Error::Unsupported(_) => 10,
+        Error::Io(_) => 11,
+        _ => 1,
+    })
+}
+
+/// 输出资源元数据
+///
+/// # 参数
+/// - `metadata`: 资源元数据
+fn print_metadata(metadata: &Metadata) {
+    let mut fields = Vec::new();
+    if let Some(mime) = &metadata.mime {
+        fields.push(format!("MIME: {}", mime));
+    }
+    if let Some(modified) = metadata.modified {
+        fields.push(format!("Modified: {}", format_timestamp(modified)));
+    }
+    if let Some(permissions) = metadata.permissions {
+        fields.push(format!("Mode: {:04o}", permissions));
+    }
+    if !fields.is_empty() {
+        println!("      {}", fields.join(" | "));
+    }
+    if !metadata.tags.is_empty() {
+        let tags: Vec<String> = metadata
+            .tags
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        println!("      Tags: {}", tags.join(", "));
+    }
+}
+
+/// 进度条显示（输出到标准错误，离开作用域时清除）
+struct ProgressDisplay {
+    quiet: bool,
+    current: Option<(Stage, ProgressBar)>,
+}
+
+impl ProgressDisplay {
+    fn new(quiet: bool) -> Self {
+        ProgressDisplay {
+            quiet,
+            current: None,
+        }
+    }
+
+    /// 更新进度，阶段变化时创建新的进度条
+    fn update(&mut self, progress: Progress) {
+        if self.quiet {
+            return;
+        }
+        if self.current.as_ref().map(|(stage, _)| *stage) != Some(progress.stage) {
+            if let Some((_, bar)) = self.current.take() {
+                bar.finish_and_clear();
+            }
+            let bar = match progress.total {
+                Some(total) => ProgressBar::with_draw_target(
+                    Some(total),
+                    ProgressDrawTarget::stderr(),
+                )
+                .with_style(
+                    ProgressStyle::with_template(
+                        "{msg:9} [{bar:40}] {bytes}/{total_bytes} ({binary_bytes_per_sec}, ETA {eta})",
+                    )
+                    .expect("Invalid progress template")
+                    .progress_chars("=> "),
+                ),
+                None => ProgressBar::with_draw_target(None, ProgressDrawTarget::stderr())
+                    .with_style(
+                        ProgressStyle::with_template(
+                            "{msg:9} {spinner} {bytes} ({binary_bytes_per_sec})",
+                        )
+                        .expect("Invalid progress template"),
+                    ),
+            };
+            bar.set_message(match progress.stage {
+                Stage::Scan => "Scanning",
+                Stage::Add => "Adding",
+                Stage::Export => "Exporting",
+                Stage::Rewrite => "Rewriting",
+                Stage::Install => "Installing",
+            });
+            self.current = Some((progress.stage, bar));
+        }
+        if let Some((_, bar)) = &self.current {
+            bar.set_position(progress.processed);
+        }
+    }
+}
+
+impl Drop for ProgressDisplay {
+    fn drop(&mut self) {
+        if let Some((_, bar)) = self.current.take() {
+            bar.finish_and_clear();
         }
     }
 }
@@ -0,0 +1,465 @@
+use crate::core::{
+    all_records, check_head_size, check_id_available, is_same_file, locate_resource,
+    locate_resources, open_source, plan_removals, read_resource_data, record_len, resolve_path,
+    sniff_mime, stream_head, write_resource, AddOptions, AppendTarget, Backend, Payload,
+    ResourceHead, Storage, RESOURCE_MAGIC,
+};
+use crate::delta::{self, DeltaPatch};
+use crate::error::Result;
+use crate::progress::{ProgressCallback, ProgressReader, ProgressTracker, Stage};
+use crate::select::Selector;
+use crate::source::FileSource;
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// 预演结果：操作将对文件做出的字节级修改（不写入任何数据）
+#[derive(Debug, Clone, PartialEq)]
+pub struct DryRun {
+    /// 被修改的文件(输出到新文件时为新文件，其初始内容为目标文件的副本)
+    pub output_path: PathBuf,
+    /// 修改前的文件大小
+    pub original_len: u64,
+    /// 修改后的文件大小
+    pub new_len: u64,
+    /// 修改列表(按执行顺序，位置相对于执行该修改时的文件)
+    pub changes: Vec<Change>,
+}
+
+/// 计划的修改
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    /// 在文件末尾追加资源记录
+    Append {
+        id: String,
+        storage: Storage,
+        offset: u64,
+        length: u64,
+    },
+    /// 删除资源记录(其后的数据前移)
+    Remove {
+        id: String,
+        offset: u64,
+        length: u64,
+    },
+    /// 原地改写资源头(长度不变)
+    Rewrite {
+        id: String,
+        offset: u64,
+        length: u64,
+    },
+    /// 创建分卷文件
+    CreateVolume { path: PathBuf, length: u64 },
+    /// 删除分卷文件
+    RemoveVolume { path: PathBuf },
+}
+
+impl DryRun {
+    /// 创建预演结果
+    ///
+    /// # 参数
+    /// - `target_file_path`: 目标文件路径
+    /// - `output_path`: 输出文件路径(可选，相对路径相对于目标文件所在目录)
+    fn new(target_file_path: &Path, output_path: Option<&Path>) -> Result<Self> {
+        let original_len = fs::metadata(target_file_path)?.len();
+        let output_path = match output_path {
+            Some(path) => resolve_path(target_file_path, path)?,
+            None => target_file_path.to_path_buf(),
+        };
+        Ok(DryRun {
+            output_path,
+            original_len,
+            new_len: original_len,
+            changes: Vec::new(),
+        })
+    }
+
+    /// 记录修改并更新修改后的文件大小
+    fn push(&mut self, change: Change) {
+        match &change {
+            Change::Append { length, .. } => self.new_len += length,
+            Change::Remove { length, .. } => self.new_len -= length,
+            _ => {}
+        }
+        self.changes.push(change);
+    }
+}
+
+/// 预演增加资源
+///
+/// 执行与 [`crate::core::add_resource`] 相同的写入流程，但数据写入只计算长度的空设备，
+/// 不修改目标文件，也不创建分卷文件。
+///
+/// # 参数
+/// - `target_file_path`: 目标文件路径
+/// - `source_file_path`: 资源文件路径
+/// - `id`: 资源ID
+/// - `options`: 增加资源选项
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok(DryRun): 计划的修改
+/// - Err(err): 操作将会失败
+pub fn plan_add(
+    target_file_path: &Path,
+    source_file_path: &Path,
+    id: &str,
+    options: &AddOptions,
+    progress: ProgressCallback,
+) -> Result<DryRun> {
+    check_id_available(target_file_path, id)?;
+    let mut dry_run = DryRun::new(target_file_path, options.output_path.as_deref())?;
+    let (mut reader, head) = open_source(target_file_path, source_file_path, id, options)?;
+    let source_size = reader.metadata()?.len();
+    let records = target_records(target_file_path)?;
+    plan_append(
+        &mut dry_run,
+        &records,
+        Payload::Seekable(&mut reader),
+        Some(source_size),
+        head,
+        options,
+        progress,
+    )?;
+    Ok(dry_run)
+}
+
+/// 预演从数据流增加资源(数据流会被读取完毕)
+///
+/// # 参数
+/// - `target_file_path`: 目标文件路径
+/// - `reader`: 资源数据流
+/// - `id`: 资源ID
+/// - `options`: 增加资源选项
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok(DryRun): 计划的修改
+/// - Err(err): 操作将会失败
+pub fn plan_add_from_reader(
+    target_file_path: &Path,
+    reader: &mut dyn Read,
+    id: &str,
+    options: &AddOptions,
+    progress: ProgressCallback,
+) -> Result<DryRun> {
+    check_id_available(target_file_path, id)?;
+    let mut dry_run = DryRun::new(target_file_path, options.output_path.as_deref())?;
+    let head = stream_head(id, options)?;
+    let records = target_records(target_file_path)?;
+    plan_append(
+        &mut dry_run,
+        &records,
+        Payload::Stream(reader),
+        None,
+        head,
+        options,
+        progress,
+    )?;
+    Ok(dry_run)
+}
+
+/// 预演更新资源
+///
+/// 差异更新时在内存中计算差异补丁，补丁不小于新版本时与 [`crate::core::update_resource`]
+/// 一样改为完整更新。
+///
+/// # 参数
+/// - `target_file_path`: 目标文件路径
+/// - `source_file_path`: 新版本资源文件路径
+/// - `id`: 资源ID
+/// - `options`: 增加资源选项
+/// - `delta`: 是否以差异补丁存储新版本
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok(DryRun): 计划的修改
+/// - Err(err): 操作将会失败
+pub fn plan_update(
+    target_file_path: &Path,
+    source_file_path: &Path,
+    id: &str,
+    options: &AddOptions,
+    delta: bool,
+    progress: ProgressCallback,
+) -> Result<DryRun> {
+    let mut dry_run = DryRun::new(target_file_path, options.output_path.as_deref())?;
+    let options = &AddOptions {
+        output_path: None,
+        ..options.clone()
+    };
+    let output_path = dry_run.output_path.clone();
+
+    let mut source_file = FileSource::open(target_file_path)?;
+    let (base_start, base) = locate_resource(&mut source_file, id, &mut *progress)?;
+    if delta && !base.checksum().is_empty() {
+        let (mut reader, mut head) = open_source(&output_path, source_file_path, id, options)?;
+        let source_size = reader.metadata()?.len();
+        let mut data = Vec::new();
+        ProgressReader::new(
+            &mut reader,
+            ProgressTracker::new(&mut *progress, Stage::Add, Some(source_size)),
+        )
+        .read_to_end(&mut data)?;
+
+        let mut base_data = Vec::new();
+        read_resource_data(
+            &mut source_file,
+            base_start,
+            &base,
+            &mut base_data,
+            &mut |_| {},
+        )?;
+        let patch = bincode::serialize(&DeltaPatch {
+            base_checksum: base.checksum().to_string(),
+            ops: delta::diff(&base_data, &data),
+        })?;
+        if patch.len() < data.len() {
+            sniff_mime(&mut data.as_slice(), &mut head)?;
+            head.set_storage(Storage::Delta);
+            check_head_size(&mut head)?;
+            let mut output = MeasureBackend::default();
+            write_resource(&mut output, &mut patch.as_slice(), &mut head, options)?;
+            dry_run.push(Change::Append {
+                id: head.id().trim().to_string(),
+                storage: Storage::Delta,
+                offset: dry_run.new_len,
+                length: output.len,
+            });
+            dry_run.push(Change::Rewrite {
+                id: base.id().trim().to_string(),
+                offset: (base_start + RESOURCE_MAGIC.len()) as u64,
+                length: base.get_len() as u64,
+            });
+            return Ok(dry_run);
+        }
+    }
+
+    // 完整更新：删除旧版本，再在重写后的文件末尾追加新版本
+    let (mut reader, head) = open_source(&output_path, source_file_path, id, options)?;
+    let source_size = reader.metadata()?.len();
+    let in_place = is_same_file(&output_path, target_file_path)?;
+    let records = plan_removal(
+        &mut dry_run,
+        &mut source_file,
+        &[(base_start, base)],
+        in_place,
+    )?;
+    plan_append(
+        &mut dry_run,
+        &records,
+        Payload::Seekable(&mut reader),
+        Some(source_size),
+        head,
+        options,
+        &mut *progress,
+    )?;
+    Ok(dry_run)
+}
+
+/// 预演删除资源
+///
+/// # 参数
+/// - `target_file_path`: 目标文件路径
+/// - `id`: 资源ID
+/// - `output_path`: 输出文件路径(可选)
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok(DryRun): 计划的修改
+/// - Err(err): 操作将会失败
+pub fn plan_remove(
+    target_file_path: &Path,
+    id: &str,
+    output_path: Option<&Path>,
+    progress: ProgressCallback,
+) -> Result<DryRun> {
+    let mut dry_run = DryRun::new(target_file_path, output_path)?;
+    let mut source_file = FileSource::open(target_file_path)?;
+    let found = locate_resource(&mut source_file, id, progress)?;
+    let in_place = is_same_file(&dry_run.output_path, target_file_path)?;
+    plan_removal(&mut dry_run, &mut source_file, &[found], in_place)?;
+    Ok(dry_run)
+}
+
+/// 预演删除全部匹配的资源
+///
+/// # 参数
+/// - `target_file_path`: 目标文件路径
+/// - `selector`: 资源选择器
+/// - `output_path`: 输出文件路径(可选)
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok(DryRun): 计划的修改
+/// - Err(err): 操作将会失败
+pub fn plan_remove_resources(
+    target_file_path: &Path,
+    selector: &Selector,
+    output_path: Option<&Path>,
+    progress: ProgressCallback,
+) -> Result<DryRun> {
+    let mut dry_run = DryRun::new(target_file_path, output_path)?;
+    let mut source_file = FileSource::open(target_file_path)?;
+    let found = locate_resources(&mut source_file, selector, progress)?;
+    let in_place = is_same_file(&dry_run.output_path, target_file_path)?;
+    plan_removal(&mut dry_run, &mut source_file, &found, in_place)?;
+    Ok(dry_run)
+}
+
+/// 计划追加资源记录
+///
+/// 以只计算长度的后端执行与追加资源相同的写入流程，存储方式与记录长度与实际写入一致。
+///
+/// # 参数
+/// - `dry_run`: 预演结果
+/// - `records`: 追加前文件中保留的资源配置(用于查找已有数据块与相同数据)
+/// - `payload`: 资源数据
+/// - `source_size`: 资源大小(未知时为 None)
+/// - `head`: 资源头
+/// - `options`: 增加资源选项
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err)
+fn plan_append(
+    dry_run: &mut DryRun,
+    records: &[ResourceHead],
+    payload: Payload,
+    source_size: Option<u64>,
+    mut head: ResourceHead,
+    options: &AddOptions,
+    progress: ProgressCallback,
+) -> Result<()> {
+    let output_path = dry_run.output_path.clone();
+    let mut output = MeasureBackend {
+        pos: dry_run.new_len,
+        len: dry_run.new_len,
+    };
+    let mut target = AppendTarget::new(Some(&output_path), &mut output, records).measure_only();
+    target.append(payload, source_size, &mut head, options, progress)?;
+    for change in target.into_changes() {
+        dry_run.push(change);
+    }
+    Ok(())
+}
+
+/// 计划删除资源记录(与删除资源的区间选择一致)
+///
+/// # 参数
+/// - `dry_run`: 预演结果
+/// - `source_file`: 目标文件
+/// - `removed`: (资源起始位置, 资源配置)列表
+/// - `in_place`: 是否在输出文件上原地删除(原地删除时一并删除分卷文件)
+///
+/// # 返回值
+/// - `Ok(Vec<ResourceHead>)`: 删除后文件中保留的资源配置
+/// - Err(err)
+fn plan_removal(
+    dry_run: &mut DryRun,
+    source_file: &mut FileSource,
+    removed: &[(usize, ResourceHead)],
+    in_place: bool,
+) -> Result<Vec<ResourceHead>> {
+    let removal = plan_removals(source_file, Some(&dry_run.output_path), removed)?;
+    let records = all_records(source_file)?;
+    // 从后向前删除，各区间位置不受之前的删除影响
+    for &(start, end) in removal.ranges.iter().rev() {
+        let id = records
+            .iter()
+            .find(|(pos, _)| *pos as u64 == start)
+            .map_or("", |(_, record)| record.id());
+        dry_run.push(Change::Remove {
+            id: id.trim().to_string(),
+            offset: start,
+            length: end - start,
+        });
+    }
+    // 被提升的引用资源改为存储数据并移动到文件末尾
+    for (head, (start, end)) in &removal.promoted {
+        dry_run.push(Change::Append {
+            id: head.id().trim().to_string(),
+            storage: Storage::Inline,
+            offset: dry_run.new_len,
+            length: record_len(head, end - start),
+        });
+    }
+    if in_place {
+        for path in removal.volumes {
+            dry_run.push(Change::RemoveVolume { path });
+        }
+    }
+    let dropped: HashSet<u64> = removal.ranges.iter().map(|(start, _)| *start).collect();
+    Ok(records
+        .into_iter()
+        .filter(|(start, _)| !dropped.contains(&(*start as u64)))
+        .map(|(_, config)| config)
+        .chain(removal.promoted.into_iter().map(|(head, _)| head))
+        .collect())
+}
+
+/// 获取目标文件中的资源配置
+///
+/// # 参数
+/// - `target_file_path`: 目标文件路径
+///
+/// # 返回值
+/// - `Ok(Vec<ResourceHead>)`: 资源配置列表
+/// - Err(err)
+fn target_records(target_file_path: &Path) -> Result<Vec<ResourceHead>> {
+    let records = all_records(&mut FileSource::open(target_file_path)?)?;
+    Ok(records.into_iter().map(|(_, config)| config).collect())
+}
+
+/// 只计算长度的读写后端（丢弃写入的数据，读取时没有数据）
+#[derive(Default)]
+struct MeasureBackend {
+    /// 当前位置
+    pos: u64,
+    /// 数据长度
+    len: u64,
+}
+
+impl Read for MeasureBackend {
+    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        Ok(0)
+    }
+}
+
+impl Write for MeasureBackend {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pos += buf.len() as u64;
+        self.len = self.len.max(self.pos);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for MeasureBackend {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.pos.checked_add_signed(offset),
+        };
+        self.pos = pos.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative position",
+            )
+        })?;
+        Ok(self.pos)
+    }
+}
+
+impl Backend for MeasureBackend {
+    fn set_len(&mut self, len: u64) -> io::Result<()> {
+        self.len = len;
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+use std::io::Read;
+
+/// 报告进度的最小间隔（256KB）
+const REPORT_INTERVAL: u64 = 1024 * 256;
+
+/// 操作阶段
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Stage {
+    /// 扫描资源
+    Scan,
+    /// 写入资源
+    Add,
+    /// 导出资源
+    Export,
+    /// 重写文件
+    Rewrite,
+    /// 安装资源
+    Install,
+}
+
+/// 进度信息
+#[derive(Debug, Clone, Copy)]
+pub struct Progress {
+    /// 当前阶段
+    pub stage: Stage,
+    /// 已处理字节数
+    pub processed: u64,
+    /// 总字节数（未知时为 None，如标准输入）
+    pub total: Option<u64>,
+}
+
+/// 进度回调
+pub type ProgressCallback<'a> = &'a mut dyn FnMut(Progress);
+
+/// 按间隔报告进度的计数器
+pub(crate) struct ProgressTracker<'a> {
+    callback: ProgressCallback<'a>,
+    stage: Stage,
+    total: Option<u64>,
+    processed: u64,
+    reported: u64,
+}
+
+impl<'a> ProgressTracker<'a> {
+    pub(crate) fn new(callback: ProgressCallback<'a>, stage: Stage, total: Option<u64>) -> Self {
+        (callback)(Progress {
+            stage,
+            processed: 0,
+            total,
+        });
+        ProgressTracker {
+            callback,
+            stage,
+            total,
+            processed: 0,
+            reported: 0,
+        }
+    }
+
+    /// 增加已处理字节数
+    pub(crate) fn advance(&mut self, bytes: u64) {
+        self.set(self.processed + bytes);
+    }
+
+    /// 设置已处理字节数
+    pub(crate) fn set(&mut self, processed: u64) {
+        self.processed = processed;
+        if self.processed.saturating_sub(self.reported) >= REPORT_INTERVAL {
+            self.report();
+        }
+    }
+
+    /// 立即报告当前进度
+    pub(crate) fn report(&mut self) {
+        self.reported = self.processed;
+        (self.callback)(Progress {
+            stage: self.stage,
+            processed: self.processed,
+            total: self.total,
+        });
+    }
+}
+
+/// 报告读取进度的数据流
+pub(crate) struct ProgressReader<'a, R> {
+    inner: R,
+    tracker: ProgressTracker<'a>,
+}
+
+impl<'a, R: Read> ProgressReader<'a, R> {
+    pub(crate) fn new(inner: R, tracker: ProgressTracker<'a>) -> Self {
+        ProgressReader { inner, tracker }
+    }
+}
+
+impl<R: Read> Read for ProgressReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if n == 0 {
+            // 数据读取完毕
+            self.tracker.report();
+        } else {
+            self.tracker.advance(n as u64);
+        }
+        Ok(n)
+    }
+}
@@ -0,0 +1,232 @@
+use crate::core::NESTED_SEPARATOR;
+use crate::error::{Error, Result};
+use regex::Regex;
+use std::fmt;
+
+/// 资源选择器（按资源ID或嵌套资源路径选择多个资源）
+///
+/// ```
+/// use appender::select::Selector;
+///
+/// let selector = Selector::glob("lang/*.json")?;
+/// assert!(selector.matches("lang/en.json"));
+/// assert!(!selector.matches("lang/en/extra.json"));
+/// # Ok::<(), appender::error::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub enum Selector {
+    /// 完全匹配
+    Exact(String),
+    /// 通配符
+    Glob(Glob),
+    /// 正则表达式(部分匹配，需要完全匹配时使用 `^...$`)
+    Regex(Regex),
+}
+
+/// 通配符模式
+///
+/// - `*`: 任意个不含 `/` 的字符
+/// - `**`: 任意个字符(可跨越嵌套层级)
+/// - `?`: 一个不是 `/` 的字符
+/// - `[abc]`、`[a-z]`、`[!a-z]`: 字符集合(`!` 表示取反)
+/// - `\x`: 字符 `x` 本身
+#[derive(Debug, Clone)]
+pub struct Glob {
+    /// 原始模式
+    pattern: String,
+    /// 解析后的模式
+    tokens: Vec<Token>,
+}
+
+/// 通配符模式单元
+#[derive(Debug, Clone)]
+enum Token {
+    /// 字符本身
+    Literal(char),
+    /// `?`
+    Any,
+    /// `*`
+    Star,
+    /// `**`
+    DoubleStar,
+    /// `[...]`(是否取反, 字符范围列表)
+    Class(bool, Vec<(char, char)>),
+}
+
+impl Selector {
+    /// 创建通配符选择器
+    ///
+    /// # 参数
+    /// - `pattern`: 通配符模式
+    ///
+    /// # 返回值
+    /// - Ok(Selector): 选择器
+    /// - Err(err): 模式无效(如字符集合未闭合)
+    pub fn glob(pattern: &str) -> Result<Self> {
+        Ok(Selector::Glob(Glob::new(pattern)?))
+    }
+
+    /// 创建正则表达式选择器
+    ///
+    /// # 参数
+    /// - `pattern`: 正则表达式
+    ///
+    /// # 返回值
+    /// - Ok(Selector): 选择器
+    /// - Err(err): 正则表达式无效
+    pub fn regex(pattern: &str) -> Result<Self> {
+        Regex::new(pattern)
+            .map(Selector::Regex)
+            .map_err(|e| Error::InvalidInput(format!("Invalid regular expression: {}", e)))
+    }
+
+    /// 是否选择该资源
+    ///
+    /// # 参数
+    /// - `path`: 资源ID(嵌套资源为 `外层ID/内层ID`)
+    pub fn matches(&self, path: &str) -> bool {
+        match self {
+            Selector::Exact(id) => id.trim() == path.trim(),
+            Selector::Glob(glob) => glob.matches(path.trim()),
+            Selector::Regex(regex) => regex.is_match(path.trim()),
+        }
+    }
+
+    /// 是否可能选择嵌套资源(模式中包含 `/` 时需要查找嵌套资源)
+    pub fn nested(&self) -> bool {
+        match self {
+            Selector::Exact(id) => id.contains(NESTED_SEPARATOR),
+            Selector::Glob(glob) => glob.pattern.contains(NESTED_SEPARATOR),
+            Selector::Regex(regex) => regex.as_str().contains(NESTED_SEPARATOR),
+        }
+    }
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Selector::Exact(id) => write!(f, "{}", id.trim()),
+            Selector::Glob(glob) => write!(f, "{}", glob.pattern),
+            Selector::Regex(regex) => write!(f, "/{}/", regex.as_str()),
+        }
+    }
+}
+
+impl Glob {
+    /// 解析通配符模式
+    ///
+    /// # 参数
+    /// - `pattern`: 通配符模式
+    ///
+    /// # 返回值
+    /// - Ok(Glob): 通配符模式
+    /// - Err(err): 模式无效
+    pub fn new(pattern: &str) -> Result<Self> {
+        let invalid = |reason: &str| {
+            Err(Error::InvalidInput(format!(
+                "Invalid pattern {:?}: {}",
+                pattern, reason
+            )))
+        };
+        let mut tokens = Vec::new();
+        let mut chars = pattern.chars().peekable();
+        while let Some(c) = chars.next() {
+            tokens.push(match c {
+                '?' => Token::Any,
+                '*' if chars.next_if_eq(&'*').is_some() => {
+                    // 连续的 `*` 等同于 `**`
+                    while chars.next_if_eq(&'*').is_some() {}
+                    Token::DoubleStar
+                }
+                '*' => Token::Star,
+                '\\' => match chars.next() {
+                    Some(c) => Token::Literal(c),
+                    None => return invalid("trailing escape character"),
+                },
+                '[' => {
+                    let negated = chars.next_if(|&c| c == '!' || c == '^').is_some();
+                    let mut ranges = Vec::new();
+                    loop {
+                        let start = match chars.next() {
+                            // 紧跟 `[` 的 `]` 作为普通字符
+                            Some(']') if !ranges.is_empty() => break,
+                            Some('\\') => chars.next(),
+                            c => c,
+                        };
+                        let Some(start) = start else {
+                            return invalid("unclosed character class");
+                        };
+                        let end = match chars.peek() {
+                            Some('-') => {
+                                chars.next();
+                                match chars.next() {
+                                    // `-` 位于末尾时作为普通字符
+                                    Some(']') => {
+                                        ranges.push((start, start));
+                                        ranges.push(('-', '-'));
+                                        break;
+                                    }
+                                    Some('\\') => chars.next(),
+                                    c => c,
+                                }
+                            }
+                            _ => Some(start),
+                        };
+                        let Some(end) = end else {
+                            return invalid("unclosed character class");
+                        };
+                        if end < start {
+                            return invalid("character range is out of order");
+                        }
+                        ranges.push((start, end));
+                    }
+                    Token::Class(negated, ranges)
+                }
+                c => Token::Literal(c),
+            });
+        }
+        Ok(Glob {
+            pattern: pattern.to_string(),
+            tokens,
+        })
+    }
+
+    /// 是否匹配(需匹配整个路径)
+    ///
+    /// # 参数
+    /// - `path`: 资源路径
+    pub fn matches(&self, path: &str) -> bool {
+        let path: Vec<char> = path.chars().collect();
+        // matched[j]: 已处理的模式能否恰好匹配 path[..j]
+        let mut matched = vec![false; path.len() + 1];
+        matched[0] = true;
+        for token in &self.tokens {
+            let mut next = vec![false; path.len() + 1];
+            for j in 0..=path.len() {
+                next[j] = match token {
+                    // 在上一位置可匹配，或继续吞入当前字符
+                    Token::Star | Token::DoubleStar => {
+                        matched[j] || (j > 0 && next[j - 1] && token.accepts(path[j - 1]))
+                    }
+                    _ => j > 0 && matched[j - 1] && token.accepts(path[j - 1]),
+                };
+            }
+            matched = next;
+        }
+        matched[path.len()]
+    }
+}
+
+impl Token {
+    /// 是否可匹配该字符
+    fn accepts(&self, c: char) -> bool {
+        match self {
+            Token::Literal(l) => c == *l,
+            Token::Any | Token::Star => c != NESTED_SEPARATOR,
+            Token::DoubleStar => true,
+            Token::Class(negated, ranges) => {
+                ranges.iter().any(|&(start, end)| start <= c && c <= end) != *negated
+            }
+        }
+    }
+}
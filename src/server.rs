@@ -0,0 +1,565 @@
+use crate::core::{
+    find_resources_config, locate_nested_resource, read_resource_data, ResourceHead, Storage,
+};
+use crate::error::{Error, Result};
+use crate::source::FileSource;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// 资源下载路径前缀
+const RESOURCE_PREFIX: &str = "/resources/";
+
+/// 请求头最大行数
+const MAX_HEADER_LINES: usize = 100;
+
+/// 请求行与请求头每行的最大长度（8KB）
+const MAX_LINE_LENGTH: u64 = 8192;
+
+/// 同时处理的最大连接数(超出时直接关闭新连接)
+const MAX_CONNECTIONS: usize = 64;
+
+/// 连接读写超时
+const IO_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 只读 HTTP 服务
+///
+/// - `GET /`: 资源列表(HTML；请求头 `Accept: application/json` 时为 JSON)
+/// - `GET /index.json`: 资源列表(JSON)
+/// - `GET /resources/<资源ID>`: 下载解码后的资源数据(支持 `Range` 与 `ETag`)
+pub struct Server {
+    listener: TcpListener,
+    target_file_path: PathBuf,
+}
+
+/// 资源列表项(JSON)
+#[derive(Serialize)]
+struct IndexEntry<'a> {
+    id: &'a str,
+    name: &'a str,
+    size: u64,
+    compressed: bool,
+    storage: Storage,
+    checksum: &'a str,
+    mime: Option<&'a str>,
+    modified: Option<u64>,
+    tags: &'a [(String, String)],
+    url: String,
+}
+
+/// HTTP 请求
+struct Request {
+    method: String,
+    path: String,
+    headers: HashMap<String, String>,
+}
+
+impl Server {
+    /// 绑定监听地址
+    ///
+    /// # 参数
+    /// - `target_file_path`: 目标文件路径
+    /// - `address`: 监听地址(如 `127.0.0.1:8080`，端口为 0 时自动分配)
+    ///
+    /// # 返回值
+    /// - Ok(Server)
+    /// - Err(err)
+    pub fn bind(target_file_path: &Path, address: &str) -> Result<Self> {
+        Ok(Server {
+            listener: TcpListener::bind(address)?,
+            target_file_path: target_file_path.to_path_buf(),
+        })
+    }
+
+    /// 获取实际监听的地址
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// 持续处理请求（每个连接一个线程，不会返回，除非监听失败）
+    ///
+    /// 同时处理的连接数超过上限时直接关闭新连接；每个连接设置读写超时，
+    /// 避免空闲或缓慢的客户端长期占用线程。
+    ///
+    /// # 返回值
+    /// - Err(err)
+    pub fn run(self) -> Result<()> {
+        let active = Arc::new(AtomicUsize::new(0));
+        for stream in self.listener.incoming() {
+            let stream = stream?;
+            if stream.set_read_timeout(Some(IO_TIMEOUT)).is_err()
+                || stream.set_write_timeout(Some(IO_TIMEOUT)).is_err()
+            {
+                continue;
+            }
+            if active.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+                active.fetch_sub(1, Ordering::SeqCst);
+                continue;
+            }
+            let guard = ConnectionGuard(Arc::clone(&active));
+            let target_file_path = self.target_file_path.clone();
+            thread::spawn(move || {
+                let _guard = guard;
+                // 客户端断开等错误只影响当前连接
+                let _ = handle_connection(stream, &target_file_path);
+            });
+        }
+        Ok(())
+    }
+}
+
+/// 连接计数(连接处理结束时减少)
+struct ConnectionGuard(Arc<AtomicUsize>);
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// 处理一个连接(一个请求，响应后关闭连接)
+///
+/// # 参数
+/// - `stream`: 连接
+/// - `target_file_path`: 目标文件路径
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err)
+fn handle_connection(stream: TcpStream, target_file_path: &Path) -> Result<()> {
+    let request = read_request(&mut BufReader::new(&stream))?;
+    let mut writer = BufWriter::new(&stream);
+    let head_only = request.method == "HEAD";
+    if request.method != "GET" && !head_only {
+        return write_text(
+            &mut writer,
+            405,
+            "Method Not Allowed",
+            &[("Allow", "GET, HEAD")],
+        );
+    }
+
+    let path = request.path.split('?').next().unwrap_or("");
+    let wants_json = request.path.ends_with("?format=json")
+        || request
+            .headers
+            .get("accept")
+            .is_some_and(|accept| accept.contains("application/json"));
+    match path {
+        "/" if wants_json => write_index_json(&mut writer, target_file_path, head_only),
+        "/" => write_index_html(&mut writer, target_file_path, head_only),
+        "/index.json" => write_index_json(&mut writer, target_file_path, head_only),
+        _ => match path.strip_prefix(RESOURCE_PREFIX).and_then(percent_decode) {
+            Some(id) if !id.is_empty() => {
+                write_resource(&mut writer, target_file_path, &id, &request, head_only)
+            }
+            _ => write_text(&mut writer, 404, "Not Found", &[]),
+        },
+    }?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// 读取请求行与请求头
+///
+/// # 参数
+/// - `reader`: 连接读取流
+///
+/// # 返回值
+/// - Ok(Request)
+/// - Err(err): 请求格式错误
+fn read_request(reader: &mut impl BufRead) -> Result<Request> {
+    let mut line = String::new();
+    read_line(reader, &mut line)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return Err(Error::InvalidInput("Malformed request line".to_string()));
+    };
+    let (method, path) = (method.to_string(), path.to_string());
+
+    let mut headers = HashMap::new();
+    for _ in 0..MAX_HEADER_LINES {
+        line.clear();
+        if read_line(reader, &mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+    Ok(Request {
+        method,
+        path,
+        headers,
+    })
+}
+
+/// 读取一行(限制长度)
+///
+/// # 参数
+/// - `reader`: 连接读取流
+/// - `line`: 读取的文本(追加)
+///
+/// # 返回值
+/// - Ok(usize): 读取的字节数(0 表示连接已关闭)
+/// - Err(err): 行超过长度上限
+fn read_line(reader: &mut impl BufRead, line: &mut String) -> Result<usize> {
+    let n = (&mut *reader).take(MAX_LINE_LENGTH).read_line(line)?;
+    if n as u64 == MAX_LINE_LENGTH && !line.ends_with('\n') {
+        return Err(Error::InvalidInput("Request line is too long".to_string()));
+    }
+    Ok(n)
+}
+
+/// 输出资源列表(HTML)
+fn write_index_html(
+    writer: &mut dyn Write,
+    target_file_path: &Path,
+    head_only: bool,
+) -> Result<()> {
+    let configs = find_resources_config(target_file_path, |_, _| {}, &mut |_| {})?;
+    let mut body = String::from(
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>Resources</title></head>\n<body>\n",
+    );
+    body.push_str(&format!(
+        "<h1>{}</h1>\n<table>\n<tr><th>ID</th><th>Name</th><th>Size</th><th>Type</th><th>SHA-256</th></tr>\n",
+        html_escape(&target_file_path.file_name().unwrap_or_default().to_string_lossy())
+    ));
+    for config in &configs {
+        body.push_str(&format!(
+            "<tr><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td><td>{}</td><td><code>{}</code></td></tr>\n",
+            resource_url(config.id().trim()),
+            html_escape(config.id().trim()),
+            html_escape(config.name().trim()),
+            decoded_size(config),
+            html_escape(config.metadata().mime.as_deref().unwrap_or("")),
+            config.checksum()
+        ));
+    }
+    body.push_str("</table>\n</body>\n</html>\n");
+    write_response(
+        writer,
+        200,
+        "OK",
+        &[("Content-Type", "text/html; charset=utf-8")],
+        body.as_bytes(),
+        head_only,
+    )
+}
+
+/// 输出资源列表(JSON)
+fn write_index_json(
+    writer: &mut dyn Write,
+    target_file_path: &Path,
+    head_only: bool,
+) -> Result<()> {
+    let configs = find_resources_config(target_file_path, |_, _| {}, &mut |_| {})?;
+    let entries: Vec<IndexEntry> = configs
+        .iter()
+        .map(|config| IndexEntry {
+            id: config.id().trim(),
+            name: config.name().trim(),
+            size: decoded_size(config),
+            compressed: config.compress() == crate::core::CompressMode::Compress,
+            storage: config.storage(),
+            checksum: config.checksum(),
+            mime: config.metadata().mime.as_deref(),
+            modified: config.metadata().modified,
+            tags: &config.metadata().tags,
+            url: resource_url(config.id().trim()),
+        })
+        .collect();
+    let body = serde_json::to_vec_pretty(&entries).map_err(io::Error::from)?;
+    write_response(
+        writer,
+        200,
+        "OK",
+        &[("Content-Type", "application/json")],
+        &body,
+        head_only,
+    )
+}
+
+/// 输出资源数据（支持单个 `Range` 区间与 `If-None-Match`/`If-Range`）
+///
+/// # 参数
+/// - `writer`: 响应输出流
+/// - `target_file_path`: 目标文件路径
+/// - `id`: 资源ID(支持嵌套资源路径)
+/// - `request`: 请求
+/// - `head_only`: 是否只输出响应头
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err)
+fn write_resource(
+    writer: &mut dyn Write,
+    target_file_path: &Path,
+    id: &str,
+    request: &Request,
+    head_only: bool,
+) -> Result<()> {
+    // 只定位请求的资源(嵌套资源只解码路径上的外层资源)
+    let source_file = FileSource::open(target_file_path)?;
+    let (mut source_file, resource_start, config) =
+        match locate_nested_resource(source_file, id, &mut |_| {}) {
+            Ok(found) => found,
+            Err(Error::NotFound(_)) => return write_text(writer, 404, "Not Found", &[]),
+            Err(e) => return Err(e),
+        };
+    let size = decoded_size(&config);
+    let etag = format!("\"{}\"", config.checksum());
+    let has_etag = !config.checksum().is_empty();
+
+    // 内容未改变时不返回数据
+    if has_etag && request.headers.get("if-none-match") == Some(&etag) {
+        return write_response(writer, 304, "Not Modified", &[("ETag", &etag)], &[], true);
+    }
+
+    // 解析区间(If-Range 与 ETag 不一致时返回完整数据)
+    let range_applies = request
+        .headers
+        .get("if-range")
+        .is_none_or(|value| has_etag && *value == etag);
+    let range = match request.headers.get("range") {
+        Some(value) if range_applies => match parse_range(value, size) {
+            Some(range) => range,
+            None => {
+                let content_range = format!("bytes */{}", size);
+                return write_text(
+                    writer,
+                    416,
+                    "Range Not Satisfiable",
+                    &[("Content-Range", &content_range)],
+                );
+            }
+        },
+        _ => None,
+    };
+
+    let mime = config
+        .metadata()
+        .mime
+        .clone()
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+    let disposition = format!(
+        "attachment; filename=\"{}\"",
+        config.name().trim().replace(['"', '\\', '\r', '\n'], "_")
+    );
+    let (status, reason, start, length) = match range {
+        Some((start, end)) => (206, "Partial Content", start, end - start + 1),
+        None => (200, "OK", 0, size),
+    };
+    let content_length = length.to_string();
+    let mut headers = vec![
+        ("Content-Type", mime.as_str()),
+        ("Content-Length", content_length.as_str()),
+        ("Content-Disposition", disposition.as_str()),
+        ("Accept-Ranges", "bytes"),
+    ];
+    if has_etag {
+        headers.push(("ETag", &etag));
+    }
+    let content_range = range.map(|(start, end)| format!("bytes {}-{}/{}", start, end, size));
+    if let Some(content_range) = &content_range {
+        headers.push(("Content-Range", content_range));
+    }
+    write_head(writer, status, reason, &headers)?;
+    if head_only {
+        return Ok(());
+    }
+
+    // 解码到请求区间结束为止，只输出请求的区间
+    let mut output = RangeWriter {
+        inner: writer,
+        skip: start,
+        remaining: length,
+    };
+    match read_resource_data(
+        &mut source_file,
+        resource_start,
+        &config,
+        &mut output,
+        &mut |_| {},
+    ) {
+        Err(Error::Io(e)) if e.get_ref().is_some_and(|e| e.is::<RangeComplete>()) => Ok(()),
+        result => result,
+    }
+}
+
+/// 只输出指定区间的数据流
+struct RangeWriter<'a> {
+    inner: &'a mut dyn Write,
+    /// 尚需跳过的字节数
+    skip: u64,
+    /// 尚需输出的字节数
+    remaining: u64,
+}
+
+/// 请求区间已输出完毕(用于提前结束解码)
+#[derive(Debug)]
+struct RangeComplete;
+
+impl fmt::Display for RangeComplete {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Requested range written")
+    }
+}
+
+impl std::error::Error for RangeComplete {}
+
+impl Write for RangeWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.remaining == 0 && !buf.is_empty() {
+            return Err(io::Error::other(RangeComplete));
+        }
+        let skipped = self.skip.min(buf.len() as u64) as usize;
+        self.skip -= skipped as u64;
+        let data = &buf[skipped..];
+        let n = self.remaining.min(data.len() as u64) as usize;
+        self.inner.write_all(&data[..n])?;
+        self.remaining -= n as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// 解析 `Range` 请求头(只支持单个区间)
+///
+/// # 参数
+/// - `value`: 请求头值(如 `bytes=0-99`、`bytes=100-`、`bytes=-100`)
+/// - `size`: 数据大小
+///
+/// # 返回值
+/// - `Some(Some((u64, u64)))`: (起始位置, 结束位置)，包含结束位置
+/// - `Some(None)`: 不支持的格式(如多个区间)，返回完整数据
+/// - None: 区间无法满足
+fn parse_range(value: &str, size: u64) -> Option<Option<(u64, u64)>> {
+    let Some(spec) = value.trim().strip_prefix("bytes=") else {
+        return Some(None);
+    };
+    if spec.contains(',') {
+        return Some(None);
+    }
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", suffix) => {
+            let suffix = suffix.parse::<u64>().ok()?;
+            if suffix == 0 {
+                return None;
+            }
+            (size.saturating_sub(suffix), size.checked_sub(1)?)
+        }
+        (start, "") => (start.parse().ok()?, size.checked_sub(1)?),
+        (start, end) => (
+            start.parse().ok()?,
+            end.parse::<u64>().ok()?.min(size.checked_sub(1)?),
+        ),
+    };
+    (start <= end && start < size).then_some(Some((start, end)))
+}
+
+/// 输出状态行与响应头
+fn write_head(
+    writer: &mut dyn Write,
+    status: u16,
+    reason: &str,
+    headers: &[(&str, &str)],
+) -> Result<()> {
+    write!(writer, "HTTP/1.1 {} {}\r\n", status, reason)?;
+    for (name, value) in headers {
+        write!(writer, "{}: {}\r\n", name, value)?;
+    }
+    write!(writer, "Connection: close\r\n\r\n")?;
+    Ok(())
+}
+
+/// 输出完整响应
+fn write_response(
+    writer: &mut dyn Write,
+    status: u16,
+    reason: &str,
+    headers: &[(&str, &str)],
+    body: &[u8],
+    head_only: bool,
+) -> Result<()> {
+    let content_length = body.len().to_string();
+    let mut all_headers = headers.to_vec();
+    all_headers.push(("Content-Length", &content_length));
+    write_head(writer, status, reason, &all_headers)?;
+    if !head_only {
+        writer.write_all(body)?;
+    }
+    Ok(())
+}
+
+/// 输出纯文本响应(状态说明)
+fn write_text(
+    writer: &mut dyn Write,
+    status: u16,
+    reason: &str,
+    headers: &[(&str, &str)],
+) -> Result<()> {
+    let mut all_headers = headers.to_vec();
+    all_headers.push(("Content-Type", "text/plain; charset=utf-8"));
+    let body = format!("{} {}\n", status, reason);
+    write_response(writer, status, reason, &all_headers, body.as_bytes(), false)
+}
+
+/// 获取资源解码后的大小
+fn decoded_size(config: &ResourceHead) -> u64 {
+    config.size().trim().parse().unwrap_or(0)
+}
+
+/// 获取资源下载地址(资源ID按 URL 规则编码，保留嵌套路径分隔符)
+fn resource_url(id: &str) -> String {
+    let mut url = String::from(RESOURCE_PREFIX);
+    for byte in id.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            url.push(byte as char);
+        } else {
+            url.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    url
+}
+
+/// 解码 URL 编码的路径
+///
+/// # 返回值
+/// - `Some(String)`: 解码后的文本
+/// - None: 编码错误或不是有效的 UTF-8
+fn percent_decode(path: &str) -> Option<String> {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+/// 转义 HTML 特殊字符
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
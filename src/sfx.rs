@@ -0,0 +1,188 @@
+use crate::core::{add_resource, add_resource_from_reader, export_resource_to_writer, AddOptions};
+use crate::error::{Error, Result};
+use crate::installer::Installer;
+use crate::progress::ProgressCallback;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// 自解压配置的资源ID
+pub const SFX_MANIFEST_ID: &str = "sfx.manifest";
+
+/// 自解压程序存根的文件名(不含扩展名)
+pub const SFX_STUB_NAME: &str = "appender-sfx";
+
+/// 自解压配置(以资源形式附加在自解压程序中)
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone)]
+pub struct SfxManifest {
+    /// 默认释放目录(相对于运行时的工作目录)
+    pub destination: String,
+    /// 需要释放的资源ID(按顺序)
+    pub resources: Vec<String>,
+    /// 释放完成后在释放目录中执行的命令(可选)
+    pub run: Option<String>,
+}
+
+/// 自解压程序选项
+#[derive(Debug, Default, Clone)]
+pub struct SfxOptions {
+    /// 默认释放目录(相对于运行时的工作目录)
+    pub destination: String,
+    /// 释放完成后执行的命令(可选)
+    pub run: Option<String>,
+    /// 压缩等级(0-9)，None 为不压缩
+    pub compression_grade: Option<u32>,
+}
+
+/// 获取随程序分发的自解压程序存根路径(与当前程序位于同一目录)
+///
+/// # 返回值
+/// - Ok(PathBuf): 存根路径
+/// - Err(err)
+pub fn default_stub_path() -> Result<PathBuf> {
+    Ok(std::env::current_exe()?.with_file_name(format!(
+        "{}{}",
+        SFX_STUB_NAME,
+        std::env::consts::EXE_SUFFIX
+    )))
+}
+
+/// 生成自解压程序（复制存根并附加资源与自解压配置）
+///
+/// 资源ID取资源文件名，失败时删除不完整的输出文件。
+///
+/// # 参数
+/// - `stub_path`: 存根程序路径
+/// - `output_path`: 输出路径
+/// - `resources`: 资源文件路径列表
+/// - `options`: 自解压程序选项
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok(SfxManifest): 写入的自解压配置
+/// - Err(err)
+pub fn build_sfx(
+    stub_path: &Path,
+    output_path: &Path,
+    resources: &[PathBuf],
+    options: &SfxOptions,
+    progress: ProgressCallback,
+) -> Result<SfxManifest> {
+    // 资源路径相对于当前工作目录(而不是输出文件所在目录)
+    let mut sources = Vec::with_capacity(resources.len());
+    let mut ids = HashSet::new();
+    for resource in resources {
+        let source = fs::canonicalize(resource)?;
+        let id = source
+            .file_name()
+            .ok_or_else(|| Error::InvalidName("Resource file has no valid filename".to_string()))?
+            .to_string_lossy()
+            .to_string();
+        if id == SFX_MANIFEST_ID || !ids.insert(id.clone()) {
+            return Err(Error::DuplicateId(id));
+        }
+        sources.push((source, id));
+    }
+
+    fs::copy(stub_path, output_path)?;
+    let result = (|| -> Result<SfxManifest> {
+        let add_options = AddOptions {
+            compression_grade: options.compression_grade,
+            ..AddOptions::default()
+        };
+        for (source, id) in &sources {
+            add_resource(output_path, source, id, &add_options, &mut *progress)?;
+        }
+
+        let manifest = SfxManifest {
+            destination: options.destination.clone(),
+            resources: sources.into_iter().map(|(_, id)| id).collect(),
+            run: options.run.clone(),
+        };
+        let data = bincode::serialize(&manifest)?;
+        add_resource_from_reader(
+            output_path,
+            &mut data.as_slice(),
+            SFX_MANIFEST_ID,
+            &AddOptions::default(),
+            progress,
+        )?;
+        Ok(manifest)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(output_path);
+    }
+    result
+}
+
+/// 释放自解压程序中的资源（任一资源释放失败时回滚已释放的文件）
+///
+/// # 参数
+/// - `executable_path`: 自解压程序路径(通常为 `std::env::current_exe()`)
+/// - `destination`: 释放目录(None 时使用自解压配置中的默认目录)
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok((PathBuf, SfxManifest)): (释放目录, 自解压配置)
+/// - Err(err)
+pub fn extract_sfx(
+    executable_path: &Path,
+    destination: Option<&Path>,
+    progress: ProgressCallback,
+) -> Result<(PathBuf, SfxManifest)> {
+    let mut data = Vec::new();
+    export_resource_to_writer(executable_path, SFX_MANIFEST_ID, &mut data, &mut |_| {}).map_err(
+        |e| match e {
+            Error::NotFound(_) => {
+                Error::NotFound(format!("self-extracting manifest ({})", SFX_MANIFEST_ID))
+            }
+            e => e,
+        },
+    )?;
+    let manifest: SfxManifest = bincode::deserialize(&data)?;
+
+    // 相对路径相对于当前工作目录
+    let destination = match destination {
+        Some(path) => path.to_path_buf(),
+        None => PathBuf::from(&manifest.destination),
+    };
+    let destination = std::env::current_dir()?.join(destination);
+    let installer = manifest.resources.iter().fold(
+        Installer::new(executable_path, &destination),
+        |installer, id| installer.resource(id),
+    );
+    installer.install(progress)?;
+    Ok((destination, manifest))
+}
+
+/// 在释放目录中执行命令(通过系统命令解释器)
+///
+/// # 参数
+/// - `command`: 命令
+/// - `directory`: 工作目录
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err): 无法启动或退出码非零
+pub fn run_post_extract(command: &str, directory: &Path) -> Result<()> {
+    let mut process = if cfg!(windows) {
+        let mut process = Command::new("cmd");
+        process.args(["/C", command]);
+        process
+    } else {
+        let mut process = Command::new("sh");
+        process.args(["-c", command]);
+        process
+    };
+    let status = process.current_dir(directory).status()?;
+    if !status.success() {
+        return Err(Error::Io(io::Error::other(format!(
+            "Post-extract command failed with {}",
+            status
+        ))));
+    }
+    Ok(())
+}
@@ -0,0 +1,133 @@
+use crate::core::{
+    all_records, export_resource_to_writer, write_record, CompressMode, ResourceHead, Storage,
+};
+use crate::error::{Error, Result};
+use crate::progress::ProgressCallback;
+use crate::source::FileSource;
+use crate::util::HashingReader;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// 旁路文件扩展名（`app.exe` 的旁路文件为 `app.exe.overlay`）
+pub const SIDECAR_EXTENSION: &str = "overlay";
+
+/// 获取旁路文件路径
+///
+/// # 参数
+/// - `target_file_path`: 宿主文件路径
+///
+/// # 返回值
+/// - PathBuf: 旁路文件路径
+pub fn sidecar_path(target_file_path: &Path) -> PathBuf {
+    let mut path = target_file_path.as_os_str().to_owned();
+    path.push(".");
+    path.push(SIDECAR_EXTENSION);
+    PathBuf::from(path)
+}
+
+/// 创建旁路文件（已存在时校验绑定）
+///
+/// 旁路文件与附加在宿主文件后的资源格式相同，第一条记录为绑定记录，记录宿主文件的校验和，
+/// 宿主文件本身不会被修改(可先签名再创建旁路文件)。
+///
+/// # 参数
+/// - `target_file_path`: 宿主文件路径
+///
+/// # 返回值
+/// - Ok(PathBuf): 旁路文件路径
+/// - Err(err)
+pub fn create_sidecar(target_file_path: &Path) -> Result<PathBuf> {
+    let path = sidecar_path(target_file_path);
+    if path.exists() {
+        return open_sidecar(target_file_path);
+    }
+
+    let name = target_file_path
+        .file_name()
+        .ok_or_else(|| Error::InvalidName("Target file has no valid filename".to_string()))?
+        .to_string_lossy()
+        .to_string();
+    let mut head = ResourceHead::new("", 0, 0, &name, CompressMode::None)?;
+    head.set_storage(Storage::Binding);
+    head.set_checksum(host_checksum(target_file_path)?);
+
+    let mut writer = BufWriter::new(
+        OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)?,
+    );
+    write_record(&mut writer, &head, &mut io::empty())?;
+    writer.flush()?;
+    Ok(path)
+}
+
+/// 打开旁路文件（校验旁路文件存在且绑定的是当前宿主文件）
+///
+/// # 参数
+/// - `target_file_path`: 宿主文件路径
+///
+/// # 返回值
+/// - Ok(PathBuf): 旁路文件路径
+/// - Err(err): 旁路文件不存在、没有绑定记录或宿主文件已改变
+pub fn open_sidecar(target_file_path: &Path) -> Result<PathBuf> {
+    let path = sidecar_path(target_file_path);
+    if !path.exists() {
+        return Err(Error::NotFound(format!("sidecar file {}", path.display())));
+    }
+    let binding = all_records(&mut FileSource::open(&path)?)?
+        .into_iter()
+        .find(|(_, config)| config.storage() == Storage::Binding)
+        .ok_or_else(|| {
+            Error::corrupted(
+                None,
+                format!("{} is not a sidecar file (binding missing)", path.display()),
+            )
+        })?;
+    if binding.1.checksum() != host_checksum(target_file_path)? {
+        return Err(Error::corrupted(
+            None,
+            format!(
+                "Sidecar file {} is bound to a different host file (host hash mismatch)",
+                path.display()
+            ),
+        ));
+    }
+    Ok(path)
+}
+
+/// 从旁路文件读取资源（校验绑定后输出到数据流）
+///
+/// # 参数
+/// - `target_file_path`: 宿主文件路径(如 `std::env::current_exe()`)
+/// - `id`: 资源ID
+/// - `writer`: 输出数据流
+/// - `progress`: 进度回调
+///
+/// # 返回值
+/// - Ok(ResourceHead): 资源配置
+/// - Err(err)
+pub fn read_sidecar_resource(
+    target_file_path: &Path,
+    id: &str,
+    writer: &mut dyn Write,
+    progress: ProgressCallback,
+) -> Result<ResourceHead> {
+    let path = open_sidecar(target_file_path)?;
+    export_resource_to_writer(&path, id, writer, progress)
+}
+
+/// 计算宿主文件的校验和
+///
+/// # 参数
+/// - `target_file_path`: 宿主文件路径
+///
+/// # 返回值
+/// - Ok(String): SHA-256 校验和(十六进制)
+/// - Err(err)
+fn host_checksum(target_file_path: &Path) -> Result<String> {
+    let mut reader = HashingReader::new(File::open(target_file_path)?);
+    io::copy(&mut reader, &mut io::sink())?;
+    Ok(reader.checksum())
+}
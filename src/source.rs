@@ -0,0 +1,264 @@
+use crate::error::{Error, Result};
+use crate::progress::ProgressTracker;
+use memchr::memmem;
+#[cfg(feature = "mmap")]
+use memmap2::Mmap;
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// 搜索缓冲区大小（512KB）
+const SEARCH_BUFFER_SIZE: usize = 1024 * 512;
+
+/// 目标文件读取后端
+///
+/// 优先使用内存映射，可一次性搜索整个文件并以切片方式读取数据；
+/// 映射失败（或未启用 `mmap` 特性）时回退到分块缓冲读取。
+/// 嵌套容器(资源数据本身携带资源)加载到内存中读取，其他数据流(如内存缓冲区)分块缓冲读取。
+pub(crate) enum FileSource<'a> {
+    /// 内存映射(映射的文件路径)
+    #[cfg(feature = "mmap")]
+    Mapped(Mmap, PathBuf),
+    /// 内存数据
+    Memory(Cow<'a, [u8]>),
+    /// 缓冲读取
+    Buffered(BufferedFile<'a>),
+}
+
+/// 可随机读取的数据流
+pub(crate) trait ReadSeek: Read + Seek {}
+
+impl<T: Read + Seek + ?Sized> ReadSeek for T {}
+
+/// 分块缓冲读取的数据流（缓存最近一次搜索的窗口）
+pub(crate) struct BufferedFile<'a> {
+    file: Box<dyn ReadSeek + 'a>,
+    len: u64,
+    path: Option<PathBuf>,
+    window: Vec<u8>,
+    window_offset: u64,
+}
+
+impl<'a> BufferedFile<'a> {
+    pub(crate) fn new(mut file: impl Read + Seek + 'a) -> Result<Self> {
+        let len = file.seek(SeekFrom::End(0))?;
+        Ok(BufferedFile {
+            file: Box::new(file),
+            len,
+            path: None,
+            window: Vec::new(),
+            window_offset: 0,
+        })
+    }
+}
+
+impl<'a> FileSource<'a> {
+    /// 打开文件
+    ///
+    /// # 参数
+    /// - `path`: 文件路径
+    ///
+    /// # 返回值
+    /// - Ok(FileSource): 读取后端
+    /// - Err(err)
+    pub(crate) fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path)?;
+        #[cfg(feature = "mmap")]
+        {
+            // SAFETY: 映射期间文件不应被其他进程修改，修改目标文件前会先释放映射
+            if let Ok(map) = unsafe { Mmap::map(&file) } {
+                return Ok(FileSource::Mapped(map, path.to_path_buf()));
+            }
+        }
+        let mut buffered = BufferedFile::new(file)?;
+        buffered.path = Some(path.to_path_buf());
+        Ok(FileSource::Buffered(buffered))
+    }
+
+    /// 从数据流读取（如内存缓冲区）
+    ///
+    /// # 参数
+    /// - `reader`: 可随机读取的数据流
+    ///
+    /// # 返回值
+    /// - Ok(FileSource): 读取后端
+    /// - Err(err)
+    pub(crate) fn from_reader(reader: impl Read + Seek + 'a) -> Result<Self> {
+        Ok(FileSource::Buffered(BufferedFile::new(reader)?))
+    }
+
+    /// 获取文件路径（内存数据或未记录路径时为 None）
+    pub(crate) fn path(&self) -> Option<&Path> {
+        match self {
+            #[cfg(feature = "mmap")]
+            FileSource::Mapped(_, path) => Some(path),
+            FileSource::Memory(_) => None,
+            FileSource::Buffered(buffered) => buffered.path.as_deref(),
+        }
+    }
+
+    /// 获取全部数据（缓冲读取时为 None）
+    fn bytes(&self) -> Option<&[u8]> {
+        match self {
+            #[cfg(feature = "mmap")]
+            FileSource::Mapped(map, _) => Some(map),
+            FileSource::Memory(data) => Some(data),
+            FileSource::Buffered(_) => None,
+        }
+    }
+
+    /// 获取文件长度
+    pub(crate) fn len(&self) -> Result<u64> {
+        match self {
+            FileSource::Buffered(buffered) => Ok(buffered.len),
+            source => Ok(source.bytes().map_or(0, |data| data.len()) as u64),
+        }
+    }
+
+    /// 从指定位置向后搜索字节序列
+    ///
+    /// # 参数
+    /// - `pattern`: 字节序列(如魔数)
+    /// - `from`: 搜索起始位置
+    /// - `tracker`: 进度计数器
+    ///
+    /// # 返回值
+    /// - Ok(Some(u64)): 找到的位置
+    /// - Ok(None): 直到文件末尾均未找到
+    /// - Err(err)
+    pub(crate) fn find(
+        &mut self,
+        pattern: &[u8],
+        from: u64,
+        tracker: &mut ProgressTracker,
+    ) -> Result<Option<u64>> {
+        let file_len = self.len()?;
+        match self {
+            FileSource::Buffered(buffered) => {
+                let mut from = from;
+                while from < file_len {
+                    // 当前窗口不足以容纳完整的字节序列时，从搜索位置重新读取窗口
+                    let window_end = buffered.window_offset + buffered.window.len() as u64;
+                    if from < buffered.window_offset
+                        || (from + pattern.len() as u64 > window_end && window_end < file_len)
+                    {
+                        buffered.window.resize(SEARCH_BUFFER_SIZE, 0);
+                        buffered.file.seek(SeekFrom::Start(from))?;
+                        let bytes_read = read_full(&mut buffered.file, &mut buffered.window)?;
+                        buffered.window.truncate(bytes_read);
+                        buffered.window_offset = from;
+                        if bytes_read == 0 {
+                            break;
+                        }
+                    }
+
+                    let start = (from - buffered.window_offset) as usize;
+                    if let Some(relative_pos) = memmem::find(&buffered.window[start..], pattern) {
+                        let pos = from + relative_pos as u64;
+                        tracker.set(pos);
+                        return Ok(Some(pos));
+                    }
+
+                    // 保留可能跨越窗口边界的部分字节序列
+                    let window_end = buffered.window_offset + buffered.window.len() as u64;
+                    if window_end >= file_len {
+                        break;
+                    }
+                    tracker.set(window_end);
+                    from = (window_end + 1)
+                        .saturating_sub(pattern.len() as u64)
+                        .max(from + 1);
+                }
+                tracker.set(file_len);
+                Ok(None)
+            }
+            source => {
+                let found = source
+                    .bytes()
+                    .and_then(|data| data.get(from as usize..))
+                    .and_then(|rest| memmem::find(rest, pattern))
+                    .map(|relative_pos| from + relative_pos as u64);
+                tracker.set(found.unwrap_or(file_len));
+                Ok(found)
+            }
+        }
+    }
+
+    /// 读取指定位置的数据（要求读满缓冲区）
+    ///
+    /// # 参数
+    /// - `offset`: 起始位置
+    /// - `buf`: 缓冲区
+    ///
+    /// # 返回值
+    /// - Ok(())
+    /// - Err(err)
+    pub(crate) fn read_exact_at(&mut self, offset: u64, buf: &mut [u8]) -> Result<()> {
+        match self {
+            FileSource::Buffered(buffered) => {
+                buffered.file.seek(SeekFrom::Start(offset))?;
+                buffered.file.read_exact(buf)?;
+                Ok(())
+            }
+            source => {
+                let start = offset as usize;
+                let data = source
+                    .bytes()
+                    .and_then(|data| data.get(start..start + buf.len()))
+                    .ok_or_else(|| Error::corrupted(offset, "Read beyond end of file"))?;
+                buf.copy_from_slice(data);
+                Ok(())
+            }
+        }
+    }
+
+    /// 获取指定区间的读取流
+    ///
+    /// # 参数
+    /// - `offset`: 起始位置
+    /// - `length`: 区间长度
+    ///
+    /// # 返回值
+    /// - Ok(Box<dyn Read>): 读取流（内存映射或内存数据时直接读取切片）
+    /// - Err(err)
+    pub(crate) fn range_reader(&mut self, offset: u64, length: u64) -> Result<Box<dyn Read + '_>> {
+        if offset + length > self.len()? {
+            return Err(Error::corrupted(offset, "Read beyond end of file"));
+        }
+        match self {
+            FileSource::Buffered(buffered) => {
+                buffered.file.seek(SeekFrom::Start(offset))?;
+                Ok(Box::new(
+                    BufReader::with_capacity(crate::core::BUFFER_SIZE, &mut buffered.file)
+                        .take(length),
+                ))
+            }
+            source => {
+                let data = source.bytes().unwrap_or_default();
+                Ok(Box::new(&data[offset as usize..(offset + length) as usize]))
+            }
+        }
+    }
+}
+
+/// 尽可能读满缓冲区（直到文件末尾）
+///
+/// # 参数
+/// - `reader`: 读取流
+/// - `buf`: 缓冲区
+///
+/// # 返回值
+/// - Ok(usize): 实际读取的字节数
+/// - Err(err)
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> Result<usize> {
+    let mut total = 0;
+    while total < buf.len() {
+        let n = reader.read(&mut buf[total..])?;
+        if n == 0 {
+            break;
+        }
+        total += n;
+    }
+    Ok(total)
+}
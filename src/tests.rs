@@ -1,15 +1,100 @@
+use crate::container::Container;
 use crate::core::{
-    add_resource, export_resource, find_resources_config, remove_resource, CompressMode,
-    ResourceHead,
+    add_resource, add_resource_from_reader, add_resource_from_reader_to_backend,
+    add_resource_to_backend, export_resource, export_resource_from_reader,
+    export_resource_to_writer, export_resources, find_nested_resources, find_resources_config,
+    find_resources_config_from_reader, inspect_resources, inspect_resources_from_reader,
+    remove_resource, remove_resource_from_backend, remove_resources, strip_resources,
+    strip_resources_from_backend, update_resource, update_resource_in_backend, verify_resource,
+    verify_resource_from_reader, volume_path, AddOptions, Backend, CompressMode, ExportOptions,
+    Metadata, ResourceHead, Storage,
 };
+use crate::error::{Error, Result};
+use crate::installer::Installer;
+use crate::plan::{plan_add, plan_remove, plan_remove_resources, plan_update, Change};
+use crate::progress::{Progress, Stage};
+use crate::select::Selector;
+use crate::server::Server;
+use crate::sidecar::{create_sidecar, open_sidecar, read_sidecar_resource, sidecar_path};
+use crate::util::{check_file_name, restore_file_attributes};
 use std::fs;
-use std::io::Write;
-use std::path::PathBuf;
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// 测试用临时目录
+///
+/// 目录名包含进程ID与序号，并行运行的测试互不影响；离开作用域时删除(测试失败时同样清理)。
+struct TestDir(PathBuf);
+
+impl TestDir {
+    fn new(name: &str) -> Self {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "appender_test_{}_{}_{}",
+            name,
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        TestDir(path)
+    }
+}
+
+impl std::ops::Deref for TestDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TestDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// 在内存数据上增加资源并提交
+fn add_in_memory(target: &mut Vec<u8>, data: &[u8], id: &str, options: &AddOptions) -> Result<()> {
+    let mut backend = Cursor::new(target);
+    add_resource_from_reader_to_backend(
+        &mut backend,
+        Cursor::new(data.to_vec()),
+        id,
+        options,
+        &mut |_| {},
+    )
+}
+
+/// 在内存数据上删除资源并提交
+fn remove_in_memory(target: &mut Vec<u8>, id: &str) -> Result<()> {
+    remove_resource_from_backend(&mut Cursor::new(target), id, &mut |_| {})
+}
+
+/// 从内存数据导出资源
+fn export_in_memory(target: &[u8], id: &str) -> Result<Vec<u8>> {
+    let mut exported = Vec::new();
+    export_resource_from_reader(Cursor::new(target), id, &mut exported, &mut |_| {})?;
+    Ok(exported)
+}
+
+/// 列出内存数据中的资源
+fn list_in_memory(target: &[u8]) -> Vec<ResourceHead> {
+    find_resources_config_from_reader(Cursor::new(target), &mut |_| {}).unwrap()
+}
 
 /// 测试 ResourceHead 序列化/反序列化
 #[test]
 fn test_resourcehead_serialization() {
-    let head = ResourceHead::new("test001", 27, 27, "resource.bin", CompressMode::None);
+    let head = ResourceHead::new("test001", 27, 27, "resource.bin", CompressMode::None).unwrap();
     let serialized = head.to_bytes().unwrap();
     let deserialized = ResourceHead::from(&serialized).unwrap();
 
@@ -18,90 +103,2071 @@ fn test_resourcehead_serialization() {
     assert_eq!(head.compress(), deserialized.compress());
 }
 
-/// 诊断测试：创建一个简单的文件，添加资源、查找资源、导出资源、删除资源
+/// 诊断测试：在内存数据上添加资源、查找资源、导出资源、删除资源
 #[test]
 fn diagnostic_test() {
-    // 创建测试目录
-    let test_dir = PathBuf::from(r"D:\Project\back-end\Rust\Appender\test_diag");
-    fs::create_dir_all(&test_dir).unwrap();
-
-    // 创建一个简单的目标文件
-    let target_file = test_dir.join("target.bin");
-    let mut f = fs::File::create(&target_file).unwrap();
-    f.write_all(b"Hello, this is a test file!").unwrap();
-    f.flush().unwrap();
-    drop(f);
-
-    // 验证原始文件大小
-    let original_size = fs::metadata(&target_file).unwrap().len();
+    // 步骤 1: 创建内存中的目标数据
+    println!("=== 步骤 1: 创建数据 ===");
+    let mut target = b"Hello, this is a test file!".to_vec();
+    let original_size = target.len();
     assert_eq!(original_size, 27);
-
-    // 创建资源文件
-    let source_file = test_dir.join("resource.bin");
     let resource_data = b"This is the resource data!";
-    let mut f = fs::File::create(&source_file).unwrap();
-    f.write_all(resource_data).unwrap();
-    f.flush().unwrap();
-    drop(f);
-
-    // 验证资源文件大小
-    let source_size = fs::metadata(&source_file).unwrap().len();
-    assert_eq!(source_size, resource_data.len() as u64);
-
-    println!("=== 步骤 1: 创建文件 ===");
-    println!("  目标文件: {:?}", target_file.file_name());
-    println!("  资源文件: {:?}", source_file.file_name());
-    println!("  目标文件大小: {} 字节", original_size);
+    println!("  目标数据大小: {} 字节", original_size);
     println!("  资源数据大小: {} 字节", resource_data.len());
 
     // 步骤 2: 添加资源
     println!("\n=== 步骤 2: 添加资源 ===");
     let resource_id = "test001";
-    add_resource(&target_file, &source_file, resource_id, None, None).unwrap();
-
-    let size_after_add = fs::metadata(&target_file).unwrap().len();
+    add_in_memory(
+        &mut target,
+        resource_data,
+        resource_id,
+        &AddOptions::default(),
+    )
+    .unwrap();
+    let size_after_add = target.len();
     println!("  ✓ 添加成功 (ID: {})", resource_id);
-    println!("  文件大小: {} -> {} 字节 (+{})", original_size, size_after_add, size_after_add - original_size);
+    println!(
+        "  数据大小: {} -> {} 字节 (+{})",
+        original_size,
+        size_after_add,
+        size_after_add - original_size
+    );
 
     // 步骤 3: 查找资源
     println!("\n=== 步骤 3: 查找资源 ===");
-    let configs = find_resources_config(&target_file, |_pos, config| {
-        println!("  - ID: {}, 名称: {}, 大小: {} 字节",
-            config.id().trim(),
-            config.name().trim(),
-            config.size().trim()
+    let configs = list_in_memory(&target);
+    for config in &configs {
+        println!(
+            "  - ID: {}, 名称: {}, 大小: {} 字节",
+            config.id(),
+            config.name(),
+            config.size()
         );
-    })
-    .unwrap();
+    }
     println!("  ✓ 共找到 {} 个资源", configs.len());
     assert!(!configs.is_empty(), "应该找到至少一个资源");
 
     // 步骤 4: 导出资源
     println!("\n=== 步骤 4: 导出资源 ===");
-    let output_file = test_dir.join("exported.bin");
-    export_resource(&target_file, resource_id, &output_file).unwrap();
-
-    let exported_data = fs::read(&output_file).unwrap();
-    let original_data = fs::read(&source_file).unwrap();
-    assert_eq!(exported_data, original_data);
+    assert_eq!(
+        export_in_memory(&target, resource_id).unwrap(),
+        resource_data
+    );
     println!("  ✓ 导出成功，内容验证通过");
 
     // 步骤 5: 删除资源
     println!("\n=== 步骤 5: 删除资源 ===");
-    let size_before_remove = fs::metadata(&target_file).unwrap().len();
-    remove_resource(&target_file, resource_id, None).unwrap();
+    remove_in_memory(&mut target, resource_id).unwrap();
+    assert_eq!(list_in_memory(&target).len(), 0);
+    assert_eq!(target, b"Hello, this is a test file!");
+    println!("  ✓ 删除成功");
+    println!("  数据大小: {} -> {} 字节", size_after_add, target.len());
+    println!("\n所有测试通过!");
+}
 
-    let size_after_remove = fs::metadata(&target_file).unwrap().len();
-    let configs_after_remove = find_resources_config(&target_file, |_pos, _config| {}).unwrap();
-    assert_eq!(configs_after_remove.len(), 0);
+/// 测试旧版(1.0.0)资源头兼容解析
+#[test]
+fn test_legacy_resourcehead() {
+    #[derive(serde::Serialize)]
+    struct Legacy {
+        version: String,
+        id: String,
+        name: String,
+        length: String,
+        size: String,
+        compress: CompressMode,
+    }
+    let legacy = bincode::serialize(&Legacy {
+        version: "1.0.0".to_string(),
+        id: "old".to_string(),
+        name: "old.bin".to_string(),
+        length: "0000000000000010".to_string(),
+        size: "0000000000000010".to_string(),
+        compress: CompressMode::None,
+    })
+    .unwrap();
 
-    println!("  ✓ 删除成功");
-    println!("  文件大小: {} -> {} 字节 (-{})", size_before_remove, size_after_remove, size_before_remove - size_after_remove);
-    println!("  ✓ 验证通过：文件中已无资源");
+    let head = ResourceHead::from(&legacy).unwrap();
+    assert_eq!(head.id(), "old");
+    assert_eq!(head.metadata(), &Metadata::default());
+    assert_eq!(head.get_len(), legacy.len());
+}
 
-    // 步骤 6: 清理测试目录
-    println!("\n=== 步骤 6: 清理 ===");
-    fs::remove_dir_all(&test_dir).unwrap();
-    println!("  ✓ 测试目录已删除");
-    println!("\n所有测试通过!");
+/// 测试元数据(修改时间、权限、MIME、标签)的记录与还原
+#[test]
+fn test_resource_metadata() {
+    let test_dir = TestDir::new("metadata");
+
+    let target_file = test_dir.join("host.bin");
+    fs::write(&target_file, b"host").unwrap();
+    let source_file = test_dir.join("helper.png");
+    fs::write(&source_file, b"\x89PNG\r\n\x1a\nimage data").unwrap();
+    let modified = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_600_000_000);
+    fs::File::options()
+        .write(true)
+        .open(&source_file)
+        .unwrap()
+        .set_modified(modified)
+        .unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&source_file, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    let tags = vec![("lang".to_string(), "en".to_string())];
+    let options = AddOptions {
+        compression_grade: Some(6),
+        tags: tags.clone(),
+        ..AddOptions::default()
+    };
+    add_resource(&target_file, &source_file, "helper", &options, &mut |_| {}).unwrap();
+
+    let configs = find_resources_config(&target_file, |_pos, _config| {}, &mut |_| {}).unwrap();
+    let metadata = configs[0].metadata();
+    assert_eq!(metadata.mime.as_deref(), Some("image/png"));
+    assert_eq!(metadata.modified, Some(1_600_000_000));
+    assert_eq!(metadata.tags, tags);
+
+    let output_file = test_dir.join("exported.png");
+    let options = ExportOptions::default();
+    export_resource(&target_file, "helper", &output_file, &options, &mut |_| {}).unwrap();
+    let exported = fs::metadata(&output_file).unwrap();
+    assert_eq!(exported.modified().unwrap(), modified);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(metadata.permissions, Some(0o755));
+        assert_eq!(exported.permissions().mode() & 0o7777, 0o755);
+        restore_file_attributes(&output_file, None, Some(0o4755)).unwrap();
+        let mode = fs::metadata(&output_file).unwrap().permissions().mode();
+        assert_eq!(mode & 0o7777, 0o755);
+    }
+    assert!(matches!(
+        restore_file_attributes(&output_file, Some(u64::MAX), None),
+        Err(Error::Corrupted { .. })
+    ));
+}
+
+/// 测试从数据流增加资源并导出到数据流
+#[test]
+fn test_stream_add_export() {
+    let mut target = b"host".to_vec();
+    let payload: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+    for (id, grade) in [("plain", None), ("packed", Some(9))] {
+        let options = AddOptions {
+            name: Some("foo.bin".to_string()),
+            compression_grade: grade,
+            ..AddOptions::default()
+        };
+        add_in_memory(&mut target, &payload, id, &options).unwrap();
+    }
+
+    for id in ["plain", "packed"] {
+        let mut exported = Vec::new();
+        let config =
+            export_resource_from_reader(Cursor::new(&target), id, &mut exported, &mut |_| {})
+                .unwrap();
+        assert_eq!(config.name(), "foo.bin");
+        assert_eq!(config.size().parse::<usize>().unwrap(), payload.len());
+        assert_eq!(exported, payload);
+    }
+}
+
+/// 测试进度回调：各阶段最终进度应等于总字节数
+#[test]
+fn test_progress_callback() {
+    let test_dir = TestDir::new("progress");
+    let target_file = test_dir.join("host.bin");
+    fs::write(&target_file, vec![0u8; 1024 * 1024]).unwrap();
+    let source_file = test_dir.join("payload.bin");
+    fs::write(&source_file, vec![7u8; 3 * 1024 * 1024]).unwrap();
+
+    let mut events: Vec<Progress> = Vec::new();
+    add_resource(
+        &target_file,
+        &source_file,
+        "payload",
+        &AddOptions::default(),
+        &mut |p| events.push(p),
+    )
+    .unwrap();
+    let last = events.last().unwrap();
+    assert_eq!(last.stage, Stage::Add);
+    assert_eq!(Some(last.processed), last.total);
+    assert!(events.len() > 2);
+
+    events.clear();
+    let output_file = test_dir.join("exported.bin");
+    let options = ExportOptions::default();
+    export_resource(&target_file, "payload", &output_file, &options, &mut |p| {
+        events.push(p)
+    })
+    .unwrap();
+    assert!(events.iter().any(|p| p.stage == Stage::Scan));
+    let last = events.last().unwrap();
+    assert_eq!(last.stage, Stage::Export);
+    assert_eq!(Some(last.processed), last.total);
+
+    events.clear();
+    remove_resource(&target_file, "payload", None, &mut |p| events.push(p)).unwrap();
+    let last = events.last().unwrap();
+    assert_eq!(last.stage, Stage::Rewrite);
+    assert_eq!(last.processed, 1024 * 1024);
+    assert_eq!(fs::metadata(&target_file).unwrap().len(), 1024 * 1024);
+}
+
+/// 测试剥离全部资源还原宿主文件（含嵌套资源与损坏尾部）
+#[test]
+fn test_strip_resources() {
+    let test_dir = TestDir::new("strip");
+    let host: Vec<u8> = (0..10_000u32).map(|i| (i % 256) as u8).collect();
+    let target_file = test_dir.join("host.bin");
+    fs::write(&target_file, &host).unwrap();
+
+    // 内层文件本身携带资源，作为外层资源的数据
+    let inner_file = test_dir.join("inner.bin");
+    fs::write(&inner_file, b"inner host").unwrap();
+    let source_file = test_dir.join("data.txt");
+    fs::write(&source_file, b"payload").unwrap();
+    add_resource(
+        &inner_file,
+        &source_file,
+        "nested",
+        &AddOptions::default(),
+        &mut |_| {},
+    )
+    .unwrap();
+    add_resource(
+        &target_file,
+        &inner_file,
+        "inner",
+        &AddOptions::default(),
+        &mut |_| {},
+    )
+    .unwrap();
+    add_resource(
+        &target_file,
+        &source_file,
+        "data",
+        &AddOptions::default(),
+        &mut |_| {},
+    )
+    .unwrap();
+
+    let clean_file = test_dir.join("clean.bin");
+    let (host_size, stripped) =
+        strip_resources(&target_file, Some(&clean_file), &mut |_| {}).unwrap();
+    assert_eq!(host_size, host.len() as u64);
+    let ids: Vec<&str> = stripped.iter().map(|c| c.id()).collect();
+    assert_eq!(ids, ["inner", "data"]);
+    assert_eq!(fs::read(&clean_file).unwrap(), host);
+
+    // 尾部存在非资源数据时拒绝剥离
+    let mut corrupted = fs::read(&target_file).unwrap();
+    corrupted.extend_from_slice(b"garbage");
+    let corrupted_file = test_dir.join("corrupted.bin");
+    fs::write(&corrupted_file, &corrupted).unwrap();
+    assert!(strip_resources(&corrupted_file, None, &mut |_| {}).is_err());
+
+    strip_resources(&target_file, None, &mut |_| {}).unwrap();
+    assert_eq!(fs::read(&target_file).unwrap(), host);
+}
+
+/// 测试资源详细信息（位置、长度、结束标识）
+#[test]
+fn test_inspect_resources() {
+    let test_dir = TestDir::new("inspect");
+    let target_file = test_dir.join("host.bin");
+    fs::write(&target_file, vec![1u8; 500]).unwrap();
+    let source_file = test_dir.join("data.txt");
+    fs::write(&source_file, "text ".repeat(1000)).unwrap();
+
+    let options = AddOptions {
+        compression_grade: Some(9),
+        ..AddOptions::default()
+    };
+    add_resource(&target_file, &source_file, "text", &options, &mut |_| {}).unwrap();
+
+    let infos = inspect_resources(&target_file, &mut |_| {}).unwrap();
+    assert_eq!(infos.len(), 1);
+    let info = &infos[0];
+    assert_eq!(info.offset, 500);
+    assert_eq!(info.header_len, info.head.get_len() as u64);
+    assert_eq!(info.data_offset, 500 + 16 + info.header_len);
+    assert_eq!(info.end(), fs::metadata(&target_file).unwrap().len());
+    assert!(info.end_marker);
+    assert!(info.ratio().unwrap() < 0.1);
+
+    // 截断结束标识后应报告缺失
+    let data = fs::read(&target_file).unwrap();
+    fs::write(&target_file, &data[..data.len() - 2]).unwrap();
+    let infos = inspect_resources(&target_file, &mut |_| {}).unwrap();
+    assert!(!infos[0].end_marker);
+}
+
+/// 测试多线程压缩：输出为多个 gzip 成员，导出结果与原数据一致
+#[test]
+fn test_parallel_compression() {
+    let mut target = b"host".to_vec();
+
+    // 跨越多个压缩块的数据
+    let block = crate::util::COMPRESSION_BLOCK_SIZE;
+    let payload: Vec<u8> = (0..block * 2 + 12345)
+        .map(|i| ((i / 7) % 256) as u8)
+        .collect();
+    let options = AddOptions {
+        compression_grade: Some(1),
+        threads: 4,
+        ..AddOptions::default()
+    };
+    for (id, data) in [("large", &payload[..]), ("empty", &[][..])] {
+        add_in_memory(&mut target, data, id, &options).unwrap();
+        assert_eq!(export_in_memory(&target, id).unwrap(), data);
+
+        // 每个数据块对应一个 gzip 成员
+        if id == "large" {
+            let members = target
+                .windows(3)
+                .filter(|w| w == &[0x1f, 0x8b, 0x08])
+                .count();
+            assert!(members >= 3);
+        }
+    }
+}
+
+/// 测试扫描后端：魔数跨越缓冲窗口边界时，内存数据与缓冲读取结果一致且不重复
+#[test]
+fn test_scan_across_windows() {
+    use crate::core::RESOURCE_MAGIC;
+    use crate::progress::ProgressTracker;
+    use crate::source::{BufferedFile, FileSource};
+
+    // 宿主数据中的魔数分别位于窗口末尾与跨越窗口边界处
+    let window = 1024 * 512;
+    let mut host = vec![0u8; window * 2 + 100];
+    let expected = [window - 70, window * 2 - 20];
+    for pos in expected {
+        host[pos..pos + RESOURCE_MAGIC.len()].copy_from_slice(RESOURCE_MAGIC);
+    }
+
+    let scan = |source: &mut FileSource| {
+        let mut positions = Vec::new();
+        let mut callback = |_| {};
+        let mut tracker = ProgressTracker::new(&mut callback, Stage::Scan, None);
+        let mut from = 0;
+        while let Some(pos) = source.find(RESOURCE_MAGIC, from, &mut tracker).unwrap() {
+            positions.push(pos as usize);
+            from = pos + 1;
+        }
+        positions
+    };
+    let mut buffered = FileSource::Buffered(BufferedFile::new(Cursor::new(&host)).unwrap());
+    let mut memory = FileSource::Memory(std::borrow::Cow::Borrowed(&host));
+    assert_eq!(scan(&mut buffered), expected);
+    assert_eq!(scan(&mut memory), expected);
+
+    // 伪造的魔数不是有效资源，只识别真正追加的资源
+    let mut target = host.clone();
+    add_in_memory(&mut target, b"payload", "real", &AddOptions::default()).unwrap();
+    let configs = list_in_memory(&target);
+    assert_eq!(configs.len(), 1);
+    assert_eq!(configs[0].id(), "real");
+}
+
+/// 测试从文件末尾反向查找资源：优先命中顶层资源，旧版资源回退到从头扫描
+#[test]
+fn test_backward_lookup() {
+    use crate::core::RESOURCE_MAGIC;
+
+    let options = AddOptions::default();
+
+    // 内层数据自身携带同名资源
+    let mut inner = b"inner host".to_vec();
+    add_in_memory(&mut inner, b"inner", "data", &options).unwrap();
+
+    let mut target = b"outer host".to_vec();
+    add_in_memory(&mut target, &inner, "bundle", &options).unwrap();
+    add_in_memory(&mut target, b"outer", "data", &options).unwrap();
+    assert_eq!(export_in_memory(&target, "data").unwrap(), b"outer");
+
+    // 两次追加之间插入了其他数据时，更早的资源仍可导出且资源ID不可重复
+    let mut junk = inner.clone();
+    junk.extend_from_slice(b"junk");
+    add_in_memory(&mut junk, b"new", "new", &options).unwrap();
+    assert_eq!(export_in_memory(&junk, "data").unwrap(), b"inner");
+    assert_eq!(export_in_memory(&junk, "new").unwrap(), b"new");
+    assert!(matches!(
+        add_in_memory(&mut junk, b"other", "data", &options),
+        Err(Error::DuplicateId(id)) if id == "data"
+    ));
+    let ids: Vec<String> = list_in_memory(&junk)
+        .iter()
+        .map(|c| c.id().to_string())
+        .collect();
+    assert_eq!(ids, ["data", "new"]);
+
+    // 追加旧版资源(无尾部长度字段)
+    #[derive(serde::Serialize)]
+    struct Legacy {
+        version: String,
+        id: String,
+        name: String,
+        length: String,
+        size: String,
+        compress: CompressMode,
+    }
+    let payload = b"legacy payload!!";
+    let legacy = bincode::serialize(&Legacy {
+        version: "1.0.0".to_string(),
+        id: "old".to_string(),
+        name: "old.bin".to_string(),
+        length: "0000000000000016".to_string(),
+        size: "0000000000000016".to_string(),
+        compress: CompressMode::None,
+    })
+    .unwrap();
+    target.extend_from_slice(RESOURCE_MAGIC);
+    target.extend_from_slice(&legacy);
+    target.extend_from_slice(payload);
+    target.extend_from_slice(b"ODEND");
+    assert_eq!(export_in_memory(&target, "old").unwrap(), payload);
+}
+
+/// 测试扫描跳过资源数据：嵌套在资源数据中的资源不作为顶层资源报告
+#[test]
+fn test_skip_nested_payloads() {
+    let test_dir = TestDir::new("skip");
+    let options = AddOptions::default();
+
+    let inner_file = test_dir.join("inner.bin");
+    fs::write(&inner_file, b"inner host").unwrap();
+    add_resource_from_reader(
+        &inner_file,
+        &mut &b"nested"[..],
+        "nested",
+        &options,
+        &mut |_| {},
+    )
+    .unwrap();
+
+    let target_file = test_dir.join("host.bin");
+    fs::write(&target_file, b"outer host").unwrap();
+    add_resource(&target_file, &inner_file, "bundle", &options, &mut |_| {}).unwrap();
+    add_resource_from_reader(
+        &target_file,
+        &mut &b"data"[..],
+        "data",
+        &options,
+        &mut |_| {},
+    )
+    .unwrap();
+
+    let mut positions = Vec::new();
+    let configs =
+        find_resources_config(&target_file, |pos, _| positions.push(pos), &mut |_| {}).unwrap();
+    let ids: Vec<&str> = configs.iter().map(|c| c.id()).collect();
+    assert_eq!(ids, ["bundle", "data"]);
+    assert_eq!(positions[0], 10);
+
+    // 嵌套资源不能直接通过顶层 ID 导出
+    let mut exported = Vec::new();
+    assert!(export_resource_to_writer(&target_file, "nested", &mut exported, &mut |_| {}).is_err());
+}
+
+/// 测试嵌套容器：以 `外层ID/内层ID` 列出、导出与校验嵌套资源(含压缩的外层资源)
+#[test]
+fn test_nested_resources() {
+    let test_dir = TestDir::new("nested");
+    let compressed = AddOptions {
+        compression_grade: Some(6),
+        ..AddOptions::default()
+    };
+
+    // 三层嵌套：host <- setup(压缩) <- runtime <- readme
+    let runtime_file = test_dir.join("runtime.bin");
+    fs::write(&runtime_file, b"runtime host").unwrap();
+    add_resource_from_reader(
+        &runtime_file,
+        &mut &b"read me"[..],
+        "readme",
+        &AddOptions::default(),
+        &mut |_| {},
+    )
+    .unwrap();
+    let setup_file = test_dir.join("setup.bin");
+    fs::write(&setup_file, b"setup host").unwrap();
+    add_resource(
+        &setup_file,
+        &runtime_file,
+        "runtime",
+        &AddOptions::default(),
+        &mut |_| {},
+    )
+    .unwrap();
+    let target_file = test_dir.join("host.bin");
+    fs::write(&target_file, b"outer host").unwrap();
+    add_resource(&target_file, &setup_file, "setup", &compressed, &mut |_| {}).unwrap();
+
+    let resources = find_nested_resources(&target_file, &mut |_| {}).unwrap();
+    let paths: Vec<&str> = resources.iter().map(|(path, _)| path.as_str()).collect();
+    assert_eq!(paths, ["setup", "setup/runtime", "setup/runtime/readme"]);
+
+    let mut exported = Vec::new();
+    let head = export_resource_to_writer(
+        &target_file,
+        "setup/runtime/readme",
+        &mut exported,
+        &mut |_| {},
+    )
+    .unwrap();
+    assert_eq!(head.id(), "readme");
+    assert_eq!(exported, b"read me");
+    verify_resource(&target_file, "setup/runtime", &mut |_| {}).unwrap();
+    assert!(verify_resource(&target_file, "setup/missing", &mut |_| {}).is_err());
+
+    // 损坏的资源无法通过校验
+    let mut corrupted = fs::read(&runtime_file).unwrap();
+    let len = corrupted.len();
+    corrupted[len - 1] = b'X';
+    let corrupted_file = test_dir.join("corrupted.bin");
+    fs::write(&corrupted_file, &corrupted).unwrap();
+    assert!(verify_resource(&corrupted_file, "readme", &mut |_| {}).is_err());
+}
+
+/// 测试相同数据去重：只存储一份数据，删除被引用的资源时提升引用
+#[test]
+fn test_deduplicate_payloads() {
+    let mut target = b"host".to_vec();
+    let payload: Vec<u8> = (0..100_000u32)
+        .map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8)
+        .collect();
+    let count_copies = |data: &[u8]| {
+        data.windows(1000)
+            .filter(|w| w == &&payload[..1000])
+            .count()
+    };
+
+    let compressed = AddOptions {
+        compression_grade: Some(6),
+        ..AddOptions::default()
+    };
+    add_in_memory(&mut target, &payload, "en", &AddOptions::default()).unwrap();
+    add_in_memory(&mut target, &payload, "fr", &compressed).unwrap();
+    add_in_memory(&mut target, &payload, "de", &AddOptions::default()).unwrap();
+    assert_eq!(count_copies(&target), 1);
+
+    let configs = list_in_memory(&target);
+    let storages: Vec<Storage> = configs.iter().map(|c| c.storage()).collect();
+    assert_eq!(
+        storages,
+        [Storage::Inline, Storage::Reference, Storage::Reference]
+    );
+    assert!(configs
+        .iter()
+        .all(|c| c.checksum() == configs[0].checksum()));
+
+    // 写入前识别重复数据：目标数据只增加一个资源头，不会先写入数据再截断
+    struct Peak(Cursor<Vec<u8>>, usize);
+    impl std::io::Read for Peak {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.0.read(buf)
+        }
+    }
+    impl Write for Peak {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            let n = self.0.write(buf)?;
+            self.1 = self.1.max(self.0.get_ref().len());
+            Ok(n)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+    impl std::io::Seek for Peak {
+        fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+            self.0.seek(pos)
+        }
+    }
+    impl Backend for Peak {
+        fn set_len(&mut self, len: u64) -> std::io::Result<()> {
+            self.0.set_len(len)
+        }
+    }
+    let mut backend = Peak(Cursor::new(target.clone()), 0);
+    let mut container = Container::from_backend(&mut backend).unwrap();
+    container
+        .add_reader(Cursor::new(payload.clone()), "es", &AddOptions::default())
+        .unwrap();
+    container.commit(None, &mut |_| {}).unwrap();
+    assert!(backend.1 < target.len() + 1024);
+    assert_eq!(count_copies(backend.0.get_ref()), 1);
+
+    // 删除存储数据的资源后，引用资源仍可导出
+    remove_in_memory(&mut target, "en").unwrap();
+    assert_eq!(count_copies(&target), 1);
+    for id in ["fr", "de"] {
+        assert_eq!(export_in_memory(&target, id).unwrap(), payload);
+    }
+
+    remove_in_memory(&mut target, "de").unwrap();
+    remove_in_memory(&mut target, "fr").unwrap();
+    assert_eq!(target, b"host");
+}
+
+/// 测试分块存储：相似数据共享数据块，删除资源时回收不再使用的数据块
+#[test]
+fn test_chunked_storage() {
+    let mut target = b"host".to_vec();
+
+    // 新版本在中间插入并修改少量数据
+    let mut state: u32 = 1;
+    let v1: Vec<u8> = (0..2 * 1024 * 1024)
+        .map(|_| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (state >> 16) as u8
+        })
+        .collect();
+    let mut v2 = v1.clone();
+    v2.splice(1_000_000..1_000_000, b"inserted bytes".iter().copied());
+    v2[1_500_000] ^= 0xFF;
+
+    let chunked = AddOptions {
+        chunked: true,
+        ..AddOptions::default()
+    };
+    add_in_memory(&mut target, &v1, "v1", &chunked).unwrap();
+    let v1_len = target.len();
+    let compressed = AddOptions {
+        compression_grade: Some(1),
+        ..chunked.clone()
+    };
+    add_in_memory(&mut target, &v2, "v2", &compressed).unwrap();
+    let v2_len = target.len();
+    assert!(v2_len - v1_len < 1024 * 1024);
+
+    // 数据块不作为资源列出
+    let configs = list_in_memory(&target);
+    let ids: Vec<&str> = configs.iter().map(|c| c.id()).collect();
+    assert_eq!(ids, ["v1", "v2"]);
+    assert!(configs.iter().all(|c| c.storage() == Storage::Chunked));
+
+    for (id, data) in [("v1", &v1), ("v2", &v2)] {
+        assert_eq!(&export_in_memory(&target, id).unwrap(), data);
+    }
+
+    // 删除 v1 只回收 v2 不使用的数据块
+    remove_in_memory(&mut target, "v1").unwrap();
+    assert!(target.len() > v2_len - v1_len);
+    assert_eq!(export_in_memory(&target, "v2").unwrap(), v2);
+
+    remove_in_memory(&mut target, "v2").unwrap();
+    assert_eq!(target, b"host");
+}
+
+/// 测试差异更新：只存储补丁，导出时还原最新版本，完整更新或删除时回收旧版本
+#[test]
+fn test_delta_update() {
+    let test_dir = TestDir::new("delta");
+    let target_file = test_dir.join("host.bin");
+    fs::write(&target_file, b"host").unwrap();
+
+    let mut state: u32 = 7;
+    let v1: Vec<u8> = (0..256 * 1024)
+        .map(|_| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (state >> 16) as u8
+        })
+        .collect();
+    let mut v2 = v1.clone();
+    v2.splice(100_000..100_000, b"patched launcher".iter().copied());
+    v2[200_000] ^= 0xFF;
+    let mut v3 = v2.clone();
+    v3.truncate(150_000);
+    let versions = [&v1, &v2, &v3];
+    for (index, data) in versions.iter().enumerate() {
+        fs::write(test_dir.join(format!("v{}.bin", index + 1)), data).unwrap();
+    }
+
+    let options = AddOptions::default();
+    add_resource(
+        &target_file,
+        &test_dir.join("v1.bin"),
+        "app",
+        &options,
+        &mut |_| {},
+    )
+    .unwrap();
+    let v1_len = fs::metadata(&target_file).unwrap().len();
+    for version in ["v2.bin", "v3.bin"] {
+        update_resource(
+            &target_file,
+            &test_dir.join(version),
+            "app",
+            &options,
+            true,
+            &mut |_| {},
+        )
+        .unwrap();
+    }
+    assert!(fs::metadata(&target_file).unwrap().len() - v1_len < 4096);
+
+    // 旧版本不作为资源列出，导出最新版本
+    let configs = find_resources_config(&target_file, |_, _| {}, &mut |_| {}).unwrap();
+    assert_eq!(configs.len(), 1);
+    assert_eq!(configs[0].storage(), Storage::Delta);
+    assert_eq!(configs[0].name(), "v3.bin");
+    let mut exported = Vec::new();
+    export_resource_to_writer(&target_file, "app", &mut exported, &mut |_| {}).unwrap();
+    assert_eq!(exported, v3);
+
+    // 完整更新删除全部旧版本
+    update_resource(
+        &target_file,
+        &test_dir.join("v1.bin"),
+        "app",
+        &options,
+        false,
+        &mut |_| {},
+    )
+    .unwrap();
+    let infos = inspect_resources(&target_file, &mut |_| {}).unwrap();
+    assert_eq!(infos.len(), 1);
+    assert_eq!(infos[0].head.storage(), Storage::Inline);
+    let mut exported = Vec::new();
+    export_resource_to_writer(&target_file, "app", &mut exported, &mut |_| {}).unwrap();
+    assert_eq!(exported, v1);
+
+    // 删除差异资源时一并删除旧版本
+    update_resource(
+        &target_file,
+        &test_dir.join("v2.bin"),
+        "app",
+        &options,
+        true,
+        &mut |_| {},
+    )
+    .unwrap();
+    let missing = test_dir.join("v2.bin");
+    assert!(update_resource(
+        &target_file,
+        &missing,
+        "missing",
+        &options,
+        true,
+        &mut |_| {}
+    )
+    .is_err());
+
+    // 输出到新文件时不修改目标文件，失败时不留下输出文件
+    let before = fs::read(&target_file).unwrap();
+    let output_file = test_dir.join("updated.bin");
+    let to_output = AddOptions {
+        output_path: Some(output_file.clone()),
+        ..AddOptions::default()
+    };
+    let none = test_dir.join("none.bin");
+    let result = update_resource(&target_file, &none, "app", &to_output, false, &mut |_| {});
+    assert!(matches!(result, Err(Error::Io(_))));
+    assert!(!output_file.exists());
+    update_resource(
+        &target_file,
+        &test_dir.join("v3.bin"),
+        "app",
+        &to_output,
+        false,
+        &mut |_| {},
+    )
+    .unwrap();
+    assert_eq!(fs::read(&target_file).unwrap(), before);
+    let configs = find_resources_config(&output_file, |_, _| {}, &mut |_| {}).unwrap();
+    assert_eq!(configs.len(), 1);
+    assert_eq!(configs[0].storage(), Storage::Inline);
+
+    remove_resource(&target_file, "app", None, &mut |_| {}).unwrap();
+    assert_eq!(fs::read(&target_file).unwrap(), b"host");
+
+    // 差异链构成循环(旧版本的基准校验和指向自身)时报告数据损坏
+    add_resource(
+        &target_file,
+        &test_dir.join("v1.bin"),
+        "app",
+        &options,
+        &mut |_| {},
+    )
+    .unwrap();
+    for _ in 0..2 {
+        update_resource(
+            &target_file,
+            &test_dir.join("v1.bin"),
+            "app",
+            &options,
+            true,
+            &mut |_| {},
+        )
+        .unwrap();
+    }
+    let infos = inspect_resources(&target_file, &mut |_| {}).unwrap();
+    let mut data = fs::read(&target_file).unwrap();
+    data.drain(infos[0].offset as usize..infos[0].end() as usize);
+    fs::write(&target_file, &data).unwrap();
+    let result = export_resource_to_writer(&target_file, "app", &mut std::io::sink(), &mut |_| {});
+    assert!(
+        matches!(result, Err(Error::Corrupted { reason, .. }) if reason == "Delta chain cycle")
+    );
+}
+
+/// 测试分卷存储：数据分布在宿主文件与分卷文件中，缺少分卷时校验失败，删除资源时删除分卷
+#[test]
+fn test_split_volumes() {
+    let test_dir = TestDir::new("split");
+    let target_file = test_dir.join("app.exe");
+    fs::write(&target_file, b"host").unwrap();
+
+    let data: Vec<u8> = (0..100_000u32).map(|i| (i * 7 % 251) as u8).collect();
+    let options = AddOptions {
+        split_size: Some(30_000),
+        ..AddOptions::default()
+    };
+    add_resource_from_reader(&target_file, &mut &data[..], "big", &options, &mut |_| {}).unwrap();
+    let compressed = AddOptions {
+        compression_grade: Some(6),
+        split_size: Some(200),
+        ..AddOptions::default()
+    };
+    add_resource_from_reader(
+        &target_file,
+        &mut &data[..],
+        "packed",
+        &compressed,
+        &mut |_| {},
+    )
+    .unwrap();
+
+    // 未压缩资源占用 3 个分卷，压缩资源的分卷序号接在其后
+    let configs = find_resources_config(&target_file, |_, _| {}, &mut |_| {}).unwrap();
+    assert_eq!(configs.len(), 2);
+    assert_eq!(configs[0].storage(), Storage::Split);
+    assert_eq!(
+        (configs[0].volumes().first, configs[0].volumes().count),
+        (1, 3)
+    );
+    assert_eq!(configs[1].volumes().first, 4);
+    assert!(fs::metadata(volume_path(&target_file, 3)).unwrap().len() > 10_000);
+
+    for id in ["big", "packed"] {
+        let mut exported = Vec::new();
+        export_resource_to_writer(&target_file, id, &mut exported, &mut |_| {}).unwrap();
+        assert_eq!(exported, data);
+    }
+
+    // 缺少或替换分卷时校验失败
+    let second = volume_path(&target_file, 2);
+    let backup = fs::read(&second).unwrap();
+    fs::remove_file(&second).unwrap();
+    assert!(verify_resource(&target_file, "big", &mut |_| {}).is_err());
+    fs::write(&second, fs::read(volume_path(&target_file, 4)).unwrap()).unwrap();
+    assert!(verify_resource(&target_file, "big", &mut |_| {}).is_err());
+    fs::write(&second, backup).unwrap();
+    verify_resource(&target_file, "big", &mut |_| {}).unwrap();
+
+    // 删除资源时一并删除其分卷
+    remove_resource(&target_file, "big", None, &mut |_| {}).unwrap();
+    assert!(!volume_path(&target_file, 1).exists());
+    assert!(volume_path(&target_file, 4).exists());
+    strip_resources(&target_file, None, &mut |_| {}).unwrap();
+    assert_eq!(fs::read_dir(&test_dir).unwrap().count(), 1);
+    assert_eq!(fs::read(&target_file).unwrap(), b"host");
+}
+
+/// 测试旁路文件：资源存储在 `app.exe.overlay` 中，宿主文件不变，宿主文件改变后拒绝读取
+#[test]
+fn test_sidecar() {
+    let test_dir = TestDir::new("sidecar");
+    let target_file = test_dir.join("app.exe");
+    fs::write(&target_file, b"signed host").unwrap();
+
+    let sidecar = create_sidecar(&target_file).unwrap();
+    assert_eq!(sidecar, sidecar_path(&target_file));
+    assert_eq!(sidecar, test_dir.join("app.exe.overlay"));
+    let options = AddOptions::default();
+    for id in ["a", "b"] {
+        add_resource_from_reader(&sidecar, &mut &b"payload"[..], id, &options, &mut |_| {})
+            .unwrap();
+    }
+    remove_resource(&sidecar, "a", None, &mut |_| {}).unwrap();
+    assert_eq!(fs::read(&target_file).unwrap(), b"signed host");
+
+    // 绑定记录不作为资源列出
+    let configs = find_resources_config(&sidecar, |_, _| {}, &mut |_| {}).unwrap();
+    assert_eq!(configs.len(), 1);
+    assert_eq!(configs[0].id(), "b");
+    let mut exported = Vec::new();
+    read_sidecar_resource(&target_file, "b", &mut exported, &mut |_| {}).unwrap();
+    assert_eq!(exported, b"payload");
+
+    // 再次创建时沿用已有旁路文件
+    assert_eq!(create_sidecar(&target_file).unwrap(), sidecar);
+
+    // 宿主文件改变后绑定失效
+    fs::write(&target_file, b"patched host").unwrap();
+    assert!(matches!(
+        open_sidecar(&target_file),
+        Err(Error::Corrupted { .. })
+    ));
+    assert!(read_sidecar_resource(&target_file, "b", &mut Vec::new(), &mut |_| {}).is_err());
+    assert!(matches!(
+        open_sidecar(&test_dir.join("missing.exe")),
+        Err(Error::NotFound(_))
+    ));
+}
+
+/// 发送 HTTP 请求并读取完整响应
+fn http_request(address: std::net::SocketAddr, request: &str) -> (String, Vec<u8>) {
+    use std::io::Read;
+    let mut stream = std::net::TcpStream::connect(address).unwrap();
+    stream.write_all(request.as_bytes()).unwrap();
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).unwrap();
+    let split = response.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+    let head = String::from_utf8_lossy(&response[..split]).to_string();
+    (head, response[split + 4..].to_vec())
+}
+
+/// 测试 HTTP 服务：资源列表、区间下载与 ETag
+#[test]
+fn test_http_server() {
+    let test_dir = TestDir::new("server");
+    let target_file = test_dir.join("host.bin");
+    fs::write(&target_file, b"host").unwrap();
+    let data: Vec<u8> = (0..10_000u32).map(|i| (i % 253) as u8).collect();
+    let options = AddOptions {
+        compression_grade: Some(6),
+        ..AddOptions::default()
+    };
+    add_resource_from_reader(
+        &target_file,
+        &mut &data[..],
+        "game assets",
+        &options,
+        &mut |_| {},
+    )
+    .unwrap();
+
+    let server = Server::bind(&target_file, "127.0.0.1:0").unwrap();
+    let address = server.local_addr().unwrap();
+    std::thread::spawn(move || server.run());
+
+    let (head, body) = http_request(address, "GET /index.json HTTP/1.1\r\n\r\n");
+    assert!(head.starts_with("HTTP/1.1 200"));
+    let body = String::from_utf8(body).unwrap();
+    assert!(body.contains("\"id\": \"game assets\""));
+    assert!(body.contains("/resources/game%20assets"));
+    let (_, body) = http_request(address, "GET / HTTP/1.1\r\n\r\n");
+    assert!(String::from_utf8(body)
+        .unwrap()
+        .contains("<a href=\"/resources/game%20assets\">"));
+
+    // 完整下载与区间下载
+    let (head, body) = http_request(address, "GET /resources/game%20assets HTTP/1.1\r\n\r\n");
+    assert!(head.starts_with("HTTP/1.1 200"));
+    assert_eq!(body, data);
+    let etag = head
+        .lines()
+        .find_map(|line| line.strip_prefix("ETag: "))
+        .unwrap()
+        .to_string();
+    let (head, body) = http_request(
+        address,
+        "GET /resources/game%20assets HTTP/1.1\r\nRange: bytes=100-199\r\n\r\n",
+    );
+    assert!(head.starts_with("HTTP/1.1 206"));
+    assert!(head.contains("Content-Range: bytes 100-199/10000"));
+    assert_eq!(body, &data[100..200]);
+    let (head, body) = http_request(
+        address,
+        "GET /resources/game%20assets HTTP/1.1\r\nRange: bytes=0-0\r\n\r\n",
+    );
+    assert!(head.starts_with("HTTP/1.1 206"));
+    assert_eq!(body, &data[..1]);
+    let (_, body) = http_request(
+        address,
+        "GET /resources/game%20assets HTTP/1.1\r\nRange: bytes=-10\r\n\r\n",
+    );
+    assert_eq!(body, &data[9_990..]);
+    let (head, _) = http_request(
+        address,
+        "GET /resources/game%20assets HTTP/1.1\r\nRange: bytes=20000-\r\n\r\n",
+    );
+    assert!(head.starts_with("HTTP/1.1 416"));
+
+    // ETag 一致时不返回数据
+    let request = format!(
+        "GET /resources/game%20assets HTTP/1.1\r\nIf-None-Match: {}\r\n\r\n",
+        etag
+    );
+    let (head, body) = http_request(address, &request);
+    assert!(head.starts_with("HTTP/1.1 304"));
+    assert!(body.is_empty());
+
+    let (head, _) = http_request(address, "GET /resources/missing HTTP/1.1\r\n\r\n");
+    assert!(head.starts_with("HTTP/1.1 404"));
+    let (head, _) = http_request(address, "GET /resources/game%20assets/x HTTP/1.1\r\n\r\n");
+    assert!(head.starts_with("HTTP/1.1 404"));
+    let (head, _) = http_request(address, "POST / HTTP/1.1\r\n\r\n");
+    assert!(head.starts_with("HTTP/1.1 405"));
+
+    // 请求行过长或连接数超过上限时直接关闭连接
+    let no_response = |request: &str| {
+        let mut stream = std::net::TcpStream::connect(address).unwrap();
+        let _ = stream.write_all(request.as_bytes());
+        let mut response = Vec::new();
+        let _ = std::io::Read::read_to_end(&mut stream, &mut response);
+        response.is_empty()
+    };
+    assert!(no_response(&format!(
+        "GET /{} HTTP/1.1\r\n\r\n",
+        "a".repeat(10_000)
+    )));
+    let idle: Vec<_> = (0..64)
+        .map(|_| std::net::TcpStream::connect(address).unwrap())
+        .collect();
+    assert!(no_response("GET / HTTP/1.1\r\n\r\n"));
+    drop(idle);
+}
+
+/// 测试安装器：释放、覆盖与失败回滚
+#[test]
+fn test_installer() {
+    let test_dir = TestDir::new("installer");
+    let target_file = test_dir.join("setup.exe");
+    fs::write(&target_file, b"installer stub").unwrap();
+    let options = AddOptions::default();
+    let config = vec![b'C'; 4096];
+    let resources: [(&str, &[u8]); 2] = [("runtime", b"runtime"), ("config", &config)];
+    for (id, mut data) in resources {
+        add_resource_from_reader(&target_file, &mut data, id, &options, &mut |_| {}).unwrap();
+    }
+
+    let destination = test_dir.join("app");
+    let mut stages = Vec::new();
+    let installed = Installer::new(&target_file, &destination)
+        .resource("runtime")
+        .resource_to("config", "etc/app.conf")
+        .install(&mut |p: Progress| stages.push((p.stage, p.processed, p.total)))
+        .unwrap();
+    assert_eq!(
+        installed,
+        vec![
+            destination.join("runtime"),
+            destination.join("etc/app.conf")
+        ]
+    );
+    assert_eq!(fs::read(destination.join("etc/app.conf")).unwrap(), config);
+    assert_eq!(stages.last(), Some(&(Stage::Install, 4103, Some(4103))));
+
+    // 路径超出目标目录、资源不存在时不修改目标目录
+    let escape = Installer::new(&target_file, &destination).resource_to("runtime", "../x");
+    assert!(matches!(
+        escape.install(&mut |_| {}),
+        Err(Error::InvalidInput(_))
+    ));
+    let missing = Installer::new(&target_file, &destination).resource("missing");
+    assert!(matches!(
+        missing.install(&mut |_| {}),
+        Err(Error::NotFound(_))
+    ));
+
+    // 第二个资源损坏时回滚：还原被覆盖的文件，删除新建的文件与目录
+    let mut corrupted = fs::read(&target_file).unwrap();
+    let offset = corrupted.windows(4).position(|w| w == b"CCCC").unwrap();
+    corrupted[offset] = b'X';
+    let corrupted_file = test_dir.join("corrupted.exe");
+    fs::write(&corrupted_file, &corrupted).unwrap();
+    let fresh = test_dir.join("fresh");
+    fs::create_dir_all(&fresh).unwrap();
+    fs::write(fresh.join("runtime"), b"old runtime").unwrap();
+    let result = Installer::new(&corrupted_file, &fresh)
+        .resource("runtime")
+        .resource_to("config", "etc/app.conf")
+        .install(&mut |_| {});
+    assert!(result.is_err());
+    assert_eq!(fs::read(fresh.join("runtime")).unwrap(), b"old runtime");
+    assert_eq!(fs::read_dir(&fresh).unwrap().count(), 1);
+}
+
+/// 测试预演：计划的文件大小与实际执行结果一致，且不修改目标文件
+#[test]
+fn test_dry_run() {
+    let test_dir = TestDir::new("dry_run");
+    let target_file = test_dir.join("app.exe");
+    fs::write(&target_file, b"host program").unwrap();
+    let v1: Vec<u8> = (0..20000u32).map(|i| (i * 7 % 251) as u8).collect();
+    let mut v2 = v1.clone();
+    v2[100..110].copy_from_slice(b"0123456789");
+    fs::write(test_dir.join("v1.bin"), &v1).unwrap();
+    fs::write(test_dir.join("v2.bin"), &v2).unwrap();
+    let compressed = AddOptions {
+        compression_grade: Some(6),
+        ..AddOptions::default()
+    };
+
+    // 执行操作前后比较计划与实际结果
+    let check = |plan: crate::plan::DryRun, run: &dyn Fn()| {
+        let before = fs::read(&target_file).unwrap();
+        assert_eq!(plan.original_len, before.len() as u64);
+        run();
+        assert_eq!(plan.new_len, fs::metadata(&target_file).unwrap().len());
+        plan
+    };
+
+    let v1_file = test_dir.join("v1.bin");
+    let plan = plan_add(&target_file, &v1_file, "app", &compressed, &mut |_| {}).unwrap();
+    assert_eq!(fs::read(&target_file).unwrap(), b"host program");
+    let plan = check(plan, &|| {
+        add_resource(&target_file, &v1_file, "app", &compressed, &mut |_| {}).unwrap()
+    });
+    assert!(matches!(
+        plan.changes[..],
+        [Change::Append {
+            offset: 12,
+            storage: Storage::Inline,
+            ..
+        }]
+    ));
+
+    // 资源ID重复时预演与实际操作都失败
+    assert!(plan_add(&target_file, &v1_file, "app", &compressed, &mut |_| {}).is_err());
+    assert!(add_resource(&target_file, &v1_file, "app", &compressed, &mut |_| {}).is_err());
+
+    // 相同数据只写入引用
+    let plan = plan_add(&target_file, &v1_file, "copy", &compressed, &mut |_| {}).unwrap();
+    let plan = check(plan, &|| {
+        add_resource(&target_file, &v1_file, "copy", &compressed, &mut |_| {}).unwrap()
+    });
+    assert!(matches!(
+        plan.changes[..],
+        [Change::Append {
+            storage: Storage::Reference,
+            ..
+        }]
+    ));
+
+    let v2_file = test_dir.join("v2.bin");
+    let plan = plan_update(
+        &target_file,
+        &v2_file,
+        "app",
+        &compressed,
+        true,
+        &mut |_| {},
+    )
+    .unwrap();
+    let plan = check(plan, &|| {
+        update_resource(
+            &target_file,
+            &v2_file,
+            "app",
+            &compressed,
+            true,
+            &mut |_| {},
+        )
+        .unwrap()
+    });
+    assert!(matches!(
+        plan.changes[..],
+        [
+            Change::Append {
+                storage: Storage::Delta,
+                ..
+            },
+            Change::Rewrite { .. }
+        ]
+    ));
+
+    let plan = plan_remove(&target_file, "copy", None, &mut |_| {}).unwrap();
+    check(plan, &|| {
+        remove_resource(&target_file, "copy", None, &mut |_| {}).unwrap()
+    });
+    let plan = plan_update(
+        &target_file,
+        &v1_file,
+        "app",
+        &compressed,
+        false,
+        &mut |_| {},
+    )
+    .unwrap();
+    check(plan, &|| {
+        update_resource(
+            &target_file,
+            &v1_file,
+            "app",
+            &compressed,
+            false,
+            &mut |_| {},
+        )
+        .unwrap()
+    });
+    let plan = plan_remove(&target_file, "app", None, &mut |_| {}).unwrap();
+    check(plan, &|| {
+        remove_resource(&target_file, "app", None, &mut |_| {}).unwrap()
+    });
+    assert_eq!(fs::read(&target_file).unwrap(), b"host program");
+    assert!(plan_remove(&target_file, "app", None, &mut |_| {}).is_err());
+
+    // 删除被引用的资源时引用资源改为存储数据
+    for id in ["a", "b"] {
+        add_resource(&target_file, &v1_file, id, &compressed, &mut |_| {}).unwrap();
+    }
+    let plan = plan_remove(&target_file, "a", None, &mut |_| {}).unwrap();
+    let plan = check(plan, &|| {
+        remove_resource(&target_file, "a", None, &mut |_| {}).unwrap()
+    });
+    assert!(matches!(
+        plan.changes[..],
+        [
+            Change::Remove { .. },
+            Change::Remove { .. },
+            Change::Append { .. }
+        ]
+    ));
+
+    // 分卷与分块存储的计划与实际写入一致，预演不创建分卷文件
+    let split = AddOptions {
+        split_size: Some(8000),
+        ..AddOptions::default()
+    };
+    let plan = plan_add(&target_file, &v2_file, "split", &split, &mut |_| {}).unwrap();
+    let volumes: Vec<_> = plan
+        .changes
+        .iter()
+        .filter_map(|change| match change {
+            Change::CreateVolume { path, length } => Some((path.clone(), *length)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(volumes.len(), 2);
+    assert!(volumes.iter().all(|(path, _)| !path.exists()));
+    check(plan, &|| {
+        add_resource(&target_file, &v2_file, "split", &split, &mut |_| {}).unwrap()
+    });
+    for (path, length) in volumes {
+        assert_eq!(fs::metadata(path).unwrap().len(), length);
+    }
+    let chunked = AddOptions {
+        chunked: true,
+        ..AddOptions::default()
+    };
+    let plan = plan_add(&target_file, &v2_file, "chunked", &chunked, &mut |_| {}).unwrap();
+    let plan = check(plan, &|| {
+        add_resource(&target_file, &v2_file, "chunked", &chunked, &mut |_| {}).unwrap()
+    });
+    assert!(matches!(
+        plan.changes.last(),
+        Some(Change::Append {
+            storage: Storage::Chunked,
+            ..
+        })
+    ));
+}
+
+/// 测试错误类别
+#[test]
+fn test_error_kinds() {
+    let test_dir = TestDir::new("error_kinds");
+    let target_file = test_dir.join("target.bin");
+    let source_file = test_dir.join("source.bin");
+    fs::write(&target_file, b"host program").unwrap();
+    fs::write(&source_file, b"resource data").unwrap();
+    let options = AddOptions::default();
+
+    // 资源文件头超过长度上限时返回错误而不是 panic
+    let long_id = "x".repeat(1024);
+    assert!(matches!(
+        ResourceHead::new(&long_id, 0, 0, "", CompressMode::None),
+        Err(Error::InvalidId(_))
+    ));
+    assert!(matches!(
+        ResourceHead::new("id", 0, 0, &long_id, CompressMode::None),
+        Err(Error::InvalidName(_))
+    ));
+    assert!(matches!(
+        add_resource(&target_file, &source_file, &long_id, &options, &mut |_| {}),
+        Err(Error::InvalidId(_))
+    ));
+
+    add_resource(&target_file, &source_file, "res", &options, &mut |_| {}).unwrap();
+    assert!(matches!(
+        add_resource(&target_file, &source_file, "res", &options, &mut |_| {}),
+        Err(Error::DuplicateId(id)) if id == "res"
+    ));
+    assert!(matches!(
+        remove_resource(&target_file, "missing", None, &mut |_| {}),
+        Err(Error::NotFound(id)) if id == "missing"
+    ));
+    let output_file = test_dir.join("out.bin");
+    let export_options = ExportOptions::default();
+    assert!(matches!(
+        export_resource(
+            &target_file,
+            "missing",
+            &output_file,
+            &export_options,
+            &mut |_| {}
+        ),
+        Err(Error::NotFound(_))
+    ));
+
+    // 资源版本高于程序支持的版本
+    let original = fs::read(&target_file).unwrap();
+    let version = original.windows(5).position(|w| w == b"1.1.0").unwrap();
+    let mut data = original.clone();
+    data[version] = b'9';
+    fs::write(&target_file, &data).unwrap();
+    assert!(matches!(
+        export_resource(&target_file, "res", &output_file, &export_options, &mut |_| {}),
+        Err(Error::VersionMismatch { found, .. }) if found == "9.1.0"
+    ));
+
+    // 资源数据损坏
+    let mut data = original.clone();
+    let payload = data
+        .windows(13)
+        .position(|w| w == b"resource data")
+        .unwrap();
+    data[payload] ^= 0xFF;
+    fs::write(&target_file, &data).unwrap();
+    let error = verify_resource(&target_file, "res", &mut |_| {}).unwrap_err();
+    assert!(matches!(error, Error::Corrupted { .. }), "{}", error);
+}
+
+/// 测试释放到目录时检查资源文件名
+#[test]
+fn test_unsafe_names() {
+    for name in [
+        "file.bin",
+        "archive.tar.gz",
+        ".hidden",
+        "CONSOLE.txt",
+        "my file",
+    ] {
+        assert!(check_file_name(name).is_ok(), "{}", name);
+    }
+    for name in [
+        "",
+        ".",
+        "..",
+        "../x",
+        "a/b",
+        "a\\b",
+        "/etc/passwd",
+        "C:\\x",
+        "C:x",
+        "x.",
+        "x ",
+        "CON",
+        "con.txt",
+        "LPT1.log",
+        "nul .txt",
+        "a\nb",
+        "\u{1b}[31m",
+    ] {
+        assert!(
+            matches!(check_file_name(name), Err(Error::InvalidName(_))),
+            "{:?}",
+            name
+        );
+    }
+
+    let test_dir = TestDir::new("unsafe_names");
+    let output_dir = test_dir.join("out");
+    fs::create_dir_all(&output_dir).unwrap();
+    let target_file = test_dir.join("target.bin");
+    fs::write(&target_file, b"host program").unwrap();
+    for (id, name) in [("escape", "../escape.txt"), ("device", "con.txt")] {
+        let options = AddOptions {
+            name: Some(name.to_string()),
+            ..AddOptions::default()
+        };
+        add_resource_from_reader(&target_file, &mut &b"data"[..], id, &options, &mut |_| {})
+            .unwrap();
+    }
+
+    // 默认拒绝不安全的资源文件名，不写入任何文件
+    let options = ExportOptions::default();
+    assert!(matches!(
+        export_resource(&target_file, "escape", &output_dir, &options, &mut |_| {}),
+        Err(Error::InvalidName(_))
+    ));
+    assert!(!test_dir.join("escape.txt").exists());
+    assert_eq!(fs::read_dir(&output_dir).unwrap().count(), 0);
+    // 指定输出文件路径时不使用资源文件名
+    let output_file = output_dir.join("escape.txt");
+    export_resource(&target_file, "escape", &output_file, &options, &mut |_| {}).unwrap();
+    assert_eq!(fs::read(&output_file).unwrap(), b"data");
+    // 显式允许时按原样使用
+    let options = ExportOptions {
+        allow_unsafe_names: true,
+    };
+    export_resource(&target_file, "escape", &output_dir, &options, &mut |_| {}).unwrap();
+    assert_eq!(fs::read(test_dir.join("escape.txt")).unwrap(), b"data");
+
+    // 安装器同样检查资源文件名
+    let install_dir = test_dir.join("install");
+    let result = Installer::new(&target_file, &install_dir)
+        .resource("device")
+        .install(&mut |_| {});
+    assert!(result.is_err());
+    assert!(!install_dir.exists());
+    Installer::new(&target_file, &install_dir)
+        .resource_to("device", "device.txt")
+        .install(&mut |_| {})
+        .unwrap();
+}
+
+/// 测试解析不可信的资源头(伪造的长度前缀与字段)
+#[test]
+fn test_hostile_headers() {
+    let head = ResourceHead::new("id", 27, 27, "a.bin", CompressMode::None).unwrap();
+    let valid = head.to_bytes().unwrap();
+    assert!(ResourceHead::from(&valid).is_ok());
+
+    // 字符串长度前缀远超资源头大小
+    let mut data = u64::MAX.to_le_bytes().to_vec();
+    data.extend_from_slice(&valid[8..]);
+    let e = ResourceHead::from(&data).unwrap_err();
+    assert!(matches!(e, Error::Corrupted { .. }), "{:?}", e);
+    let mut data = valid.clone();
+    data[13..21].copy_from_slice(&(1u64 << 40).to_le_bytes());
+    assert!(matches!(
+        ResourceHead::from(&data),
+        Err(Error::Corrupted { .. })
+    ));
+
+    // 分卷序号溢出(分卷信息位于资源头末尾)
+    let mut data = valid.clone();
+    let len = data.len();
+    data[len - 8..].fill(0xFF);
+    assert!(matches!(
+        ResourceHead::from(&data),
+        Err(Error::Corrupted { .. })
+    ));
+
+    // 无法表示的修改时间与特殊权限位
+    for metadata in [
+        Metadata {
+            modified: Some(u64::MAX),
+            ..Metadata::default()
+        },
+        Metadata {
+            permissions: Some(0o4755),
+            ..Metadata::default()
+        },
+    ] {
+        let data = head.clone().with_metadata(metadata).to_bytes().unwrap();
+        assert!(matches!(
+            ResourceHead::from(&data),
+            Err(Error::Corrupted { .. })
+        ));
+    }
+
+    // 字段长度与数值超出范围
+    #[derive(serde::Serialize)]
+    struct Legacy {
+        version: String,
+        id: String,
+        name: String,
+        length: String,
+        size: String,
+        compress: CompressMode,
+    }
+    let legacy = |id: &str, length: &str| {
+        bincode::serialize(&Legacy {
+            version: "1.0.0".to_string(),
+            id: id.to_string(),
+            name: "old.bin".to_string(),
+            length: length.to_string(),
+            size: "0000000000000010".to_string(),
+            compress: CompressMode::None,
+        })
+        .unwrap()
+    };
+    assert!(ResourceHead::from(&legacy("old", "0000000000000010")).is_ok());
+    for data in [
+        legacy(&"x".repeat(65), "0000000000000010"),
+        legacy("old", "18446744073709551615"),
+        legacy("old", "-1"),
+        legacy("old", ""),
+        legacy("old", "0x10"),
+    ] {
+        assert!(matches!(
+            ResourceHead::from(&data),
+            Err(Error::Corrupted { .. })
+        ));
+    }
+}
+
+/// 简单的伪随机数生成器(xorshift64，固定种子保证测试可复现)
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n.max(1) as u64) as usize
+    }
+}
+
+/// 模糊测试：随机破坏资源文件后，解析与扫描只返回错误而不会 panic
+#[test]
+fn test_fuzz_parser_and_scanners() {
+    use crate::core::RESOURCE_MAGIC;
+
+    let test_dir = TestDir::new("fuzz");
+    let target_file = test_dir.join("target.bin");
+    let nested_file = test_dir.join("nested.bin");
+    let fuzz_file = test_dir.join("fuzz.bin");
+    let content: Vec<u8> = (0..50_000u32).map(|i| (i * 7 % 251) as u8).collect();
+    let source_file = test_dir.join("source.bin");
+    fs::write(&source_file, &content).unwrap();
+
+    // 包含各种存储方式的资源文件
+    fs::write(&nested_file, b"inner host").unwrap();
+    let options = AddOptions::default();
+    add_resource_from_reader(
+        &nested_file,
+        &mut &b"inner"[..],
+        "inner",
+        &options,
+        &mut |_| {},
+    )
+    .unwrap();
+    fs::write(&target_file, b"host program").unwrap();
+    let compressed = AddOptions {
+        compression_grade: Some(6),
+        ..AddOptions::default()
+    };
+    let chunked = AddOptions {
+        chunked: true,
+        ..AddOptions::default()
+    };
+    let split = AddOptions {
+        split_size: Some(4_000),
+        ..AddOptions::default()
+    };
+    add_resource(&target_file, &source_file, "plain", &options, &mut |_| {}).unwrap();
+    add_resource(&target_file, &source_file, "copy", &compressed, &mut |_| {}).unwrap();
+    add_resource(
+        &target_file,
+        &nested_file,
+        "nested",
+        &compressed,
+        &mut |_| {},
+    )
+    .unwrap();
+    add_resource_from_reader(
+        &target_file,
+        &mut &content[..20_000],
+        "chunks",
+        &chunked,
+        &mut |_| {},
+    )
+    .unwrap();
+    add_resource_from_reader(
+        &target_file,
+        &mut &content[..10_000],
+        "split",
+        &split,
+        &mut |_| {},
+    )
+    .unwrap();
+    let original = fs::read(&target_file).unwrap();
+    let ids = ["plain", "copy", "nested", "nested/inner", "chunks", "split"];
+    // 资源头位置(多数修改落在资源头内)
+    let headers: Vec<usize> = original
+        .windows(RESOURCE_MAGIC.len())
+        .enumerate()
+        .filter(|(_, w)| *w == RESOURCE_MAGIC)
+        .map(|(pos, _)| pos + RESOURCE_MAGIC.len())
+        .collect();
+
+    let mut rng = XorShift(0x5EED_1234_ABCD_0001);
+    for iteration in 0..300 {
+        let mut data = original.clone();
+        for _ in 0..=rng.below(4) {
+            let pos = if rng.below(4) == 0 {
+                rng.below(data.len())
+            } else {
+                (headers[rng.below(headers.len())] + rng.below(300)).min(data.len() - 1)
+            };
+            match rng.below(4) {
+                0 => data[pos] ^= 1 << rng.below(8),
+                1 => data[pos] = rng.next() as u8,
+                2 => {
+                    let end = (pos + 8).min(data.len());
+                    data[pos..end].fill(0xFF);
+                }
+                _ => data.truncate(pos.max(1)),
+            }
+        }
+        fs::write(&fuzz_file, &data).unwrap();
+
+        let result = std::panic::catch_unwind(|| {
+            let _ = ResourceHead::from(&data[data.len().min(16)..]);
+            let _ = find_resources_config(&fuzz_file, |_, _| {}, &mut |_| {});
+            let _ = inspect_resources(&fuzz_file, &mut |_| {});
+            let _ = find_nested_resources(&fuzz_file, &mut |_| {});
+            for id in ids {
+                let _ = verify_resource(&fuzz_file, id, &mut |_| {});
+            }
+        });
+        assert!(result.is_ok(), "iteration {} panicked", iteration);
+    }
+
+    // 随机字节作为资源头
+    for _ in 0..2000 {
+        let mut data = vec![0u8; rng.below(256)];
+        data.iter_mut().for_each(|b| *b = rng.next() as u8);
+        let _ = ResourceHead::from(&data);
+    }
+}
+
+/// 测试通配符与正则表达式选择器
+#[test]
+fn test_selectors() {
+    let glob = |pattern: &str| Selector::glob(pattern).unwrap();
+    assert!(glob("lang/*").matches("lang/en"));
+    assert!(!glob("lang/*").matches("lang/en/extra"));
+    assert!(!glob("lang/*").matches("language"));
+    assert!(glob("lang/**").matches("lang/en/extra"));
+    assert!(glob("**.json").matches("a/b/c.json"));
+    assert!(glob("*").matches(""));
+    assert!(glob("file?.bin").matches("file1.bin"));
+    assert!(!glob("file?.bin").matches("file10.bin"));
+    assert!(glob("v[0-9].[!x]").matches("v2.a"));
+    assert!(!glob("v[0-9].[!x]").matches("v2.x"));
+    assert!(glob("[]a]").matches("]"));
+    assert!(glob("[a-]").matches("-"));
+    assert!(glob("\\*").matches("*"));
+    assert!(!glob("\\*").matches("a"));
+    assert!(glob("a*b*c").matches("aXbYbZc"));
+    assert!(!glob("a*b*c").matches("aXbY"));
+    assert!(glob("lang/*").nested() && !glob("*.json").nested());
+    for pattern in ["[abc", "[z-a]", "a\\"] {
+        assert!(matches!(
+            Selector::glob(pattern),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+
+    let regex = Selector::regex("^lang/(en|fr)$").unwrap();
+    assert!(regex.matches("lang/fr") && !regex.matches("lang/de"));
+    assert!(Selector::regex("en").unwrap().matches("lang/en/x"));
+    assert!(matches!(Selector::regex("("), Err(Error::InvalidInput(_))));
+    assert!(Selector::Exact("id".to_string()).matches(" id "));
+}
+
+/// 测试按模式释放与删除资源
+#[test]
+fn test_select_export_remove() {
+    let test_dir = TestDir::new("select");
+    let output_dir = test_dir.join("out");
+    fs::create_dir_all(&output_dir).unwrap();
+    let target_file = test_dir.join("target.bin");
+    let host = b"host program".to_vec();
+    fs::write(&target_file, &host).unwrap();
+    let add = |id: &str, name: &str, data: &[u8], options: &AddOptions| {
+        let options = AddOptions {
+            name: Some(name.to_string()),
+            ..options.clone()
+        };
+        add_resource_from_reader(&target_file, &mut &data[..], id, &options, &mut |_| {}).unwrap();
+    };
+    let plain = AddOptions::default();
+    let chunked = AddOptions {
+        chunked: true,
+        ..AddOptions::default()
+    };
+    let big: Vec<u8> = (0..200_000u32).map(|i| (i * 31 % 253) as u8).collect();
+
+    // 嵌套容器
+    let container = test_dir.join("container.bin");
+    fs::write(&container, b"inner host").unwrap();
+    let options = AddOptions {
+        name: Some("inner.txt".to_string()),
+        ..AddOptions::default()
+    };
+    add_resource_from_reader(
+        &container,
+        &mut &b"inner"[..],
+        "inner",
+        &options,
+        &mut |_| {},
+    )
+    .unwrap();
+    add("lang/en", "en.json", b"english", &plain);
+    add("lang/fr", "fr.json", b"french", &plain);
+    add("copy", "copy.json", b"english", &plain);
+    add("big/a", "a.bin", &big, &chunked);
+    add("big/b", "b.bin", &big[..150_000], &chunked);
+    add("pack", "pack.bin", &fs::read(&container).unwrap(), &plain);
+
+    let options = ExportOptions::default();
+    let exported = export_resources(
+        &target_file,
+        &Selector::glob("lang/*").unwrap(),
+        &output_dir,
+        &options,
+        &mut |_| {},
+    )
+    .unwrap();
+    assert_eq!(exported.len(), 2);
+    assert_eq!(fs::read(output_dir.join("en.json")).unwrap(), b"english");
+    assert_eq!(fs::read(output_dir.join("fr.json")).unwrap(), b"french");
+
+    // 嵌套资源
+    let selector = Selector::regex("^pack/").unwrap();
+    let exported =
+        export_resources(&target_file, &selector, &output_dir, &options, &mut |_| {}).unwrap();
+    assert_eq!(exported[0].0, "pack/inner");
+    assert_eq!(fs::read(output_dir.join("inner.txt")).unwrap(), b"inner");
+
+    // 文件名冲突时不保留已释放的文件
+    add("dup", "en.json", b"other", &plain);
+    fs::remove_dir_all(&output_dir).unwrap();
+    fs::create_dir_all(&output_dir).unwrap();
+    let selector = Selector::regex("en|dup").unwrap();
+    assert!(matches!(
+        export_resources(&target_file, &selector, &output_dir, &options, &mut |_| {}),
+        Err(Error::InvalidInput(_))
+    ));
+    assert_eq!(fs::read_dir(&output_dir).unwrap().count(), 0);
+    let selector = Selector::glob("missing*").unwrap();
+    assert!(matches!(
+        export_resources(&target_file, &selector, &output_dir, &options, &mut |_| {}),
+        Err(Error::NotFound(_))
+    ));
+
+    // 删除被引用的资源时引用资源改为存储数据，预演与实际结果一致
+    let check = |selector: &Selector, expected: &[&str]| {
+        let plan = plan_remove_resources(&target_file, selector, None, &mut |_| {}).unwrap();
+        let removed = remove_resources(&target_file, selector, None, &mut |_| {}).unwrap();
+        assert_eq!(removed, expected);
+        assert_eq!(fs::metadata(&target_file).unwrap().len(), plan.new_len);
+        for config in find_resources_config(&target_file, |_, _| {}, &mut |_| {}).unwrap() {
+            verify_resource(&target_file, config.id(), &mut |_| {}).unwrap();
+        }
+    };
+    check(
+        &Selector::regex("^(lang/|dup)").unwrap(),
+        &["lang/en", "lang/fr", "dup"],
+    );
+    let mut data = Vec::new();
+    export_resource_to_writer(&target_file, "copy", &mut data, &mut |_| {}).unwrap();
+    assert_eq!(data, b"english");
+
+    // 删除共享数据块的全部分块资源时一并删除数据块
+    check(&Selector::glob("big/*").unwrap(), &["big/a", "big/b"]);
+    check(&Selector::glob("*").unwrap(), &["pack", "copy"]);
+    assert_eq!(fs::read(&target_file).unwrap(), host);
+}
+
+/// 测试资源容器：多个修改一次提交，追加时去重，重写时提升引用并重命名差异资源的旧版本，失败时不修改文件
+#[test]
+fn test_container() {
+    let test_dir = TestDir::new("container");
+    let target_file = test_dir.join("host.bin");
+    fs::write(&target_file, b"host").unwrap();
+
+    let mut state: u32 = 3;
+    let mut random = |len: usize| -> Vec<u8> {
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                (state >> 16) as u8
+            })
+            .collect()
+    };
+    let shared = random(50_000);
+    let extra = random(10_000);
+    let v1 = random(64 * 1024);
+    let mut v2 = v1.clone();
+    v2[30_000] ^= 0xFF;
+    fs::write(test_dir.join("shared.bin"), &shared).unwrap();
+    fs::write(test_dir.join("v2.bin"), &v2).unwrap();
+    let options = AddOptions::default();
+    let list = || -> Vec<(String, Storage)> {
+        find_resources_config(&target_file, |_, _| {}, &mut |_| {})
+            .unwrap()
+            .iter()
+            .map(|c| (c.id().to_string(), c.storage()))
+            .collect()
+    };
+    let export = |id: &str| {
+        let mut exported = Vec::new();
+        export_resource_to_writer(&target_file, id, &mut exported, &mut |_| {}).unwrap();
+        exported
+    };
+
+    // 只增加资源时追加到文件末尾，同一次提交中的重复数据改为引用
+    let mut container = Container::open(&target_file).unwrap();
+    container
+        .add_reader(std::io::Cursor::new(shared.clone()), "a", &options)
+        .unwrap()
+        .add("shared.bin", "b", &options)
+        .unwrap()
+        .add_reader(std::io::Cursor::new(v1.clone()), "app", &options)
+        .unwrap();
+    assert!(matches!(
+        container.add("shared.bin", "a", &options),
+        Err(Error::DuplicateId(_))
+    ));
+    assert!(container.is_modified());
+    container.commit(None, &mut |_| {}).unwrap();
+    assert_eq!(
+        list(),
+        [
+            ("a".to_string(), Storage::Inline),
+            ("b".to_string(), Storage::Reference),
+            ("app".to_string(), Storage::Inline)
+        ]
+    );
+    update_resource(
+        &target_file,
+        &test_dir.join("v2.bin"),
+        "app",
+        &options,
+        true,
+        &mut |_| {},
+    )
+    .unwrap();
+
+    // 删除、重命名与增加一次重写
+    let mut container = Container::open(&target_file).unwrap();
+    container
+        .remove("a")
+        .unwrap()
+        .rename("app", "launcher")
+        .unwrap()
+        .add_reader(std::io::Cursor::new(extra.clone()), "c", &options)
+        .unwrap()
+        .rename("c", "extra")
+        .unwrap();
+    assert!(matches!(container.remove("a"), Err(Error::NotFound(_))));
+    assert!(matches!(
+        container.rename("b", "launcher"),
+        Err(Error::DuplicateId(_))
+    ));
+    assert!(matches!(
+        container.rename("b", " "),
+        Err(Error::InvalidId(_))
+    ));
+    assert_eq!(container.ids(), ["b", "launcher", "extra"]);
+    container.commit(None, &mut |_| {}).unwrap();
+    assert_eq!(
+        list(),
+        [
+            ("launcher".to_string(), Storage::Delta),
+            ("b".to_string(), Storage::Inline),
+            ("extra".to_string(), Storage::Inline)
+        ]
+    );
+    assert_eq!(export("launcher"), v2);
+    assert_eq!(export("b"), shared);
+    assert_eq!(export("extra"), extra);
+
+    // 提交失败时目标文件保持不变，已有的同名临时文件不会被覆盖或删除
+    let before = fs::read(&target_file).unwrap();
+    fs::write(test_dir.join("host.bin.tmp"), b"user data").unwrap();
+    let mut container = Container::open(&target_file).unwrap();
+    container
+        .remove("b")
+        .unwrap()
+        .add("missing.bin", "missing", &options)
+        .unwrap();
+    assert!(container.commit(None, &mut |_| {}).is_err());
+    assert_eq!(fs::read(&target_file).unwrap(), before);
+    assert_eq!(
+        fs::read(test_dir.join("host.bin.tmp")).unwrap(),
+        b"user data"
+    );
+    fs::remove_file(test_dir.join("host.bin.tmp")).unwrap();
+    let mut files = fs::read_dir(&test_dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name());
+    assert!(files.all(|name| !name.to_string_lossy().ends_with(".tmp")));
+
+    // 更新资源：删除旧版本，新版本追加到文件末尾
+    let mut container = Container::open(&target_file).unwrap();
+    container.update("v2.bin", "extra", &options).unwrap();
+    container.commit(None, &mut |_| {}).unwrap();
+    assert_eq!(list().last().unwrap().0, "extra");
+    assert_eq!(export("extra"), v2);
+
+    // 删除全部资源(包括重命名后的旧版本)
+    let mut container = Container::open(&target_file).unwrap();
+    for id in ["launcher", "b", "extra"] {
+        container.remove(id).unwrap();
+    }
+    container.commit(None, &mut |_| {}).unwrap();
+    assert_eq!(fs::read(&target_file).unwrap(), b"host");
+}
+
+/// 测试内存后端：容器修改与读取均不访问磁盘
+#[test]
+fn test_memory_backend() {
+    let shared: Vec<u8> = (0..40_000u32).map(|i| (i * 7 % 251) as u8).collect();
+    let mut buffer = std::io::Cursor::new(b"host".to_vec());
+    let options = AddOptions::default();
+
+    // 只增加资源时追加到末尾，重复数据改为引用
+    let mut container = Container::from_backend(&mut buffer).unwrap();
+    container
+        .add_reader(std::io::Cursor::new(shared.clone()), "a", &options)
+        .unwrap()
+        .add_reader(std::io::Cursor::new(shared.clone()), "b", &options)
+        .unwrap()
+        .add_reader(std::io::Cursor::new(b"hello".to_vec()), "c", &options)
+        .unwrap();
+    container.commit(None, &mut |_| {}).unwrap();
+    let list = |data: &[u8]| -> Vec<(String, Storage)> {
+        find_resources_config_from_reader(std::io::Cursor::new(data), &mut |_| {})
+            .unwrap()
+            .iter()
+            .map(|c| (c.id().to_string(), c.storage()))
+            .collect()
+    };
+    assert_eq!(
+        list(buffer.get_ref()),
+        [
+            ("a".to_string(), Storage::Inline),
+            ("b".to_string(), Storage::Reference),
+            ("c".to_string(), Storage::Inline)
+        ]
+    );
+
+    let container = Container::from_backend(&mut buffer).unwrap();
+    assert!(matches!(
+        container.commit(Some(std::path::Path::new("out.bin")), &mut |_| {}),
+        Err(Error::Unsupported(_))
+    ));
+
+    // 删除与重命名在内存中重写后写回(被引用的资源移到末尾)
+    let mut container = Container::from_backend(&mut buffer).unwrap();
+    container
+        .remove("a")
+        .unwrap()
+        .rename("c", "greeting")
+        .unwrap();
+    container.commit(None, &mut |_| {}).unwrap();
+    assert_eq!(
+        list(buffer.get_ref()),
+        [
+            ("greeting".to_string(), Storage::Inline),
+            ("b".to_string(), Storage::Inline)
+        ]
+    );
+    let mut exported = Vec::new();
+    export_resource_from_reader(&mut buffer, "b", &mut exported, &mut |_| {}).unwrap();
+    assert_eq!(exported, shared);
+    verify_resource_from_reader(&mut buffer, "greeting", &mut |_| {}).unwrap();
+
+    // 分卷资源不支持读写后端，提交失败时数据保持不变
+    let before = buffer.get_ref().clone();
+    let split = AddOptions {
+        split_size: Some(1024),
+        ..AddOptions::default()
+    };
+    let mut container = Container::from_backend(&mut buffer).unwrap();
+    container
+        .add_reader(std::io::Cursor::new(shared.clone()), "split", &split)
+        .unwrap();
+    assert!(matches!(
+        container.commit(None, &mut |_| {}),
+        Err(Error::Unsupported(_))
+    ));
+    assert_eq!(buffer.get_ref(), &before);
+
+    // 删除全部资源后只剩宿主数据
+    let mut container = Container::from_backend(&mut buffer).unwrap();
+    container.remove("b").unwrap().remove("greeting").unwrap();
+    container.commit(None, &mut |_| {}).unwrap();
+    assert_eq!(buffer.get_ref(), b"host");
+}
+
+/// 测试读写后端上的增加、更新、检查、删除与剥离
+#[test]
+fn test_backend_operations() {
+    let test_dir = TestDir::new("backend_operations");
+    let v1_file = test_dir.join("v1.txt");
+    let v2_file = test_dir.join("v2.txt");
+    fs::write(&v1_file, b"version 1").unwrap();
+    fs::write(&v2_file, b"version 2, longer").unwrap();
+    let options = AddOptions::default();
+
+    let mut target = b"host program".to_vec();
+    add_resource_to_backend(
+        &mut Cursor::new(&mut target),
+        &v1_file,
+        "res",
+        &options,
+        &mut |_| {},
+    )
+    .unwrap();
+    add_in_memory(&mut target, b"other", "other", &options).unwrap();
+    update_resource_in_backend(
+        &mut Cursor::new(&mut target),
+        &v2_file,
+        "res",
+        &options,
+        &mut |_| {},
+    )
+    .unwrap();
+    assert_eq!(
+        export_in_memory(&target, "res").unwrap(),
+        b"version 2, longer"
+    );
+    let infos = inspect_resources_from_reader(Cursor::new(&target), &mut |_| {}).unwrap();
+    assert_eq!(infos.len(), 2);
+    assert_eq!(infos[0].offset, 12);
+    assert!(infos.iter().all(|info| info.end_marker));
+
+    // 输出路径只支持目标文件
+    let output = AddOptions {
+        output_path: Some(test_dir.join("out.bin")),
+        ..AddOptions::default()
+    };
+    assert!(matches!(
+        add_resource_to_backend(
+            &mut Cursor::new(&mut target),
+            &v1_file,
+            "new",
+            &output,
+            &mut |_| {}
+        ),
+        Err(Error::Unsupported(_))
+    ));
+
+    // 已打开的文件同样可以作为读写后端
+    let host_file = test_dir.join("host.bin");
+    fs::write(&host_file, &target).unwrap();
+    let mut file = fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(&host_file)
+        .unwrap();
+    remove_resource_from_backend(&mut file, "other", &mut |_| {}).unwrap();
+    let (host_len, stripped) = strip_resources_from_backend(&mut file, &mut |_| {}).unwrap();
+    drop(file);
+    assert_eq!(host_len, 12);
+    assert_eq!(stripped.len(), 1);
+    assert_eq!(stripped[0].id(), "res");
+    assert_eq!(fs::read(&host_file).unwrap(), b"host program");
 }
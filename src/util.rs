@@ -1,55 +1,407 @@
-use anyhow::{anyhow, Result};
-use flate2::write::GzDecoder;
+use crate::error::{Error, Result};
+use flate2::read::MultiGzDecoder;
 use flate2::write::GzEncoder;
 use flate2::Compression;
+use sha2::{Digest, Sha256};
 use std::cmp::Ordering;
-use std::fs::File;
-use std::io::copy;
-use std::io::BufReader;
+use std::fs;
+use std::io::Read;
+use std::io::Write;
+use std::io::{self, copy};
 use std::path::Path;
+use std::thread;
+use std::time::{Duration, UNIX_EPOCH};
 
-/// 压缩文件
+/// 可还原的权限位(不含 setuid、setgid 与 sticky 位)
+pub const PERMISSION_BITS: u32 = 0o777;
+
+/// 多线程压缩时每个数据块的大小（4MB）
+pub const COMPRESSION_BLOCK_SIZE: usize = 1024 * 1024 * 4;
+
+/// 内容定义分块的最小块大小（16KB）
+pub const CHUNK_MIN_SIZE: usize = 1024 * 16;
+
+/// 内容定义分块的最大块大小（256KB）
+pub const CHUNK_MAX_SIZE: usize = 1024 * 256;
+
+/// 内容定义分块的切分掩码（平均块大小约 64KB）
+const CHUNK_MASK: u64 = 0xFFFF << 48;
+
+/// Gear 滚动哈希表（由固定种子生成，保证不同版本切分结果一致）
+const GEAR_TABLE: [u64; 256] = {
+    let mut table = [0u64; 256];
+    let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+    let mut i = 0;
+    while i < 256 {
+        // splitmix64
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
+};
+
+/// Windows 保留的设备名(不区分大小写，带扩展名时同样保留)
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// 文件魔数与 MIME 类型对照表
+const MIME_SIGNATURES: &[(&[u8], &str)] = &[
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xff\xd8\xff", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"BM", "image/bmp"),
+    (b"\x00\x00\x01\x00", "image/x-icon"),
+    (b"%PDF-", "application/pdf"),
+    (b"PK\x03\x04", "application/zip"),
+    (b"\x1f\x8b", "application/gzip"),
+    (b"7z\xbc\xaf\x27\x1c", "application/x-7z-compressed"),
+    (b"Rar!\x1a\x07", "application/vnd.rar"),
+    (b"\xfd7zXZ\x00", "application/x-xz"),
+    (b"BZh", "application/x-bzip2"),
+    (b"\x28\xb5\x2f\xfd", "application/zstd"),
+    (b"MZ", "application/vnd.microsoft.portable-executable"),
+    (b"\x7fELF", "application/x-executable"),
+    (b"ID3", "audio/mpeg"),
+    (b"OggS", "audio/ogg"),
+    (b"fLaC", "audio/flac"),
+    (b"SQLite format 3\x00", "application/vnd.sqlite3"),
+];
+
+/// 文件扩展名与 MIME 类型对照表
+const MIME_EXTENSIONS: &[(&str, &str)] = &[
+    ("txt", "text/plain"),
+    ("html", "text/html"),
+    ("htm", "text/html"),
+    ("css", "text/css"),
+    ("js", "text/javascript"),
+    ("json", "application/json"),
+    ("xml", "application/xml"),
+    ("csv", "text/csv"),
+    ("ini", "text/plain"),
+    ("svg", "image/svg+xml"),
+    ("webp", "image/webp"),
+    ("wav", "audio/wav"),
+    ("mp4", "video/mp4"),
+    ("dll", "application/vnd.microsoft.portable-executable"),
+    ("exe", "application/vnd.microsoft.portable-executable"),
+    ("msi", "application/x-msi"),
+];
+
+/// 压缩数据流
 ///
 /// # 参数
-/// - `file_path`: 压缩文件路径
-/// - `output_path`: 输出路径
+/// - `input`: 输入数据流
+/// - `output`: 输出数据流
 /// - `compression_grade`: 压缩等级(0-9)
 ///     - 0: 不压缩
 ///     - 1: 为优化编码的最佳速度
 ///     - 9: 针对正在编码的数据大小进行优化。
 ///
 /// # 返回值
-/// - `Ok(())`: 成功
-/// - `Err(anyhow!("Error message"))`: 失败
-pub fn compression_file(
-    file_path: &Path,
-    output_path: &Path,
+/// - `Ok(u64)`: 压缩后的字节数
+/// - `Err(err)`: 失败
+pub fn compression_stream<R: Read + ?Sized, W: Write>(
+    input: &mut R,
+    output: W,
     compression_grade: u32,
-) -> Result<()> {
-    let mut input = BufReader::new(File::open(file_path)?);
-    let output = File::create(output_path)?;
-    let mut encoder = GzEncoder::new(output, Compression::new(compression_grade));
-    copy(&mut input, &mut encoder)?;
-    encoder.finish()?;
-    Ok(())
+) -> Result<u64> {
+    let mut encoder = GzEncoder::new(
+        CountingWriter::new(output),
+        Compression::new(compression_grade),
+    );
+    copy(input, &mut encoder)?;
+    Ok(encoder.finish()?.count())
 }
 
-/// 还原压缩文件
+/// 多线程压缩数据流
+///
+/// 数据按 [`COMPRESSION_BLOCK_SIZE`] 分块并行压缩，每块为独立的 gzip 成员并按顺序拼接(与 pigz 相同)，
+/// 可由 [`decompress_stream`] 直接还原。
 ///
 /// # 参数
-/// - `file_path`: 压缩文件路径
-/// - `output_path`: 输出路径
+/// - `input`: 输入数据流
+/// - `output`: 输出数据流
+/// - `compression_grade`: 压缩等级(0-9)
+/// - `threads`: 压缩线程数
 ///
 /// # 返回值
-/// - `Ok(())`: 成功
-/// - `Err(anyhow!("Error message"))`: 失败
-pub fn decompress_file(file_path: &Path, output_path: &Path) -> Result<()> {
-    let mut input = BufReader::new(File::open(file_path)?);
-    let output = File::create(output_path)?;
-    let mut decoder = GzDecoder::new(output);
-    copy(&mut input, &mut decoder)?;
-    decoder.finish()?;
-    Ok(())
+/// - `Ok(u64)`: 压缩后的字节数
+/// - `Err(err)`: 失败
+pub fn parallel_compression_stream<R: Read + ?Sized, W: Write>(
+    input: &mut R,
+    mut output: W,
+    compression_grade: u32,
+    threads: usize,
+) -> Result<u64> {
+    let threads = threads.max(1);
+    let mut written = 0;
+    loop {
+        // 读取一批数据块（每个线程一块）
+        let mut blocks = Vec::with_capacity(threads);
+        for _ in 0..threads {
+            let mut block = Vec::with_capacity(COMPRESSION_BLOCK_SIZE);
+            input
+                .take(COMPRESSION_BLOCK_SIZE as u64)
+                .read_to_end(&mut block)?;
+            if block.is_empty() {
+                break;
+            }
+            blocks.push(block);
+        }
+        let last_batch = blocks.len() < threads;
+        if blocks.is_empty() {
+            if written > 0 {
+                break;
+            }
+            // 空数据也输出一个 gzip 成员，保证结果可被解压
+            blocks.push(Vec::new());
+        }
+
+        // 并行压缩并按顺序写出
+        let members = thread::scope(|scope| {
+            let handles: Vec<_> = blocks
+                .iter()
+                .map(|block| {
+                    scope.spawn(move || -> std::io::Result<Vec<u8>> {
+                        let mut encoder = GzEncoder::new(
+                            Vec::with_capacity(block.len() / 2),
+                            Compression::new(compression_grade),
+                        );
+                        encoder.write_all(block)?;
+                        encoder.finish()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .map_err(|_| io::Error::other("Compression thread panicked"))?
+                        .map_err(Into::into)
+                })
+                .collect::<Result<Vec<_>>>()
+        })?;
+        for member in members {
+            output.write_all(&member)?;
+            written += member.len() as u64;
+        }
+        if last_batch {
+            break;
+        }
+    }
+    Ok(written)
+}
+
+/// 还原压缩数据流
+///
+/// 支持由多个 gzip 成员拼接而成的数据(多线程压缩结果)。
+///
+/// # 参数
+/// - `input`: 压缩数据流
+/// - `output`: 输出数据流
+///
+/// # 返回值
+/// - `Ok(u64)`: 还原后的字节数
+/// - `Err(err)`: 失败
+pub fn decompress_stream<R: Read, W: Write + ?Sized>(input: R, output: &mut W) -> Result<u64> {
+    let mut decoder = MultiGzDecoder::new(input);
+    copy(&mut decoder, output).map_err(|e| match e.kind() {
+        // 压缩数据无效
+        io::ErrorKind::InvalidData | io::ErrorKind::InvalidInput => {
+            Error::corrupted(None, format!("Invalid compressed data ({})", e))
+        }
+        _ => e.into(),
+    })
+}
+
+/// 统计读取字节数的数据流
+pub struct CountingReader<R> {
+    inner: R,
+    count: u64,
+}
+
+impl<R: Read> CountingReader<R> {
+    pub fn new(inner: R) -> Self {
+        CountingReader { inner, count: 0 }
+    }
+
+    /// 获取已读取的字节数
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+}
+
+/// 统计写入字节数的数据流
+pub struct CountingWriter<W> {
+    inner: W,
+    count: u64,
+}
+
+impl<W: Write> CountingWriter<W> {
+    pub fn new(inner: W) -> Self {
+        CountingWriter { inner, count: 0 }
+    }
+
+    /// 获取已写入的字节数
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// 内容定义分块（Gear 滚动哈希）
+///
+/// 切分点只取决于附近的数据内容，插入或删除数据只影响相邻的数据块，
+/// 因此相似数据的大部分数据块相同。
+pub struct ContentChunker<R> {
+    inner: R,
+    buffer: Vec<u8>,
+    eof: bool,
+}
+
+impl<R: Read> ContentChunker<R> {
+    pub fn new(inner: R) -> Self {
+        ContentChunker {
+            inner,
+            buffer: Vec::with_capacity(CHUNK_MAX_SIZE),
+            eof: false,
+        }
+    }
+
+    /// 读取下一个数据块
+    ///
+    /// # 返回值
+    /// - `Ok(Some(Vec<u8>))`: 数据块
+    /// - Ok(None): 数据读取完毕
+    /// - `Err(err)`: 失败
+    pub fn next_chunk(&mut self) -> Result<Option<Vec<u8>>> {
+        // 补足一个最大块的数据
+        while !self.eof && self.buffer.len() < CHUNK_MAX_SIZE {
+            let wanted = (CHUNK_MAX_SIZE - self.buffer.len()) as u64;
+            let n = (&mut self.inner)
+                .take(wanted)
+                .read_to_end(&mut self.buffer)?;
+            self.eof = n == 0;
+        }
+        if self.buffer.is_empty() {
+            return Ok(None);
+        }
+
+        let mut hash: u64 = 0;
+        let mut cut = self.buffer.len();
+        for (i, &byte) in self.buffer.iter().enumerate() {
+            hash = (hash << 1).wrapping_add(GEAR_TABLE[byte as usize]);
+            if i + 1 >= CHUNK_MIN_SIZE && hash & CHUNK_MASK == 0 {
+                cut = i + 1;
+                break;
+            }
+        }
+        let rest = self.buffer.split_off(cut);
+        Ok(Some(std::mem::replace(&mut self.buffer, rest)))
+    }
+}
+
+/// 计算 SHA-256 校验和的读取流
+pub struct HashingReader<R> {
+    inner: R,
+    hasher: Sha256,
+}
+
+impl<R: Read> HashingReader<R> {
+    pub fn new(inner: R) -> Self {
+        HashingReader {
+            inner,
+            hasher: Sha256::new(),
+        }
+    }
+
+    /// 获取内部数据流
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// 获取已读取数据的校验和(十六进制)
+    pub fn checksum(self) -> String {
+        format!("{:x}", self.hasher.finalize())
+    }
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+/// 计算 SHA-256 校验和的写入流
+pub struct HashingWriter<W> {
+    inner: W,
+    hasher: Sha256,
+}
+
+impl<W: Write> HashingWriter<W> {
+    pub fn new(inner: W) -> Self {
+        HashingWriter {
+            inner,
+            hasher: Sha256::new(),
+        }
+    }
+
+    /// 获取已写入数据的校验和(十六进制)
+    pub fn checksum(self) -> String {
+        format!("{:x}", self.hasher.finalize())
+    }
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// 计算数据的 SHA-256 校验和
+///
+/// # 参数
+/// - `data`: 数据
+///
+/// # 返回值
+/// - `String`: 校验和(十六进制)
+pub fn checksum(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
 }
 
 /// 比较版本号大小
@@ -71,16 +423,16 @@ pub fn compare_version(version1: &str, version2: &str) -> Result<Ordering> {
     // 比较版本
     for i in 0..std::cmp::max(n1, n2) {
         let i1 = if i < n1 {
-            nums1[i]
-                .parse::<i32>()
-                .map_err(|_| anyhow!("Invalid version number: {}", nums1[i]))?
+            nums1[i].parse::<i32>().map_err(|_| {
+                Error::corrupted(None, format!("Invalid version number: {}", nums1[i]))
+            })?
         } else {
             0
         };
         let i2 = if i < n2 {
-            nums2[i]
-                .parse::<i32>()
-                .map_err(|_| anyhow!("Invalid version number: {}", nums2[i]))?
+            nums2[i].parse::<i32>().map_err(|_| {
+                Error::corrupted(None, format!("Invalid version number: {}", nums2[i]))
+            })?
         } else {
             0
         };
@@ -95,3 +447,192 @@ pub fn compare_version(version1: &str, version2: &str) -> Result<Ordering> {
     // 版本相等
     Ok(Ordering::Equal)
 }
+
+/// 检测文件 MIME 类型
+///
+/// 优先根据文件魔数判断，无法识别时根据扩展名判断。
+///
+/// # 参数
+/// - `header`: 文件开头的字节(至少 16 字节以获得最佳结果)
+/// - `file_name`: 文件名
+///
+/// # 返回值
+/// - `String`: MIME 类型，无法识别时为 `application/octet-stream`
+pub fn detect_mime(header: &[u8], file_name: &str) -> String {
+    // RIFF 容器需要根据子类型区分
+    if header.len() >= 12 && header.starts_with(b"RIFF") {
+        match &header[8..12] {
+            b"WAVE" => return "audio/wav".to_string(),
+            b"WEBP" => return "image/webp".to_string(),
+            b"AVI " => return "video/x-msvideo".to_string(),
+            _ => {}
+        }
+    }
+    if header.len() >= 12 && &header[4..8] == b"ftyp" {
+        return "video/mp4".to_string();
+    }
+    if let Some((_, mime)) = MIME_SIGNATURES
+        .iter()
+        .find(|(signature, _)| header.starts_with(signature))
+    {
+        return mime.to_string();
+    }
+
+    let extension = Path::new(file_name)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    MIME_EXTENSIONS
+        .iter()
+        .find(|(ext, _)| *ext == extension)
+        .map(|(_, mime)| mime.to_string())
+        .unwrap_or_else(|| "application/octet-stream".to_string())
+}
+
+/// 检查资源文件名能否安全地用作输出目录中的文件名
+///
+/// 资源文件名来自文件中的资源头，可能被构造为跳出输出目录的路径。
+/// 为保证资源在各平台释放结果一致，按最严格的规则(Windows)检查。
+///
+/// # 参数
+/// - `name`: 资源文件名
+///
+/// # 返回值
+/// - Ok(())
+/// - Err(err): 文件名为空、包含路径分隔符或控制字符、为 `.`/`..`、绝对路径、
+///   以空格或 `.` 结尾，或为保留的设备名
+pub fn check_file_name(name: &str) -> Result<()> {
+    let reason = if name.is_empty() {
+        "name is empty"
+    } else if name.chars().any(char::is_control) {
+        "name contains control characters"
+    } else if name == "." || name == ".." {
+        "name refers to a directory"
+    } else if name.contains(['/', '\\']) {
+        "name contains path separators"
+    } else if name.contains(':') {
+        "name contains a drive or stream separator"
+    } else if name.ends_with(['.', ' ']) {
+        "name ends with a dot or space"
+    } else {
+        let stem = name.split('.').next().unwrap_or(name).trim_end();
+        if RESERVED_NAMES.iter().any(|r| r.eq_ignore_ascii_case(stem)) {
+            "name is a reserved device name"
+        } else {
+            return Ok(());
+        }
+    };
+    Err(Error::InvalidName(format!("{} ({:?})", reason, name)))
+}
+
+/// 获取文件修改时间
+///
+/// # 参数
+/// - `metadata`: 文件元数据
+///
+/// # 返回值
+/// - `Some(u64)`: Unix 时间戳(秒)
+/// - `None`: 系统不支持或时间早于 Unix 纪元
+pub fn file_modified(metadata: &fs::Metadata) -> Option<u64> {
+    metadata
+        .modified()
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|d| d.as_secs())
+}
+
+/// 获取文件权限位
+///
+/// # 参数
+/// - `metadata`: 文件元数据
+///
+/// # 返回值
+/// - `Some(u32)`: Unix 权限位(不含特殊位)，非 Unix 系统根据只读属性推算
+pub fn file_permissions(metadata: &fs::Metadata) -> Option<u32> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        Some(metadata.permissions().mode() & PERMISSION_BITS)
+    }
+    #[cfg(not(unix))]
+    {
+        Some(if metadata.permissions().readonly() {
+            0o444
+        } else {
+            0o644
+        })
+    }
+}
+
+/// 还原文件修改时间与权限
+///
+/// # 参数
+/// - `file_path`: 文件路径
+/// - `modified`: 修改时间(Unix 时间戳，秒)
+/// - `permissions`: 权限位(只还原 [`PERMISSION_BITS`])
+///
+/// # 返回值
+/// - `Ok(())`: 成功
+/// - `Err(err)`: 失败
+pub fn restore_file_attributes(
+    file_path: &Path,
+    modified: Option<u64>,
+    permissions: Option<u32>,
+) -> Result<()> {
+    if let Some(modified) = modified {
+        let modified = UNIX_EPOCH
+            .checked_add(Duration::from_secs(modified))
+            .ok_or_else(|| Error::corrupted(None, "Modification time out of range"))?;
+        let file = fs::OpenOptions::new().write(true).open(file_path)?;
+        file.set_modified(modified)?;
+    }
+    if let Some(mode) = permissions {
+        #[cfg(unix)]
+        let permissions = {
+            use std::os::unix::fs::PermissionsExt;
+            fs::Permissions::from_mode(mode & PERMISSION_BITS)
+        };
+        #[cfg(not(unix))]
+        let permissions = {
+            let mut permissions = fs::metadata(file_path)?.permissions();
+            permissions.set_readonly(mode & 0o222 == 0);
+            permissions
+        };
+        fs::set_permissions(file_path, permissions)?;
+    }
+    Ok(())
+}
+
+/// 格式化 Unix 时间戳为 UTC 时间
+///
+/// # 参数
+/// - `timestamp`: Unix 时间戳(秒)
+///
+/// # 返回值
+/// - `String`: `YYYY-MM-DD HH:MM:SS UTC`
+pub fn format_timestamp(timestamp: u64) -> String {
+    let days = (timestamp / 86400) as i64;
+    let rem = timestamp % 86400;
+
+    // 由天数推算公历日期
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
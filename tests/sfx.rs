@@ -0,0 +1,49 @@
+use std::fs;
+use std::process::Command;
+
+/// 测试自解压程序：以存根生成自解压程序，运行后释放资源并执行命令
+#[test]
+fn test_sfx_extracts_resources() {
+    let test_dir = std::env::temp_dir().join("appender_test_sfx");
+    let _ = fs::remove_dir_all(&test_dir);
+    fs::create_dir_all(&test_dir).unwrap();
+    let readme = test_dir.join("readme.txt");
+    let payload = test_dir.join("payload.bin");
+    fs::write(&readme, b"hello").unwrap();
+    let data: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+    fs::write(&payload, &data).unwrap();
+
+    let installer = test_dir.join("installer");
+    let status = Command::new(env!("CARGO_BIN_EXE_Appender"))
+        .arg("sfx")
+        .arg(&installer)
+        .arg(&readme)
+        .arg(&payload)
+        .arg("--stub")
+        .arg(env!("CARGO_BIN_EXE_appender-sfx"))
+        .args(["--run", "echo done > ran.txt", "-q"])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    // 释放到运行时指定的目录
+    let output = Command::new(&installer)
+        .arg("extracted")
+        .current_dir(&test_dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let extracted = test_dir.join("extracted");
+    assert_eq!(fs::read(extracted.join("readme.txt")).unwrap(), b"hello");
+    assert_eq!(fs::read(extracted.join("payload.bin")).unwrap(), data);
+    assert!(extracted.join("ran.txt").exists());
+
+    // 没有附加资源的存根无法释放
+    let output = Command::new(env!("CARGO_BIN_EXE_appender-sfx"))
+        .current_dir(&test_dir)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+
+    fs::remove_dir_all(&test_dir).unwrap();
+}